@@ -0,0 +1,112 @@
+// grpc-service/src/auth.rs
+// Bearer-token authentication for every gRPC call
+
+use std::sync::Arc;
+
+use shared::auth::verify_bearer;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use crate::error::to_status;
+
+/// ID of the caller, placed in request extensions by [`AuthInterceptor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticatedUser(pub i64);
+
+/// Require `authorization: Bearer <token>` metadata on every call.
+///
+/// Tokens are checked with the same `shared::auth` code as the web
+/// service's `with_auth` filter, so one token works on both transports.
+/// Calls without a valid token fail with `Unauthenticated` before they
+/// reach the service.
+#[derive(Debug, Clone)]
+pub struct AuthInterceptor {
+    secret: Arc<str>,
+}
+
+impl AuthInterceptor {
+    /// Verify tokens signed with `secret`.
+    pub fn new(secret: Arc<str>) -> Self {
+        Self { secret }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let header = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok());
+        let user_id = verify_bearer(header, &self.secret).map_err(to_status)?;
+
+        request.extensions_mut().insert(AuthenticatedUser(user_id));
+        Ok(request)
+    }
+}
+
+/// The user the interceptor authenticated for this request.
+///
+/// Fails with `Unauthenticated` if the service was mounted without
+/// [`AuthInterceptor`].
+// `Status` is what every handler returns, so boxing it here buys nothing
+#[allow(clippy::result_large_err)]
+pub fn current_user<T>(request: &Request<T>) -> Result<i64, Status> {
+    request
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .map(|user| user.0)
+        .ok_or_else(|| Status::unauthenticated("Missing authentication"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use shared::auth::issue_token;
+    use tonic::Code;
+
+    const SECRET: &str = "test-secret";
+
+    #[allow(clippy::result_large_err)]
+    fn intercept(authorization: Option<&str>) -> Result<Request<()>, Status> {
+        let mut request = Request::new(());
+        if let Some(value) = authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", value.parse().unwrap());
+        }
+        AuthInterceptor::new(Arc::from(SECRET)).call(request)
+    }
+
+    #[test]
+    fn test_valid_token_sets_user() {
+        let token = issue_token(7, SECRET, Duration::hours(1)).unwrap();
+
+        let request = intercept(Some(&format!("Bearer {}", token))).unwrap();
+
+        assert_eq!(current_user(&request).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_missing_or_invalid_metadata_is_unauthenticated() {
+        let expired = issue_token(7, SECRET, Duration::minutes(-5)).unwrap();
+        let wrong_secret = issue_token(7, "other", Duration::hours(1)).unwrap();
+
+        for authorization in [
+            None,
+            Some("Bearer garbage".to_string()),
+            Some(format!("Bearer {}", expired)),
+            Some(format!("Bearer {}", wrong_secret)),
+            Some(expired.clone()),
+        ] {
+            let status = intercept(authorization.as_deref()).unwrap_err();
+            assert_eq!(status.code(), Code::Unauthenticated, "{:?}", authorization);
+        }
+
+        // A request that never went through the interceptor has no user
+        assert_eq!(
+            current_user(&Request::new(())).unwrap_err().code(),
+            Code::Unauthenticated
+        );
+    }
+}
@@ -0,0 +1,86 @@
+// grpc-service/src/error.rs
+// Maps shared::AppError onto gRPC status codes
+
+use shared::AppError;
+use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, StatusExt};
+use tracing::error;
+
+/// Convert an application error into a gRPC `Status`.
+///
+/// Internal errors are logged and replaced with a generic message so
+/// database details never reach clients. Failures on several fields carry
+/// a `google.rpc.BadRequest` detail with one field violation each, so
+/// clients can show every message next to its field.
+pub fn to_status(err: AppError) -> Status {
+    if err.is_not_found() {
+        Status::not_found(err.to_string())
+    } else if let AppError::ValidationMany(errors) = &err {
+        let mut details = ErrorDetails::new();
+        for (field, message) in errors.iter() {
+            details.add_bad_request_violation(field, message);
+        }
+        Status::with_error_details(Code::InvalidArgument, err.to_string(), details)
+    } else if err.is_validation() {
+        Status::invalid_argument(err.to_string())
+    } else if err.is_auth() {
+        Status::unauthenticated(err.to_string())
+    } else if matches!(err, AppError::Forbidden(_)) {
+        Status::permission_denied(err.to_string())
+    } else if err.is_conflict() {
+        Status::already_exists(err.to_string())
+    } else if matches!(err, AppError::RateLimited(_)) {
+        Status::resource_exhausted(err.to_string())
+    } else if matches!(err, AppError::Timeout(_)) {
+        Status::deadline_exceeded(err.to_string())
+    } else if matches!(err, AppError::ReadOnly) || err.is_pool_timeout() {
+        // Same client-facing message as the HTTP API
+        Status::unavailable(err.to_response().1.message)
+    } else {
+        error!("Request failed: {}", err);
+        Status::internal("Internal server error")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_status_codes() {
+        assert_eq!(to_status(AppError::TaskNotFound(1)).code(), Code::NotFound);
+        assert_eq!(
+            to_status(AppError::NotFound {
+                entity: "Project",
+                id: 5
+            })
+            .code(),
+            Code::NotFound
+        );
+        assert_eq!(
+            to_status(AppError::Validation("bad".into())).code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            to_status(AppError::Conflict("email already exists".into())).code(),
+            Code::AlreadyExists
+        );
+        assert_eq!(
+            to_status(AppError::Timeout(std::time::Duration::from_secs(1))).code(),
+            Code::DeadlineExceeded
+        );
+        assert_eq!(
+            to_status(AppError::Forbidden("not yours".into())).code(),
+            Code::PermissionDenied
+        );
+        assert_eq!(to_status(AppError::ReadOnly).code(), Code::Unavailable);
+        assert_eq!(
+            to_status(AppError::Database(sqlx::Error::PoolTimedOut)).code(),
+            Code::Unavailable
+        );
+        assert_eq!(
+            to_status(AppError::Internal("boom".into())).code(),
+            Code::Internal
+        );
+    }
+}
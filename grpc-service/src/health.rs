@@ -0,0 +1,132 @@
+// grpc-service/src/health.rs
+// Standard grpc.health.v1 health checks, backed by the database check
+//
+// Kubernetes and gRPC-aware load balancers probe Health/Check (or keep a
+// Health/Watch stream open) instead of an HTTP /health route. The health
+// service is added next to TaskService without the auth interceptor, since
+// probes don't carry tokens.
+
+use std::time::Duration;
+
+use shared::db::check_health;
+use shared::proto::task_service_server::TaskServiceServer;
+use shared::DbPool;
+use tokio::task::JoinHandle;
+use tonic::server::NamedService;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::warn;
+
+use crate::service::TaskServiceImpl;
+
+/// How often the database is checked to refresh the reported status.
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Check the database once and report the result, both for the server as
+/// a whole (the empty service name) and for `TaskService`.
+///
+/// Returns the status reported.
+pub async fn update_health(pool: &DbPool, reporter: &mut HealthReporter) -> ServingStatus {
+    let status = if check_health(pool).await {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    };
+
+    reporter.set_service_status("", status).await;
+    reporter
+        .set_service_status(TaskServiceServer::<TaskServiceImpl>::NAME, status)
+        .await;
+    status
+}
+
+/// Re-check the database every `interval` for as long as the server runs.
+///
+/// `Health/Watch` streams only send changes, so flapping is logged here
+/// rather than on every tick.
+pub fn spawn_health_updater(
+    pool: DbPool,
+    mut reporter: HealthReporter,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        let mut last = ServingStatus::Serving;
+
+        loop {
+            ticks.tick().await;
+            let status = update_health(&pool, &mut reporter).await;
+            if status != last {
+                warn!("gRPC health status changed to {:?}", status);
+                last = status;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::db::create_test_pool;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::{Channel, Server};
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    /// Serve the health service from `health_reporter()` on an ephemeral
+    /// port, as main.rs does, and connect to it.
+    async fn spawn_health() -> (HealthReporter, HealthClient<Channel>) {
+        let (reporter, service) = tonic_health::server::health_reporter();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming(incoming),
+        );
+
+        // tonic-health's generated client has no `connect` shortcut
+        let channel = Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        (reporter, HealthClient::new(channel))
+    }
+
+    async fn check(client: &mut HealthClient<Channel>, name: &str) -> i32 {
+        client
+            .check(HealthCheckRequest {
+                service: name.to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .status
+    }
+
+    #[tokio::test]
+    async fn test_reports_serving_against_live_pool() {
+        let pool = create_test_pool().await.unwrap();
+        let (mut reporter, mut client) = spawn_health().await;
+
+        assert_eq!(
+            update_health(&pool, &mut reporter).await,
+            ServingStatus::Serving
+        );
+        let serving = tonic_health::pb::health_check_response::ServingStatus::Serving as i32;
+        assert_eq!(check(&mut client, "").await, serving);
+        assert_eq!(
+            check(&mut client, TaskServiceServer::<TaskServiceImpl>::NAME).await,
+            serving
+        );
+
+        pool.close().await;
+        assert_eq!(
+            update_health(&pool, &mut reporter).await,
+            ServingStatus::NotServing
+        );
+        assert_ne!(check(&mut client, "").await, serving);
+    }
+}
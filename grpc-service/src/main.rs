@@ -2,22 +2,42 @@
 // Entry point for the gRPC backend service
 
 // These are like Python's imports, but checked at compile time
-use tracing::{info, Level};
-use tracing_subscriber;
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use shared::db::{warmup, ShutdownCause};
+use shared::logging::init_logging;
+use shared::proto::task_service_server::TaskServiceServer;
+use shared::{create_pool, run_migrations, Config};
+use tonic::transport::Server;
+use tracing::{info, warn};
+
+mod auth;
+mod error;
+mod health;
+mod service;
+
+use auth::AuthInterceptor;
+use service::TaskServiceImpl;
+
+/// Log filter used when RUST_LOG isn't set
+const DEFAULT_LOG_FILTER: &str = "info";
 
 // The #[tokio::main] macro transforms our async main into a regular main
 // It sets up the Tokio async runtime for us
 // Python equivalent: asyncio.run() but happens automatically
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    // Read settings from the environment (DATABASE_URL, GRPC_PORT, JWT_SECRET)
+    // The ? operator turns a bad value (e.g. GRPC_PORT=abc) into a startup error
+    let config = Config::from_env()?;
+
     // Initialize structured logging
-    // tracing_subscriber sets up log formatting and filtering
+    // LOG_FORMAT picks pretty or JSON lines; RUST_LOG sets the levels
+    // (INFO and above when unset)
     // Like Python's logging.basicConfig() but more powerful
-    tracing_subscriber::fmt()
-        .with_target(false) // Don't show module paths in logs
-        .with_level(true) // Show log level (INFO, WARN, etc.)
-        .with_max_level(Level::INFO) // Only show INFO and above
-        .init();
+    init_logging(config.log_format, DEFAULT_LOG_FILTER);
 
     // Log startup message
     // info! is a macro (ends with !) that logs at INFO level
@@ -25,33 +45,63 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("gRPC Service starting...");
     info!("Version: {}", env!("CARGO_PKG_VERSION"));
 
-    // In Phase 2, we'll:
-    // 1. Load configuration from environment variables
-    // 2. Set up database connection pool
-    // 3. Start gRPC server on port 50051
-    // 4. Handle graceful shutdown on SIGTERM/SIGINT
-    info!("⚠️  gRPC server not yet implemented (Phase 2)");
-    info!("🎯 Next steps:");
-    info!("   - Define .proto files for task service");
-    info!("   - Generate Rust code with tonic-build");
-    info!("   - Implement TaskService trait");
-    info!("   - Start server on 0.0.0.0:50051");
-
-    // Simulate a running service for now
-    // tokio::time::sleep is like asyncio.sleep() in Python
-    info!("💤 Service running in placeholder mode (press Ctrl+C to stop)");
-
-    // Create a channel to wait for Ctrl+C signal
-    // This is async-safe signal handling
-    tokio::signal::ctrl_c()
-        .await
-        .expect("Failed to listen for Ctrl+C");
+    // Same secret as the web service, so its tokens work here too; refuse
+    // to start without one unless ALLOW_DEV_JWT_SECRET allows the fallback
+    let jwt_secret: Arc<str> = config.signing_secret()?.into();
+    if config.jwt_secret.is_none() {
+        warn!("⚠️  JWT_SECRET not set, using the insecure development secret");
+    }
+
+    // One limit for every repository call in the process
+    shared::db::set_query_timeout(std::time::Duration::from_secs(config.query_timeout_secs));
 
-    info!("🛑 Received shutdown signal, cleaning up...");
-    info!("👋 gRPC service stopped gracefully");
+    // Connect to the database and make sure the schema is current
+    let pool = create_pool(&config.database_url).await?;
+    run_migrations(&pool).await?;
+    // Open the whole pool now rather than during the first requests
+    warmup(&pool, pool.options().get_max_connections()).await?;
+    info!("🗄️  Database ready at {}", config.database_url);
+
+    // READ_ONLY=true starts with writes blocked; SIGUSR1 toggles it
+    shared::db::set_read_only(config.read_only);
+    #[cfg(unix)]
+    shared::db::spawn_read_only_toggle()?;
+    if config.read_only {
+        warn!("🔒 Read-only mode: writes are blocked until SIGUSR1");
+    }
+
+    // 0.0.0.0 means listen on all network interfaces
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.grpc_port));
+
+    // Every call must carry `authorization: Bearer <token>` metadata
+    let service = TaskServiceServer::with_interceptor(
+        TaskServiceImpl::new(pool.clone()),
+        AuthInterceptor::new(jwt_secret),
+    );
+
+    // grpc.health.v1 status, refreshed from the database in the background
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    health::spawn_health_updater(pool.clone(), health_reporter, health::HEALTH_CHECK_INTERVAL);
+
+    info!("🚀 gRPC server listening on {}", addr);
+    info!("   Press Ctrl+C to stop");
+
+    // serve_with_shutdown stops accepting requests once the future resolves
+    // and lets in-flight requests finish
+    // It only returns Ok after the signal; an Err means the server died
+    let cause = match Server::builder()
+        .add_service(health_service)
+        .add_service(service)
+        .serve_with_shutdown(addr, shared::db::shutdown_signal())
+        .await
+    {
+        Ok(()) => ShutdownCause::Signal,
+        Err(err) => ShutdownCause::Fatal(format!("gRPC server failed: {}", err)),
+    };
 
+    // Log why we stopped and close the pool; a fatal cause exits non-zero
+    // so orchestrators see a crash
     // Result<T, E> is Rust's way of handling errors
-    // Ok(()) means success with no return value
-    // Python equivalent: return None or just return
-    Ok(())
+    // Ok(code) means startup worked, and code is the process exit status
+    Ok(shared::db::finish_shutdown(&pool, "gRPC service", cause).await)
 }
@@ -0,0 +1,369 @@
+// grpc-service/src/service.rs
+// TaskService implementation backed by the shared repository layer
+
+use std::pin::Pin;
+
+use shared::constants::MAX_PAGE_SIZE;
+use shared::proto::task_service_server::TaskService;
+use shared::proto::{
+    self, CreateTaskRequest, DeleteTaskRequest, DeleteTaskResponse, GetTaskRequest,
+    ListTasksRequest, ListTasksResponse, UpdateTaskRequest,
+};
+use shared::{AppError, CreateTask, DbPool, Task, TaskRepository, UpdateTask};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+
+use crate::auth::current_user;
+use crate::error::to_status;
+
+/// How many tasks `stream_tasks` buffers ahead of a slow client.
+const STREAM_BUFFER: usize = 32;
+
+/// Stream of tasks sent by `stream_tasks`.
+pub type TaskStream = Pin<Box<dyn Stream<Item = Result<proto::Task, Status>> + Send>>;
+
+/// gRPC handler for task operations.
+///
+/// Holds a clone of the connection pool; tonic may call methods
+/// concurrently, and the pool handles that for us.
+///
+/// Must be mounted behind [`AuthInterceptor`](crate::auth::AuthInterceptor):
+/// every method only ever sees the authenticated user's own tasks.
+pub struct TaskServiceImpl {
+    pool: DbPool,
+}
+
+impl TaskServiceImpl {
+    /// Create a service that runs queries against `pool`.
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Load a task the caller owns.
+    ///
+    /// Same rule as the web service: `NotFound` for a task that doesn't
+    /// exist and `PermissionDenied` for someone else's.
+    async fn find_owned_task(&self, id: i64, user_id: i64) -> Result<Task, Status> {
+        let task = TaskRepository::find_by_id(&self.pool, id)
+            .await
+            .map_err(to_status)?;
+
+        if task.user_id != user_id {
+            return Err(to_status(AppError::Forbidden(format!(
+                "Task {} belongs to another user",
+                id
+            ))));
+        }
+
+        Ok(task)
+    }
+}
+
+// #[tonic::async_trait] lets us write async fns in the trait impl
+#[tonic::async_trait]
+impl TaskService for TaskServiceImpl {
+    async fn create_task(
+        &self,
+        request: Request<CreateTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let user_id = current_user(&request)?;
+        let mut task = CreateTask::try_from(request.into_inner()).map_err(to_status)?;
+        // Never trust an owner supplied in the request
+        task.user_id = user_id;
+        task.validate_new()
+            .map_err(|errors| to_status(errors.into()))?;
+        let task = TaskRepository::create(&self.pool, task)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn get_task(
+        &self,
+        request: Request<GetTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let user_id = current_user(&request)?;
+        let task = self
+            .find_owned_task(request.into_inner().id, user_id)
+            .await?;
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn list_tasks(
+        &self,
+        request: Request<ListTasksRequest>,
+    ) -> Result<Response<ListTasksResponse>, Status> {
+        let user_id = current_user(&request)?;
+        let tasks = TaskRepository::find_by_user(&self.pool, user_id)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(ListTasksResponse {
+            tasks: tasks.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    type StreamTasksStream = TaskStream;
+
+    /// Send a user's tasks one at a time, newest first.
+    ///
+    /// A background task reads pages of `MAX_PAGE_SIZE` rows with
+    /// `find_by_user_after` and feeds them through a bounded channel, so at
+    /// most one page is held in memory. If the client cancels, the channel
+    /// closes, the next send fails, and paging stops without running
+    /// another query.
+    async fn stream_tasks(
+        &self,
+        request: Request<ListTasksRequest>,
+    ) -> Result<Response<Self::StreamTasksStream>, Status> {
+        let user_id = current_user(&request)?;
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            let mut cursor = None;
+            loop {
+                let page =
+                    match TaskRepository::find_by_user_after(&pool, user_id, cursor, MAX_PAGE_SIZE)
+                        .await
+                    {
+                        Ok(page) => page,
+                        Err(err) => {
+                            let _ = tx.send(Err(to_status(err))).await;
+                            return;
+                        }
+                    };
+
+                let last_page = (page.len() as i64) < MAX_PAGE_SIZE;
+                cursor = page.last().map(|t| t.id);
+
+                for task in page {
+                    if tx.send(Ok(task.into())).await.is_err() {
+                        // Client went away
+                        return;
+                    }
+                }
+
+                if last_page {
+                    return;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn update_task(
+        &self,
+        request: Request<UpdateTaskRequest>,
+    ) -> Result<Response<proto::Task>, Status> {
+        let user_id = current_user(&request)?;
+        let request = request.into_inner();
+        let id = request.id;
+        let update = UpdateTask::try_from(request).map_err(to_status)?;
+        self.find_owned_task(id, user_id).await?;
+        let task = TaskRepository::update(&self.pool, id, user_id, update)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(task.into()))
+    }
+
+    async fn delete_task(
+        &self,
+        request: Request<DeleteTaskRequest>,
+    ) -> Result<Response<DeleteTaskResponse>, Status> {
+        let user_id = current_user(&request)?;
+        let id = request.into_inner().id;
+        self.find_owned_task(id, user_id).await?;
+        TaskRepository::delete(&self.pool, id, user_id)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(DeleteTaskResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthInterceptor;
+    use chrono::Duration;
+    use shared::auth::issue_token;
+    use shared::constants::MAX_DESCRIPTION_LENGTH;
+    use shared::db::{create_test_pool, create_test_task, create_test_user};
+    use shared::proto::task_service_client::TaskServiceClient;
+    use shared::proto::task_service_server::TaskServiceServer;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::{Channel, Server};
+    use tonic_types::StatusExt;
+
+    const SECRET: &str = "test-secret";
+
+    /// Wrap `message` in a request carrying a bearer token for `user_id`.
+    fn authed<T>(message: T, user_id: i64) -> Request<T> {
+        let token = issue_token(user_id, SECRET, Duration::hours(1)).unwrap();
+        let mut request = Request::new(message);
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        request
+    }
+
+    /// Serve `pool` on an ephemeral port in the background and connect to it.
+    async fn spawn_client(pool: DbPool) -> TaskServiceClient<Channel> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(TaskServiceServer::with_interceptor(
+                    TaskServiceImpl::new(pool),
+                    AuthInterceptor::new(SECRET.into()),
+                ))
+                .serve_with_incoming(incoming),
+        );
+
+        TaskServiceClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_then_get_round_trip() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "tester").await.unwrap();
+        let mut client = spawn_client(pool).await;
+
+        let created = client
+            .create_task(authed(
+                CreateTaskRequest {
+                    title: "Write proto".to_string(),
+                    description: "Define the task service".to_string(),
+                    status: proto::TaskStatus::InProgress as i32,
+                    priority: proto::TaskPriority::High as i32,
+                    due_date: None,
+                    user_id,
+                },
+                user_id,
+            ))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let fetched = client
+            .get_task(authed(GetTaskRequest { id: created.id }, user_id))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(fetched, created);
+        assert_eq!(fetched.title, "Write proto");
+        assert_eq!(fetched.status, proto::TaskStatus::InProgress as i32);
+
+        let missing = client
+            .get_task(authed(GetTaskRequest { id: 9999 }, user_id))
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_create_reports_each_invalid_field() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "tester").await.unwrap();
+        let mut client = spawn_client(pool).await;
+
+        let status = client
+            .create_task(authed(
+                CreateTaskRequest {
+                    title: "   ".to_string(),
+                    description: "x".repeat(MAX_DESCRIPTION_LENGTH + 1),
+                    status: proto::TaskStatus::Todo as i32,
+                    priority: proto::TaskPriority::Low as i32,
+                    due_date: None,
+                    user_id,
+                },
+                user_id,
+            ))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let bad_request = status.get_details_bad_request().unwrap();
+        let fields: Vec<&str> = bad_request
+            .field_violations
+            .iter()
+            .map(|violation| violation.field.as_str())
+            .collect();
+        assert_eq!(fields, ["title", "description"]);
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_scoped_to_the_caller() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let bobs = create_test_task(&pool, bob, "Bob's task").await.unwrap();
+        let mut client = spawn_client(pool).await;
+
+        // Someone else's task is refused, not hidden
+        let err = client
+            .get_task(authed(GetTaskRequest { id: bobs.id }, alice))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+        // The user_id in the request body is ignored
+        let listed = client
+            .list_tasks(authed(ListTasksRequest { user_id: bob }, alice))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(listed.tasks.is_empty());
+
+        let err = client
+            .list_tasks(ListTasksRequest { user_id: bob })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[tokio::test]
+    async fn test_stream_tasks_yields_every_task() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "tester").await.unwrap();
+        let other = create_test_user(&pool, "other").await.unwrap();
+
+        // More than two pages, so paging has to continue past the first
+        let total = MAX_PAGE_SIZE * 2 + 5;
+        for i in 0..total {
+            create_test_task(&pool, user_id, &format!("Task {}", i))
+                .await
+                .unwrap();
+        }
+        create_test_task(&pool, other, "Not mine").await.unwrap();
+
+        let mut client = spawn_client(pool).await;
+        let mut stream = client
+            .stream_tasks(authed(ListTasksRequest { user_id }, user_id))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let mut ids = Vec::new();
+        while let Some(task) = stream.message().await.unwrap() {
+            assert_eq!(task.user_id, user_id);
+            ids.push(task.id);
+        }
+
+        assert_eq!(ids.len() as i64, total);
+        // Newest first, no repeats across page boundaries
+        assert!(ids.windows(2).all(|w| w[0] > w[1]));
+    }
+}
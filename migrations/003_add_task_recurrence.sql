@@ -0,0 +1,8 @@
+-- Add recurrence to tasks
+-- Migration: 003_add_task_recurrence
+-- Purpose: Let a task repeat on a schedule
+
+-- Recurrence rule: 'daily', 'weekly', 'monthly', or 'every_<n>_days'
+-- NULL means the task does not repeat
+-- Parsed by the RecurrenceRule enum in Rust
+ALTER TABLE tasks ADD COLUMN recurrence TEXT;
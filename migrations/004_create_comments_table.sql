@@ -0,0 +1,26 @@
+-- Create comments table
+-- Migration: 004_create_comments_table
+-- Purpose: Discussion threads on tasks
+
+CREATE TABLE IF NOT EXISTS comments (
+    id INTEGER PRIMARY KEY NOT NULL,
+
+    -- Task being discussed; comments go away with their task
+    task_id INTEGER NOT NULL,
+
+    -- Author of the comment
+    user_id INTEGER NOT NULL,
+
+    -- Comment text (blank comments are rejected in Rust too)
+    body TEXT NOT NULL,
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+
+    CHECK (length(body) > 0)
+);
+
+-- A task's thread is always read in order
+CREATE INDEX IF NOT EXISTS idx_comments_task_created ON comments(task_id, created_at);
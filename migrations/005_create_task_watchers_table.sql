@@ -0,0 +1,20 @@
+-- Create task watchers table
+-- Migration: 005_create_task_watchers_table
+-- Purpose: Users who follow a task's changes, separate from its owner
+
+CREATE TABLE IF NOT EXISTS task_watchers (
+    task_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- One row per watcher; also makes adding a watcher idempotent
+    PRIMARY KEY (task_id, user_id),
+
+    -- Watching ends when either the task or the user goes away
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- "What am I watching?" lookups
+CREATE INDEX IF NOT EXISTS idx_task_watchers_user ON task_watchers(user_id);
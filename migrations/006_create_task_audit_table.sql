@@ -0,0 +1,24 @@
+-- Create task audit table
+-- Migration: 006_create_task_audit_table
+-- Purpose: Trail of who created, changed, or deleted each task
+
+CREATE TABLE IF NOT EXISTS task_audit (
+    id INTEGER PRIMARY KEY NOT NULL,
+
+    -- No foreign keys: the trail has to outlive the task (and user) it describes
+    task_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+
+    -- 'create', 'update', or 'delete'
+    action TEXT NOT NULL CHECK (action IN ('create', 'update', 'delete')),
+
+    -- JSON objects holding only the fields that changed
+    -- NULL for the side that didn't exist (old on create, new on delete)
+    old_value TEXT,
+    new_value TEXT,
+
+    at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- History is always read per task, in order
+CREATE INDEX IF NOT EXISTS idx_task_audit_task_at ON task_audit(task_id, at);
@@ -0,0 +1,7 @@
+-- Add time tracking to tasks
+-- Migration: 007_add_task_time_tracking
+-- Purpose: Estimated and actual effort per task, in minutes
+
+-- NULL means "not estimated" / "no time logged yet"
+ALTER TABLE tasks ADD COLUMN estimated_minutes INTEGER CHECK (estimated_minutes >= 0);
+ALTER TABLE tasks ADD COLUMN actual_minutes INTEGER CHECK (actual_minutes >= 0);
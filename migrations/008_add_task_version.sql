@@ -0,0 +1,7 @@
+-- Add a version counter to tasks
+-- Migration: 008_add_task_version
+-- Purpose: Optimistic concurrency control for task updates
+
+-- Bumped on every write; an update can require the version it last read,
+-- so a stale client gets a conflict instead of overwriting newer changes
+ALTER TABLE tasks ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
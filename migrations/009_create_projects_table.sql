@@ -0,0 +1,31 @@
+-- Create projects table
+-- Migration: 009_create_projects_table
+-- Purpose: Group a user's tasks into projects
+
+CREATE TABLE IF NOT EXISTS projects (
+    id INTEGER PRIMARY KEY NOT NULL,
+
+    -- Owner of the project; projects go away with their user
+    user_id INTEGER NOT NULL,
+
+    name TEXT NOT NULL,
+
+    -- Display color as '#rrggbb' (checked in Rust)
+    color TEXT NOT NULL DEFAULT '#808080',
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+
+    CHECK (length(name) > 0)
+);
+
+-- "My projects" lookups
+CREATE INDEX IF NOT EXISTS idx_projects_user ON projects(user_id);
+
+-- NULL means the task isn't in a project
+-- Deleting a project keeps its tasks and just takes them out of it
+ALTER TABLE tasks ADD COLUMN project_id INTEGER REFERENCES projects(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_project ON tasks(project_id) WHERE project_id IS NOT NULL;
@@ -0,0 +1,26 @@
+-- Create task dependencies table
+-- Migration: 010_create_task_dependencies_table
+-- Purpose: "Task A is blocked by task B" relationships
+
+CREATE TABLE IF NOT EXISTS task_dependencies (
+    -- The blocked task
+    task_id INTEGER NOT NULL,
+
+    -- The task it waits on
+    depends_on_id INTEGER NOT NULL,
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- One row per pair; also makes adding a dependency idempotent
+    PRIMARY KEY (task_id, depends_on_id),
+
+    -- Deleting either task drops the dependency
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (depends_on_id) REFERENCES tasks(id) ON DELETE CASCADE,
+
+    -- Longer loops are rejected in Rust; this catches the trivial one
+    CHECK (task_id != depends_on_id)
+);
+
+-- "What does this task block?" lookups walk the graph backwards
+CREATE INDEX IF NOT EXISTS idx_task_dependencies_depends_on ON task_dependencies(depends_on_id);
@@ -0,0 +1,21 @@
+-- Add email verification
+-- Migration: 011_add_email_verification
+-- Purpose: Confirm users own the email address they registered with
+
+-- Flipped to 1 once the user follows a verification link
+ALTER TABLE users ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS email_verification_tokens (
+    -- Random, URL-safe, and single-use: verifying deletes the row
+    token TEXT PRIMARY KEY NOT NULL,
+
+    -- Tokens go away with their user
+    user_id INTEGER NOT NULL,
+
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_verification_tokens_user ON email_verification_tokens(user_id);
@@ -0,0 +1,9 @@
+-- Add login lockout tracking
+-- Migration: 012_add_login_lockout
+-- Purpose: Lock accounts for a while after repeated failed logins
+
+-- Wrong passwords since the last successful login (or the last lockout)
+ALTER TABLE users ADD COLUMN failed_attempts INTEGER NOT NULL DEFAULT 0;
+
+-- Logins are refused until this time; NULL when not locked
+ALTER TABLE users ADD COLUMN locked_until TEXT;
@@ -0,0 +1,6 @@
+-- Add a timezone to users
+-- Migration: 013_add_user_timezone
+-- Purpose: Work out "today" in the user's local time, not UTC
+
+-- IANA name like 'Europe/Paris' (checked in Rust); NULL means UTC
+ALTER TABLE users ADD COLUMN timezone TEXT;
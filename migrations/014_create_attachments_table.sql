@@ -0,0 +1,29 @@
+-- Create attachments table
+-- Migration: 014_create_attachments_table
+-- Purpose: Metadata for files attached to tasks (the bytes live in object storage)
+
+CREATE TABLE IF NOT EXISTS attachments (
+    id INTEGER PRIMARY KEY NOT NULL,
+
+    -- Task the file is attached to; attachments go away with their task
+    task_id INTEGER NOT NULL,
+
+    -- Original file name, without any directory (checked in Rust)
+    filename TEXT NOT NULL,
+
+    content_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+
+    -- Where the bytes are in object storage
+    storage_key TEXT NOT NULL UNIQUE,
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+
+    CHECK (length(filename) > 0),
+    CHECK (size_bytes >= 0)
+);
+
+-- A task's attachments are listed together
+CREATE INDEX IF NOT EXISTS idx_attachments_task ON attachments(task_id);
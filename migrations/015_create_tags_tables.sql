@@ -0,0 +1,31 @@
+-- Create tags tables
+-- Migration: 015_create_tags_tables
+-- Purpose: Free-form labels on tasks, many-to-many
+
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY NOT NULL,
+
+    -- Stored normalized (trimmed, lowercase) so "Urgent" and "urgent" are one tag
+    name TEXT NOT NULL UNIQUE,
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    CHECK (length(name) > 0)
+);
+
+CREATE TABLE IF NOT EXISTS task_tags (
+    task_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- A task carries a tag at most once
+    PRIMARY KEY (task_id, tag_id),
+
+    -- Deleting a task or a tag drops its associations
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+-- "Which tasks have this tag?" lookups
+CREATE INDEX IF NOT EXISTS idx_task_tags_tag ON task_tags(tag_id);
@@ -0,0 +1,10 @@
+-- Add a manual sort position to tasks
+-- Migration: 016_add_task_position
+-- Purpose: Let users order their tasks by hand, independent of created_at
+
+-- Set by TaskRepository::reorder, starting at 1; new tasks get 0, which
+-- puts them at the top until the list is reordered
+ALTER TABLE tasks ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+-- The ordered list reads a user's tasks by position
+CREATE INDEX IF NOT EXISTS idx_tasks_user_position ON tasks(user_id, position);
@@ -0,0 +1,14 @@
+-- Add composite indexes for the task listing queries
+-- Migration: 017_add_task_listing_indexes
+-- Purpose: Serve a user's newest-first listing straight from an index
+
+-- find_by_user_and_status and status filters (already created in 002;
+-- repeated so this file lists every index the listings rely on)
+CREATE INDEX IF NOT EXISTS idx_tasks_user_status ON tasks(user_id, status);
+
+-- find_by_user and the default listing: WHERE user_id = ? ORDER BY created_at DESC
+-- Walking this index backwards yields the rows already sorted
+CREATE INDEX IF NOT EXISTS idx_tasks_user_created ON tasks(user_id, created_at);
+
+-- Both composites start with user_id, so the single-column index is redundant
+DROP INDEX IF EXISTS idx_tasks_user_id;
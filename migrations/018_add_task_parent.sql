@@ -0,0 +1,11 @@
+-- Add subtasks: a task may sit under a parent task
+-- Migration: 018_add_task_parent
+-- Purpose: Let tasks form a tree, so completing a parent can complete its subtasks
+
+-- Deleting a parent deletes its subtasks with it; a task can't be its own parent
+ALTER TABLE tasks ADD COLUMN parent_id INTEGER
+    REFERENCES tasks(id) ON DELETE CASCADE
+    CHECK (parent_id <> id);
+
+-- Walking the tree downwards looks up children by parent
+CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id);
@@ -0,0 +1,10 @@
+-- Store email verification tokens hashed
+-- Migration: 019_hash_verification_tokens
+-- Purpose: Keep a leaked database from being usable to verify addresses
+
+-- Outstanding tokens were stored as-is and can't be converted (SQLite has
+-- no SHA-256), so they're dropped; users ask for a new link
+DELETE FROM email_verification_tokens;
+
+-- Now the hex SHA-256 of the token; the token itself is only ever emailed
+ALTER TABLE email_verification_tokens RENAME COLUMN token TO token_hash;
@@ -0,0 +1,14 @@
+// shared/build.rs
+// Compiles the .proto files into Rust code at build time.
+//
+// The generated server and client code lands in OUT_DIR and is pulled
+// into the crate by `tonic::include_proto!` in src/proto.rs.
+// Also tells cargo to rebuild when the embedded migrations change.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // sqlx::migrate! embeds the migration files; rebuild when they change
+    println!("cargo:rerun-if-changed=../migrations");
+
+    tonic_build::compile_protos("proto/task.proto")?;
+    Ok(())
+}
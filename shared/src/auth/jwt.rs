@@ -0,0 +1,141 @@
+//! JSON Web Token issuing and verification.
+//!
+//! Tokens are signed with HMAC-SHA256 using a shared secret. The `sub`
+//! claim carries the authenticated user's ID.
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// Claims stored inside an access token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// ID of the authenticated user
+    pub sub: i64,
+
+    /// Issued-at time (seconds since the Unix epoch)
+    pub iat: i64,
+
+    /// Expiry time (seconds since the Unix epoch)
+    pub exp: i64,
+}
+
+/// Issue a signed token for a user.
+///
+/// # Arguments
+/// * `user_id` - ID of the authenticated user (stored as `sub`)
+/// * `secret` - HMAC signing secret
+/// * `ttl` - How long the token stays valid
+///
+/// # Returns
+/// * `AppResult<String>` - Encoded JWT
+///
+/// # Errors
+/// * `AppError::Internal` - If the token cannot be encoded
+pub fn issue_token(user_id: i64, secret: &str, ttl: Duration) -> AppResult<String> {
+    let now = Utc::now();
+    let claims = Claims {
+        sub: user_id,
+        iat: now.timestamp(),
+        exp: (now + ttl).timestamp(),
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .map_err(|e| AppError::Internal(format!("Failed to issue token: {}", e)))
+}
+
+/// Verify a token's signature and expiry and return its claims.
+///
+/// # Arguments
+/// * `token` - Encoded JWT (without the `Bearer ` prefix)
+/// * `secret` - HMAC signing secret used to issue it
+///
+/// # Returns
+/// * `AppResult<Claims>` - Decoded claims
+///
+/// # Errors
+/// * `AppError::Unauthorized` - If the token is expired, malformed, or has a bad signature
+pub fn verify_token(token: &str, secret: &str) -> AppResult<Claims> {
+    decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map(|data| data.claims)
+    .map_err(|e| match e.kind() {
+        ErrorKind::ExpiredSignature => AppError::Unauthorized("Token expired".to_string()),
+        _ => AppError::Unauthorized("Invalid token".to_string()),
+    })
+}
+
+/// Verify an `Authorization` value of the form `Bearer <token>`.
+///
+/// Used by both the web filter and the gRPC interceptor, so the two
+/// transports accept exactly the same credentials.
+///
+/// # Arguments
+/// * `header` - The header (or metadata) value, if one was sent
+/// * `secret` - HMAC signing secret
+///
+/// # Returns
+/// * `AppResult<i64>` - The authenticated user's ID
+///
+/// # Errors
+/// * `AppError::Unauthorized` - If the value is missing, isn't a bearer
+///   token, or the token doesn't verify
+pub fn verify_bearer(header: Option<&str>, secret: &str) -> AppResult<i64> {
+    let header =
+        header.ok_or_else(|| AppError::Unauthorized("Missing Authorization header".to_string()))?;
+    let token = header
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| AppError::Unauthorized("Expected a Bearer token".to_string()))?;
+
+    Ok(verify_token(token.trim(), secret)?.sub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    #[test]
+    fn test_issue_and_verify_round_trip() {
+        let token = issue_token(42, SECRET, Duration::hours(1)).unwrap();
+        let claims = verify_token(&token, SECRET).unwrap();
+        assert_eq!(claims.sub, 42);
+        assert!(claims.exp > claims.iat);
+    }
+
+    #[test]
+    fn test_expired_token_rejected() {
+        // Well past the default 60s leeway
+        let token = issue_token(42, SECRET, Duration::minutes(-5)).unwrap();
+        let err = verify_token(&token, SECRET).unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(msg) if msg == "Token expired"));
+    }
+
+    #[test]
+    fn test_wrong_secret_rejected() {
+        let token = issue_token(42, SECRET, Duration::hours(1)).unwrap();
+        assert!(verify_token(&token, "other-secret").unwrap_err().is_auth());
+        assert!(verify_token("not.a.jwt", SECRET).unwrap_err().is_auth());
+    }
+
+    #[test]
+    fn test_verify_bearer() {
+        let token = issue_token(42, SECRET, Duration::hours(1)).unwrap();
+
+        let header = format!("Bearer {}", token);
+        assert_eq!(verify_bearer(Some(&header), SECRET).unwrap(), 42);
+        assert!(verify_bearer(None, SECRET).unwrap_err().is_auth());
+        assert!(verify_bearer(Some(&token), SECRET).unwrap_err().is_auth());
+    }
+}
@@ -0,0 +1,16 @@
+//! Authentication utilities shared by both services.
+//!
+//! - `jwt`: issuing and verifying signed access tokens
+//! - `password`: hashing and checking passwords with Argon2
+//! - `token`: random single-use tokens for links sent to users
+//!
+//! Keeping these here means the web and gRPC layers agree on token format
+//! and validation rules.
+
+pub mod jwt;
+pub mod password;
+pub mod token;
+
+pub use jwt::{issue_token, verify_bearer, verify_token, Claims};
+pub use password::{hash_password, verify_dummy_password, verify_password};
+pub use token::{hash_token, random_token};
@@ -0,0 +1,66 @@
+//! Password hashing with Argon2.
+//!
+//! Hashes are stored in PHC string format (`$argon2id$v=19$...`), which
+//! embeds the salt and parameters, so verifying needs nothing but the hash.
+
+use std::sync::OnceLock;
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+
+use crate::error::{AppError, AppResult};
+
+/// Hash a plain-text password with a fresh random salt.
+///
+/// # Errors
+/// * `AppError::Internal` - If hashing fails (should not happen in practice)
+pub fn hash_password(password: &str) -> AppResult<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))
+}
+
+/// Check a plain-text password against a stored hash.
+///
+/// Returns `false` for a wrong password; only a malformed hash is an error.
+///
+/// # Errors
+/// * `AppError::Internal` - If `hash` is not a valid PHC string
+pub fn verify_password(password: &str, hash: &str) -> AppResult<bool> {
+    let parsed = PasswordHash::new(hash)
+        .map_err(|e| AppError::Internal(format!("Invalid password hash: {}", e)))?;
+
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
+}
+
+/// Check `password` against a throwaway hash and discard the answer.
+///
+/// For logins naming a user that doesn't exist: running the same Argon2
+/// verification as for a real user keeps response times from revealing
+/// which usernames are taken.
+pub fn verify_dummy_password(password: &str) {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    let hash = DUMMY_HASH.get_or_init(|| hash_password("dummy password").unwrap_or_default());
+    let _ = verify_password(password, hash);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_then_verify() {
+        let hash = hash_password("correct horse").unwrap();
+
+        assert!(hash.starts_with("$argon2"));
+        assert!(verify_password("correct horse", &hash).unwrap());
+        assert!(!verify_password("wrong horse", &hash).unwrap());
+        assert!(verify_password("x", "not-a-hash").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+//! Random single-use tokens, e.g. for email verification links.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Bytes of randomness in each token.
+const TOKEN_BYTES: usize = 32;
+
+/// Generate an unguessable token that's safe to put in a URL as-is.
+///
+/// 256 bits from the OS's secure random source, hex-encoded.
+pub fn random_token() -> String {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The form a token is stored in: its SHA-256, hex-encoded.
+///
+/// Tokens are already random, so a plain hash (no salt or stretching)
+/// is enough to keep a leaked table from being usable.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_token_is_url_safe_and_unique() {
+        let token = random_token();
+
+        assert_eq!(token.len(), TOKEN_BYTES * 2);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, random_token());
+    }
+
+    #[test]
+    fn test_hash_token_is_stable_and_hides_the_token() {
+        let token = random_token();
+
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(hash_token(&token), token);
+        assert_eq!(
+            hash_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,621 @@
+//! Runtime configuration loaded from environment variables.
+//!
+//! Every setting has a default from [`crate::constants`], so both services
+//! start with no environment at all during development.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::constants;
+use crate::db::DatabaseUrl;
+use crate::error::{AppError, AppResult};
+use crate::logging::LogFormat;
+
+/// Settings shared by the web and gRPC services.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// SQLite connection URL (`DATABASE_URL`), checked when loaded
+    pub database_url: DatabaseUrl,
+
+    /// Port for the HTTP service (`WEB_PORT`)
+    pub web_port: u16,
+
+    /// Port for the gRPC service (`GRPC_PORT`)
+    pub grpc_port: u16,
+
+    /// Requests each client may make per minute (`RATE_LIMIT_PER_MINUTE`)
+    pub rate_limit_per_minute: u32,
+
+    /// Largest request body the HTTP service accepts, in bytes
+    /// (`MAX_BODY_BYTES`)
+    pub max_body_bytes: u64,
+
+    /// Secret for signing and verifying tokens (`JWT_SECRET`).
+    /// `None` when unset; see [`Config::signing_secret`].
+    pub jwt_secret: Option<String>,
+
+    /// Let the services fall back to [`constants::DEV_JWT_SECRET`] when
+    /// `JWT_SECRET` is unset (`ALLOW_DEV_JWT_SECRET`). Off by default, so a
+    /// forgotten secret stops startup instead of accepting forged tokens.
+    pub allow_dev_secret: bool,
+
+    /// Which other sites' pages may call the HTTP API
+    pub cors: CorsConfig,
+
+    /// When repeated wrong passwords lock an account
+    pub lockout: LockoutConfig,
+
+    /// PEM certificate chain for serving HTTPS (`TLS_CERT`).
+    /// Set together with `tls_key`; with neither, the web service serves
+    /// plain HTTP.
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key for `tls_cert` (`TLS_KEY`)
+    pub tls_key: Option<PathBuf>,
+
+    /// Where to POST due-task notifications; `None` when `WEBHOOK_URL`
+    /// is unset
+    pub webhook: Option<WebhookConfig>,
+
+    /// Start with writes blocked (`READ_ONLY`); see `db::read_only`
+    pub read_only: bool,
+
+    /// How long a repository call may run before it fails with
+    /// `AppError::Timeout` (`QUERY_TIMEOUT_SECS`); see
+    /// `db::set_query_timeout`
+    pub query_timeout_secs: u64,
+
+    /// `pretty` or `json` log lines (`LOG_FORMAT`); levels come from
+    /// `RUST_LOG`
+    pub log_format: LogFormat,
+}
+
+/// Account lockout policy for logins.
+///
+/// After `max_failed_logins` wrong passwords in a row, the next wrong one
+/// locks the account for `lockout_minutes`, during which even the right
+/// password is refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutConfig {
+    /// Wrong passwords allowed before locking (`LOGIN_MAX_FAILURES`)
+    pub max_failed_logins: u32,
+
+    /// How long a lock lasts (`LOGIN_LOCKOUT_MINUTES`)
+    pub lockout_minutes: u32,
+}
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        Self {
+            max_failed_logins: constants::MAX_FAILED_LOGINS,
+            lockout_minutes: constants::LOCKOUT_MINUTES,
+        }
+    }
+}
+
+/// Outgoing webhook for due-task notifications (see
+/// `notify::WebhookNotifier`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    /// `http` or `https` URL to POST to (`WEBHOOK_URL`)
+    pub url: String,
+
+    /// Time limit for each delivery attempt (`WEBHOOK_TIMEOUT_SECS`)
+    pub timeout_secs: u64,
+
+    /// Key for the `X-Signature-256` HMAC header (`WEBHOOK_SECRET`);
+    /// payloads go unsigned when `None`
+    pub secret: Option<String>,
+}
+
+/// Cross-origin (CORS) policy for the HTTP service.
+///
+/// Debug builds default to allowing any origin, so a frontend dev server
+/// on another port just works; release builds default to allowing none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsConfig {
+    /// Origins like `https://app.example.com` that may make cross-origin
+    /// requests, or just `*` for any (`CORS_ALLOWED_ORIGINS`, comma-separated)
+    pub allowed_origins: Vec<String>,
+
+    /// Methods allowed in cross-origin requests (`CORS_ALLOWED_METHODS`,
+    /// comma-separated)
+    pub allowed_methods: Vec<String>,
+
+    /// Whether cross-origin requests may carry cookies
+    /// (`CORS_ALLOW_CREDENTIALS`). Can't be combined with `*`.
+    pub allow_credentials: bool,
+}
+
+impl CorsConfig {
+    /// Whether every origin is allowed (`*`).
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        let allowed_origins = if cfg!(debug_assertions) {
+            vec!["*".to_string()]
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            allowed_origins,
+            allowed_methods: ["GET", "POST", "PUT", "DELETE"].map(String::from).to_vec(),
+            allow_credentials: false,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_url: DatabaseUrl::default(),
+            web_port: constants::WEB_PORT,
+            grpc_port: constants::GRPC_PORT,
+            rate_limit_per_minute: constants::RATE_LIMIT_PER_MINUTE,
+            max_body_bytes: constants::MAX_BODY_BYTES,
+            jwt_secret: None,
+            allow_dev_secret: false,
+            cors: CorsConfig::default(),
+            lockout: LockoutConfig::default(),
+            tls_cert: None,
+            tls_key: None,
+            webhook: None,
+            read_only: false,
+            query_timeout_secs: constants::QUERY_TIMEOUT_SECS,
+            log_format: LogFormat::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration from the process environment.
+    ///
+    /// # Errors
+    /// * `AppError::Internal` - If a variable is set but malformed
+    ///   (e.g. a non-numeric port)
+    /// * `AppError::Validation` - If `DATABASE_URL` isn't a supported
+    ///   database URL; see [`DatabaseUrl::parse`]
+    pub fn from_env() -> AppResult<Self> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// The secret to sign and verify tokens with.
+    ///
+    /// # Returns
+    /// * `AppResult<&str>` - `JWT_SECRET`, or [`constants::DEV_JWT_SECRET`]
+    ///   if it's unset and `allow_dev_secret` is on
+    ///
+    /// # Errors
+    /// * `AppError::Internal` - If `JWT_SECRET` is unset and the development
+    ///   secret wasn't allowed
+    pub fn signing_secret(&self) -> AppResult<&str> {
+        match (&self.jwt_secret, self.allow_dev_secret) {
+            (Some(secret), _) => Ok(secret),
+            (None, true) => Ok(constants::DEV_JWT_SECRET),
+            (None, false) => Err(AppError::Internal(
+                "JWT_SECRET must be set (ALLOW_DEV_JWT_SECRET=true allows an insecure development secret)"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Load configuration using `lookup` to read each variable.
+    ///
+    /// This is what [`Config::from_env`] uses under the hood; passing a
+    /// closure instead of touching real env vars keeps tests independent.
+    ///
+    /// # Errors
+    /// * `AppError::Internal` - If a variable is set but malformed
+    /// * `AppError::Validation` - If `DATABASE_URL` isn't a supported
+    ///   database URL; see [`DatabaseUrl::parse`]
+    pub fn from_lookup<F>(lookup: F) -> AppResult<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let defaults = Self::default();
+
+        let rate_limit_per_minute = parse_or(
+            "RATE_LIMIT_PER_MINUTE",
+            &lookup,
+            defaults.rate_limit_per_minute,
+        )?;
+        if rate_limit_per_minute == 0 {
+            return Err(AppError::Internal(
+                "RATE_LIMIT_PER_MINUTE must be at least 1".to_string(),
+            ));
+        }
+
+        let max_body_bytes = parse_or("MAX_BODY_BYTES", &lookup, defaults.max_body_bytes)?;
+        if max_body_bytes == 0 {
+            return Err(AppError::Internal(
+                "MAX_BODY_BYTES must be at least 1".to_string(),
+            ));
+        }
+
+        let lockout = LockoutConfig {
+            max_failed_logins: parse_or(
+                "LOGIN_MAX_FAILURES",
+                &lookup,
+                defaults.lockout.max_failed_logins,
+            )?,
+            lockout_minutes: parse_or(
+                "LOGIN_LOCKOUT_MINUTES",
+                &lookup,
+                defaults.lockout.lockout_minutes,
+            )?,
+        };
+        if lockout.max_failed_logins == 0 {
+            return Err(AppError::Internal(
+                "LOGIN_MAX_FAILURES must be at least 1".to_string(),
+            ));
+        }
+
+        let tls_cert = lookup("TLS_CERT")
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+        let tls_key = lookup("TLS_KEY")
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+        if tls_cert.is_some() != tls_key.is_some() {
+            return Err(AppError::Internal(
+                "TLS_CERT and TLS_KEY must be set together".to_string(),
+            ));
+        }
+
+        let query_timeout_secs =
+            parse_or("QUERY_TIMEOUT_SECS", &lookup, defaults.query_timeout_secs)?;
+        if query_timeout_secs == 0 {
+            return Err(AppError::Internal(
+                "QUERY_TIMEOUT_SECS must be at least 1".to_string(),
+            ));
+        }
+
+        let database_url = match lookup("DATABASE_URL") {
+            Some(url) => DatabaseUrl::parse(&url)?,
+            None => defaults.database_url,
+        };
+
+        Ok(Self {
+            database_url,
+            web_port: parse_or("WEB_PORT", &lookup, defaults.web_port)?,
+            grpc_port: parse_or("GRPC_PORT", &lookup, defaults.grpc_port)?,
+            rate_limit_per_minute,
+            max_body_bytes,
+            jwt_secret: lookup("JWT_SECRET").filter(|s| !s.is_empty()),
+            allow_dev_secret: parse_or("ALLOW_DEV_JWT_SECRET", &lookup, defaults.allow_dev_secret)?,
+            cors: cors_from_lookup(&lookup, defaults.cors)?,
+            lockout,
+            tls_cert,
+            tls_key,
+            webhook: webhook_from_lookup(&lookup)?,
+            read_only: parse_or("READ_ONLY", &lookup, defaults.read_only)?,
+            query_timeout_secs,
+            log_format: parse_or("LOG_FORMAT", &lookup, defaults.log_format)?,
+        })
+    }
+}
+
+/// Read the `CORS_*` variables, checking each value so building the
+/// web service's CORS filter can't fail later.
+fn cors_from_lookup<F>(lookup: &F, defaults: CorsConfig) -> AppResult<CorsConfig>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let allowed_origins = match lookup("CORS_ALLOWED_ORIGINS") {
+        Some(value) => split_list(&value),
+        None => defaults.allowed_origins,
+    };
+    for origin in &allowed_origins {
+        if origin != "*" && !is_valid_origin(origin) {
+            return Err(AppError::Internal(format!(
+                "Invalid origin in CORS_ALLOWED_ORIGINS: {:?} (expected e.g. https://example.com)",
+                origin
+            )));
+        }
+    }
+
+    let allowed_methods = match lookup("CORS_ALLOWED_METHODS") {
+        Some(value) => split_list(&value)
+            .into_iter()
+            .map(|method| method.to_ascii_uppercase())
+            .collect(),
+        None => defaults.allowed_methods,
+    };
+    for method in &allowed_methods {
+        if http::Method::from_bytes(method.as_bytes()).is_err() {
+            return Err(AppError::Internal(format!(
+                "Invalid method in CORS_ALLOWED_METHODS: {:?}",
+                method
+            )));
+        }
+    }
+
+    let cors = CorsConfig {
+        allowed_origins,
+        allowed_methods,
+        allow_credentials: parse_or("CORS_ALLOW_CREDENTIALS", lookup, defaults.allow_credentials)?,
+    };
+    // Credentials for any site at all would let every page act as the user
+    if cors.allow_credentials && cors.allows_any_origin() {
+        return Err(AppError::Internal(
+            "CORS_ALLOW_CREDENTIALS cannot be used with CORS_ALLOWED_ORIGINS=*".to_string(),
+        ));
+    }
+
+    Ok(cors)
+}
+
+/// Read the `WEBHOOK_*` variables; no `WEBHOOK_URL` means no webhook.
+fn webhook_from_lookup<F>(lookup: &F) -> AppResult<Option<WebhookConfig>>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let Some(url) = lookup("WEBHOOK_URL").filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    match reqwest::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => {
+            return Err(AppError::Internal(format!(
+                "Invalid WEBHOOK_URL: {:?} (expected an http or https URL)",
+                url
+            )))
+        }
+    }
+
+    let timeout_secs = parse_or(
+        "WEBHOOK_TIMEOUT_SECS",
+        lookup,
+        constants::WEBHOOK_TIMEOUT_SECS,
+    )?;
+    if timeout_secs == 0 {
+        return Err(AppError::Internal(
+            "WEBHOOK_TIMEOUT_SECS must be at least 1".to_string(),
+        ));
+    }
+
+    Ok(Some(WebhookConfig {
+        url,
+        timeout_secs,
+        secret: lookup("WEBHOOK_SECRET").filter(|s| !s.is_empty()),
+    }))
+}
+
+/// Split a comma-separated value, dropping blank entries.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Whether `origin` is `scheme://host[:port]` with nothing after it.
+fn is_valid_origin(origin: &str) -> bool {
+    match origin.split_once("://") {
+        Some((scheme, authority)) => {
+            !scheme.is_empty()
+                && scheme.chars().all(|c| c.is_ascii_alphabetic())
+                && !authority.contains('/')
+                && authority.parse::<http::uri::Authority>().is_ok()
+        }
+        None => false,
+    }
+}
+
+/// Parse `key` if it's set, otherwise fall back to `default`.
+fn parse_or<T, F>(key: &str, lookup: &F, default: T) -> AppResult<T>
+where
+    T: FromStr,
+    F: Fn(&str) -> Option<String>,
+{
+    match lookup(key) {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| AppError::Internal(format!("Invalid value for {}: {:?}", key, value))),
+        None => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load(vars: &[(&str, &str)]) -> AppResult<Config> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Config::from_lookup(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn test_defaults_when_unset() {
+        let config = load(&[]).unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.database_url.as_str(), crate::DEFAULT_DB_PATH);
+        assert_eq!(config.web_port, constants::WEB_PORT);
+        assert_eq!(config.grpc_port, constants::GRPC_PORT);
+        assert_eq!(config.jwt_secret, None);
+    }
+
+    #[test]
+    fn test_values_from_env() {
+        let config = load(&[
+            ("DATABASE_URL", "sqlite::memory:"),
+            ("WEB_PORT", "8080"),
+            ("GRPC_PORT", "9090"),
+            ("RATE_LIMIT_PER_MINUTE", "30"),
+            ("MAX_BODY_BYTES", "1024"),
+            ("JWT_SECRET", "s3cret"),
+            ("LOGIN_MAX_FAILURES", "3"),
+            ("LOGIN_LOCKOUT_MINUTES", "60"),
+            ("QUERY_TIMEOUT_SECS", "10"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.database_url.as_str(), "sqlite::memory:");
+        assert_eq!(config.web_port, 8080);
+        assert_eq!(config.grpc_port, 9090);
+        assert_eq!(config.rate_limit_per_minute, 30);
+        assert_eq!(config.max_body_bytes, 1024);
+        assert_eq!(config.query_timeout_secs, 10);
+        assert_eq!(config.jwt_secret.as_deref(), Some("s3cret"));
+        assert_eq!(
+            config.lockout,
+            LockoutConfig {
+                max_failed_logins: 3,
+                lockout_minutes: 60,
+            }
+        );
+
+        assert_eq!(config.signing_secret().unwrap(), "s3cret");
+
+        // Caught here rather than when the pool connects
+        assert!(load(&[("DATABASE_URL", "mysql://localhost/tasks")])
+            .unwrap_err()
+            .is_validation());
+    }
+
+    #[test]
+    fn test_signing_secret_requires_jwt_secret_or_dev_flag() {
+        assert!(load(&[]).unwrap().signing_secret().is_err());
+
+        let config = load(&[("ALLOW_DEV_JWT_SECRET", "true")]).unwrap();
+        assert_eq!(config.signing_secret().unwrap(), constants::DEV_JWT_SECRET);
+
+        // A real secret always wins over the fallback
+        let config = load(&[("ALLOW_DEV_JWT_SECRET", "true"), ("JWT_SECRET", "s3cret")]).unwrap();
+        assert_eq!(config.signing_secret().unwrap(), "s3cret");
+    }
+
+    #[test]
+    fn test_cors_from_env() {
+        let config = load(&[
+            (
+                "CORS_ALLOWED_ORIGINS",
+                "https://app.example.com, http://localhost:5173",
+            ),
+            ("CORS_ALLOWED_METHODS", "get,post"),
+            ("CORS_ALLOW_CREDENTIALS", "true"),
+        ])
+        .unwrap();
+        assert_eq!(
+            config.cors.allowed_origins,
+            ["https://app.example.com", "http://localhost:5173"]
+        );
+        assert_eq!(config.cors.allowed_methods, ["GET", "POST"]);
+        assert!(config.cors.allow_credentials);
+
+        // An empty list allows no other origins at all
+        let config = load(&[("CORS_ALLOWED_ORIGINS", "")]).unwrap();
+        assert!(config.cors.allowed_origins.is_empty());
+
+        for vars in [
+            [("CORS_ALLOWED_ORIGINS", "app.example.com")],
+            [("CORS_ALLOWED_ORIGINS", "https://example.com/path")],
+            [("CORS_ALLOWED_METHODS", "GET,NOT A METHOD")],
+        ] {
+            assert!(load(&vars).is_err(), "{:?}", vars);
+        }
+        let err = load(&[
+            ("CORS_ALLOWED_ORIGINS", "*"),
+            ("CORS_ALLOW_CREDENTIALS", "true"),
+        ])
+        .unwrap_err();
+        assert!(matches!(err, AppError::Internal(_)));
+    }
+
+    #[test]
+    fn test_tls_paths_come_as_a_pair() {
+        let config = load(&[("TLS_CERT", "cert.pem"), ("TLS_KEY", "key.pem")]).unwrap();
+        assert_eq!(config.tls_cert, Some(PathBuf::from("cert.pem")));
+        assert_eq!(config.tls_key, Some(PathBuf::from("key.pem")));
+
+        for vars in [[("TLS_CERT", "cert.pem")], [("TLS_KEY", "key.pem")]] {
+            let err = load(&vars).unwrap_err();
+            assert!(matches!(err, AppError::Internal(_)), "{:?}", vars);
+        }
+    }
+
+    #[test]
+    fn test_webhook_from_env() {
+        assert_eq!(load(&[]).unwrap().webhook, None);
+
+        let config = load(&[
+            ("WEBHOOK_URL", "https://hooks.example.com/tasks"),
+            ("WEBHOOK_SECRET", "shh"),
+        ])
+        .unwrap();
+        assert_eq!(
+            config.webhook,
+            Some(WebhookConfig {
+                url: "https://hooks.example.com/tasks".to_string(),
+                timeout_secs: constants::WEBHOOK_TIMEOUT_SECS,
+                secret: Some("shh".to_string()),
+            })
+        );
+
+        for vars in [
+            [
+                ("WEBHOOK_URL", "hooks.example.com"),
+                ("WEBHOOK_TIMEOUT_SECS", "5"),
+            ],
+            [
+                ("WEBHOOK_URL", "ftp://example.com"),
+                ("WEBHOOK_TIMEOUT_SECS", "5"),
+            ],
+            [
+                ("WEBHOOK_URL", "https://example.com"),
+                ("WEBHOOK_TIMEOUT_SECS", "0"),
+            ],
+        ] {
+            assert!(load(&vars).is_err(), "{:?}", vars);
+        }
+    }
+
+    #[test]
+    fn test_read_only_from_env() {
+        assert!(!load(&[]).unwrap().read_only);
+        assert!(load(&[("READ_ONLY", "true")]).unwrap().read_only);
+        assert!(load(&[("READ_ONLY", "yes")]).is_err());
+    }
+
+    #[test]
+    fn test_log_format_from_env() {
+        assert_eq!(load(&[]).unwrap().log_format, LogFormat::default());
+        assert_eq!(
+            load(&[("LOG_FORMAT", "json")]).unwrap().log_format,
+            LogFormat::Json
+        );
+        assert_eq!(
+            load(&[("LOG_FORMAT", "pretty")]).unwrap().log_format,
+            LogFormat::Pretty
+        );
+        let err = load(&[("LOG_FORMAT", "xml")]).unwrap_err();
+        assert!(matches!(err, AppError::Internal(_)));
+    }
+
+    #[test]
+    fn test_malformed_port_is_internal_error() {
+        for port in ["abc", "70000", "-1"] {
+            let err = load(&[("WEB_PORT", port)]).unwrap_err();
+            assert!(matches!(err, AppError::Internal(_)), "port {:?}", port);
+        }
+
+        let err = load(&[("RATE_LIMIT_PER_MINUTE", "0")]).unwrap_err();
+        assert!(matches!(err, AppError::Internal(_)));
+        let err = load(&[("MAX_BODY_BYTES", "0")]).unwrap_err();
+        assert!(matches!(err, AppError::Internal(_)));
+        let err = load(&[("QUERY_TIMEOUT_SECS", "0")]).unwrap_err();
+        assert!(matches!(err, AppError::Internal(_)));
+    }
+}
@@ -0,0 +1,230 @@
+//! Repository for task attachments.
+//!
+//! Only metadata is stored here; uploading and serving the bytes is up to
+//! whatever sits behind `storage_key`.
+
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::db::{ensure_writable, DbPool};
+use crate::error::{AppError, AppResult};
+use crate::models::{Attachment, CreateAttachment};
+
+/// Repository for attachment entity operations.
+pub struct AttachmentRepository;
+
+impl AttachmentRepository {
+    /// Record a file attached to a task.
+    ///
+    /// The storage key is `tasks/<task_id>/<uuid>/<filename>`; the UUID keeps
+    /// two uploads of the same name apart.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `attachment` - The file's metadata
+    ///
+    /// # Returns
+    /// * `AppResult<Attachment>` - Created attachment with its storage key
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the metadata is invalid, including a
+    ///   filename with a path separator
+    /// * `AppError::TaskNotFound` - If the task doesn't exist
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, attachment), fields(task_id = attachment.task_id), level = "debug")]
+    pub async fn add_attachment(
+        pool: &DbPool,
+        attachment: CreateAttachment,
+    ) -> AppResult<Attachment> {
+        ensure_writable()?;
+        attachment.validate()?;
+
+        // Check the task up front for a clear error instead of a foreign key failure
+        let (task_exists,): (bool,) =
+            sqlx::query_as("SELECT EXISTS (SELECT 1 FROM tasks WHERE id = ?)")
+                .bind(attachment.task_id)
+                .fetch_one(pool)
+                .await?;
+        if !task_exists {
+            return Err(AppError::TaskNotFound(attachment.task_id));
+        }
+
+        let storage_key = format!(
+            "tasks/{}/{}/{}",
+            attachment.task_id,
+            Uuid::new_v4(),
+            attachment.filename
+        );
+
+        let attachment = sqlx::query_as::<_, Attachment>(
+            r#"
+            INSERT INTO attachments (task_id, filename, content_type, size_bytes, storage_key)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(attachment.task_id)
+        .bind(&attachment.filename)
+        .bind(&attachment.content_type)
+        .bind(attachment.size_bytes)
+        .bind(&storage_key)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(attachment)
+    }
+
+    /// List a task's attachments, oldest first.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Attachment>>` - The attachments (empty vec if none)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn attachments_for_task(pool: &DbPool, task_id: i64) -> AppResult<Vec<Attachment>> {
+        let attachments = sqlx::query_as::<_, Attachment>(
+            r#"
+            SELECT * FROM attachments
+            WHERE task_id = ?
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(attachments)
+    }
+
+    /// Delete an attachment's metadata by ID.
+    ///
+    /// The stored bytes are not touched; callers remove them using the
+    /// attachment's `storage_key`.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of attachment to delete
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::NotFound` - If attachment doesn't exist
+    /// * `AppError::Database` - If database deletion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn delete_attachment(pool: &DbPool, id: i64) -> AppResult<()> {
+        ensure_writable()?;
+        let result = sqlx::query("DELETE FROM attachments WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound {
+                entity: "Attachment",
+                id,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{create_test_pool, create_test_task, create_test_user, TaskRepository};
+
+    fn file(task_id: i64, filename: &str) -> CreateAttachment {
+        CreateAttachment {
+            task_id,
+            filename: filename.to_string(),
+            content_type: "application/pdf".to_string(),
+            size_bytes: 1024,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_and_list_attachments() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Attach to me")
+            .await
+            .unwrap();
+
+        let first = AttachmentRepository::add_attachment(&pool, file(task.id, "spec.pdf"))
+            .await
+            .unwrap();
+        let second = AttachmentRepository::add_attachment(&pool, file(task.id, "spec.pdf"))
+            .await
+            .unwrap();
+        assert_eq!(first.size_bytes, 1024);
+        assert!(first
+            .storage_key
+            .starts_with(&format!("tasks/{}/", task.id)));
+        assert!(first.storage_key.ends_with("/spec.pdf"));
+        assert_ne!(first.storage_key, second.storage_key);
+
+        let listed = AttachmentRepository::attachments_for_task(&pool, task.id)
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![first.clone(), second]);
+
+        AttachmentRepository::delete_attachment(&pool, first.id)
+            .await
+            .unwrap();
+        assert!(matches!(
+            AttachmentRepository::delete_attachment(&pool, first.id).await,
+            Err(AppError::NotFound {
+                entity: "Attachment",
+                ..
+            })
+        ));
+
+        TaskRepository::delete(&pool, task.id, task.user_id)
+            .await
+            .unwrap();
+        assert!(AttachmentRepository::attachments_for_task(&pool, task.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_filename_with_path_separator_rejected() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Attach to me")
+            .await
+            .unwrap();
+
+        for filename in [
+            "../../etc/passwd",
+            "dir/file.txt",
+            "..\\secret.txt",
+            "..",
+            "",
+        ] {
+            let err = AttachmentRepository::add_attachment(&pool, file(task.id, filename))
+                .await
+                .unwrap_err();
+            assert!(err.is_validation(), "{:?} should be rejected", filename);
+        }
+        assert!(AttachmentRepository::attachments_for_task(&pool, task.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let err = AttachmentRepository::add_attachment(&pool, file(9999, "ok.txt"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::TaskNotFound(9999)));
+    }
+}
@@ -0,0 +1,262 @@
+//! Online backup and offline restore for the SQLite database file.
+//!
+//! [`backup`] is safe to run while the application keeps serving: SQLite's
+//! `VACUUM INTO` reads from a single transaction, so the copy is a
+//! consistent snapshot even with writes happening concurrently.
+//! [`restore`] replaces the database file itself and must only run while
+//! nothing has it open.
+
+use std::path::{Path, PathBuf};
+
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection};
+
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+
+/// Write a consistent copy of the database behind `pool` to `dest_path`.
+///
+/// The copy is also vacuumed (compacted), and is a normal standalone
+/// database file with no `-wal` file alongside it.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `dest_path` - Where to write the backup; must not exist yet
+///
+/// # Returns
+/// * `AppResult<()>` - Success or error
+///
+/// # Errors
+/// * `AppError::Validation` - If `dest_path` already exists or isn't valid UTF-8
+/// * `AppError::Database` - If SQLite can't write the backup
+/// * `AppError::Internal` - If SQLite reports success but no file appears
+pub async fn backup(pool: &DbPool, dest_path: impl AsRef<Path>) -> AppResult<()> {
+    let dest_path = dest_path.as_ref();
+    // VACUUM INTO refuses to overwrite too, but with a vaguer message
+    if dest_path.exists() {
+        return Err(AppError::Validation(format!(
+            "Backup destination already exists: {}",
+            dest_path.display()
+        )));
+    }
+
+    // VACUUM INTO opens its target with the source database's flags, so
+    // for an in-memory source (sqlx opens those as `mode=memory` URIs) a
+    // plain path "succeeds" without writing anything. A `file:` URI with
+    // its own mode makes it a real file either way; it goes in as a quoted
+    // literal, on a connection of its own.
+    let uri = format!("file:{}?mode=rwc", uri_escape(utf8_path(dest_path)?));
+    let mut conn = pool.acquire().await?;
+    sqlx::raw_sql(&format!("VACUUM INTO '{}'", uri.replace('\'', "''")))
+        .execute(&mut *conn)
+        .await?;
+
+    if !dest_path.is_file() {
+        return Err(AppError::Internal(format!(
+            "Backup reported success but wrote no file at {}",
+            dest_path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Replace the database at `db_path` with the backup at `src_path`.
+///
+/// Every pool and connection on `db_path` must be closed first, and the
+/// application restarted (or the pool recreated) afterwards; swapping the
+/// file under open connections corrupts it. The steps are:
+///
+/// 1. Check `src_path` is an intact SQLite database.
+/// 2. Copy it next to `db_path`, so the final step stays on one filesystem.
+/// 3. Checkpoint `db_path`, folding its `-wal` file into the old database,
+///    then delete any `-wal` and `-shm` files left; a crash here loses
+///    nothing, and they'd otherwise be replayed into the new data.
+/// 4. Rename the copy over `db_path`, which is atomic: a crash leaves
+///    either the old database or the new one, never a mix.
+///
+/// # Arguments
+/// * `src_path` - Backup file written by [`backup`]
+/// * `db_path` - Database file to replace (created if missing)
+///
+/// # Returns
+/// * `AppResult<()>` - Success or error
+///
+/// # Errors
+/// * `AppError::Validation` - If `src_path` isn't a usable SQLite database
+/// * `AppError::Database` - If the existing database can't be checkpointed
+/// * `AppError::Internal` - If copying or renaming the files fails
+pub async fn restore(src_path: impl AsRef<Path>, db_path: impl AsRef<Path>) -> AppResult<()> {
+    let (src_path, db_path) = (src_path.as_ref(), db_path.as_ref());
+
+    check_integrity(src_path).await?;
+
+    let staging = sibling(db_path, ".restore");
+    tokio::fs::copy(src_path, &staging)
+        .await
+        .map_err(|e| file_error("copy backup", &staging, e))?;
+
+    checkpoint(db_path).await?;
+    for suffix in ["-wal", "-shm"] {
+        let path = sibling(db_path, suffix);
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(file_error("remove", &path, e)),
+        }
+    }
+
+    tokio::fs::rename(&staging, db_path)
+        .await
+        .map_err(|e| file_error("replace", db_path, e))?;
+
+    Ok(())
+}
+
+/// Fold `path`'s write-ahead log into the database file, if it exists.
+async fn checkpoint(path: &Path) -> AppResult<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let mut conn = SqliteConnectOptions::new().filename(path).connect().await?;
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&mut conn)
+        .await?;
+    conn.close().await?;
+    Ok(())
+}
+
+/// Open `path` read-only and run SQLite's integrity check on it.
+async fn check_integrity(path: &Path) -> AppResult<()> {
+    let invalid = |detail: String| {
+        AppError::Validation(format!(
+            "{} is not a usable backup: {}",
+            path.display(),
+            detail
+        ))
+    };
+
+    let mut conn = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await
+        .map_err(|e| invalid(e.to_string()))?;
+    let (result,): (String,) = sqlx::query_as("PRAGMA integrity_check")
+        .fetch_one(&mut conn)
+        .await
+        .map_err(|e| invalid(e.to_string()))?;
+    conn.close().await?;
+
+    if result != "ok" {
+        return Err(invalid(result));
+    }
+    Ok(())
+}
+
+/// `path` with `suffix` appended to its file name, e.g. `tasks.db-wal`.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn utf8_path(path: &Path) -> AppResult<&str> {
+    path.to_str()
+        .ok_or_else(|| AppError::Validation(format!("Path is not valid UTF-8: {}", path.display())))
+}
+
+/// Percent-encode the characters that would end the path part of a
+/// SQLite URI filename.
+fn uri_escape(path: &str) -> String {
+    path.replace('%', "%25")
+        .replace('?', "%3f")
+        .replace('#', "%23")
+}
+
+fn file_error(action: &str, path: &Path, err: std::io::Error) -> AppError {
+    AppError::Internal(format!("Failed to {} {}: {}", action, path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{create_pool, create_test_task, create_test_user, DatabaseUrl};
+    use uuid::Uuid;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}.db", name, Uuid::new_v4()))
+    }
+
+    async fn all_tasks(pool: &DbPool) -> Vec<(i64, String, i64)> {
+        sqlx::query_as("SELECT id, title, user_id FROM tasks ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore_keep_every_row() {
+        let src = temp_path("src");
+        let pool = create_pool(&DatabaseUrl::parse(&format!("sqlite:{}", src.display())).unwrap())
+            .await
+            .unwrap();
+        crate::db::run_migrations(&pool).await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        for title in ["One", "Two", "Three"] {
+            create_test_task(&pool, alice, title).await.unwrap();
+        }
+        let expected = all_tasks(&pool).await;
+
+        let backup_path = temp_path("backup");
+        backup(&pool, &backup_path).await.unwrap();
+        // Never overwrite an existing file
+        assert!(backup(&pool, &backup_path)
+            .await
+            .unwrap_err()
+            .is_validation());
+
+        let copy =
+            create_pool(&DatabaseUrl::parse(&format!("sqlite:{}", backup_path.display())).unwrap())
+                .await
+                .unwrap();
+        assert_eq!(all_tasks(&copy).await, expected);
+        copy.close().await;
+
+        // Restore over an existing database with different contents
+        let db_path = temp_path("restored");
+        let old =
+            create_pool(&DatabaseUrl::parse(&format!("sqlite:{}", db_path.display())).unwrap())
+                .await
+                .unwrap();
+        crate::db::run_migrations(&old).await.unwrap();
+        create_test_user(&old, "bob").await.unwrap();
+        old.close().await;
+        restore(&backup_path, &db_path).await.unwrap();
+        let restored =
+            create_pool(&DatabaseUrl::parse(&format!("sqlite:{}", db_path.display())).unwrap())
+                .await
+                .unwrap();
+        assert_eq!(all_tasks(&restored).await, expected);
+        restored.close().await;
+
+        for path in [&backup_path, &db_path] {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(sibling(path, suffix));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_non_database() {
+        let src = temp_path("garbage");
+        std::fs::write(&src, b"definitely not sqlite").unwrap();
+        let db_path = temp_path("target");
+
+        let err = restore(&src, &db_path).await.unwrap_err();
+
+        assert!(err.is_validation());
+        assert!(!db_path.exists());
+        let _ = std::fs::remove_file(&src);
+    }
+}
@@ -0,0 +1,208 @@
+//! Repository for task comments.
+//!
+//! Mirrors [`TaskRepository`](crate::db::TaskRepository): a unit struct with
+//! async associated functions that take the pool explicitly.
+
+use tracing::instrument;
+
+use crate::db::{ensure_writable, DbPool};
+use crate::error::{AppError, AppResult};
+use crate::models::Comment;
+
+/// Repository for comment entity operations.
+pub struct CommentRepository;
+
+impl CommentRepository {
+    /// Post a comment on a task.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task being discussed
+    /// * `user_id` - ID of the comment's author
+    /// * `body` - Comment text
+    ///
+    /// # Returns
+    /// * `AppResult<Comment>` - Created comment with generated ID and timestamp
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `body` is empty or only whitespace
+    /// * `AppError::TaskNotFound` - If the task doesn't exist
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, body), level = "debug")]
+    pub async fn add_comment(
+        pool: &DbPool,
+        task_id: i64,
+        user_id: i64,
+        body: &str,
+    ) -> AppResult<Comment> {
+        ensure_writable()?;
+        if body.trim().is_empty() {
+            return Err(AppError::Validation("Comment cannot be empty".to_string()));
+        }
+
+        // Check the task up front for a clear error instead of a foreign key failure
+        let (task_exists,): (bool,) =
+            sqlx::query_as("SELECT EXISTS (SELECT 1 FROM tasks WHERE id = ?)")
+                .bind(task_id)
+                .fetch_one(pool)
+                .await?;
+        if !task_exists {
+            return Err(AppError::TaskNotFound(task_id));
+        }
+
+        let comment = sqlx::query_as::<_, Comment>(
+            r#"
+            INSERT INTO comments (task_id, user_id, body)
+            VALUES (?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(task_id)
+        .bind(user_id)
+        .bind(body)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(comment)
+    }
+
+    /// List a task's comments, oldest first.
+    ///
+    /// `created_at` only has one-second resolution, so comments posted in
+    /// the same second fall back to insertion (id) order.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Comment>>` - The thread (empty vec if none)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn comments_for_task(pool: &DbPool, task_id: i64) -> AppResult<Vec<Comment>> {
+        let comments = sqlx::query_as::<_, Comment>(
+            r#"
+            SELECT * FROM comments
+            WHERE task_id = ?
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(comments)
+    }
+
+    /// Delete a comment by ID.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of comment to delete
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::NotFound` - If comment doesn't exist
+    /// * `AppError::Database` - If database deletion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn delete_comment(pool: &DbPool, id: i64) -> AppResult<()> {
+        ensure_writable()?;
+        let result = sqlx::query("DELETE FROM comments WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound {
+                entity: "Comment",
+                id,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{create_test_pool, create_test_task, create_test_user, TaskRepository};
+
+    #[tokio::test]
+    async fn test_comments_come_back_in_order() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let task = create_test_task(&pool, alice, "Discuss me").await.unwrap();
+
+        let first = CommentRepository::add_comment(&pool, task.id, alice, "First!")
+            .await
+            .unwrap();
+        let second = CommentRepository::add_comment(&pool, task.id, bob, "Second")
+            .await
+            .unwrap();
+
+        let thread = CommentRepository::comments_for_task(&pool, task.id)
+            .await
+            .unwrap();
+        assert_eq!(thread, vec![first, second]);
+        assert_eq!(thread[1].user_id, bob);
+    }
+
+    #[tokio::test]
+    async fn test_empty_comment_rejected() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Discuss me").await.unwrap();
+
+        for body in ["", "   \n"] {
+            let err = CommentRepository::add_comment(&pool, task.id, alice, body)
+                .await
+                .unwrap_err();
+            assert!(err.is_validation());
+        }
+
+        let err = CommentRepository::add_comment(&pool, 9999, alice, "Hello")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::TaskNotFound(9999)));
+    }
+
+    #[tokio::test]
+    async fn test_delete_comment_and_cascade() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Discuss me").await.unwrap();
+        let comment = CommentRepository::add_comment(&pool, task.id, alice, "Gone soon")
+            .await
+            .unwrap();
+        CommentRepository::add_comment(&pool, task.id, alice, "Goes with the task")
+            .await
+            .unwrap();
+
+        CommentRepository::delete_comment(&pool, comment.id)
+            .await
+            .unwrap();
+        assert!(matches!(
+            CommentRepository::delete_comment(&pool, comment.id).await,
+            Err(AppError::NotFound {
+                entity: "Comment",
+                ..
+            })
+        ));
+
+        TaskRepository::delete(&pool, task.id, task.user_id)
+            .await
+            .unwrap();
+        assert!(CommentRepository::comments_for_task(&pool, task.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Composable task filters.
+//!
+//! [`TaskFilter`] collects optional criteria with a builder API; the
+//! repository turns whichever ones are set into a single WHERE clause,
+//! followed by the ORDER BY from [`TaskSort`] and an optional page.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::AppError;
+use crate::models::{TaskPriority, TaskStatus};
+
+/// Order of the rows returned by a filtered query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TaskSort {
+    /// Newest first
+    #[default]
+    CreatedDesc,
+    /// Oldest first
+    CreatedAsc,
+    /// Soonest due first; tasks without a due date come last
+    DueDateAsc,
+    /// Latest due first; tasks without a due date come last
+    DueDateDesc,
+}
+
+impl TaskSort {
+    /// The snake_case name accepted in query params.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskSort::CreatedDesc => "created_desc",
+            TaskSort::CreatedAsc => "created_asc",
+            TaskSort::DueDateAsc => "due_date_asc",
+            TaskSort::DueDateDesc => "due_date_desc",
+        }
+    }
+
+    /// The ORDER BY clause (without the keyword) for this sort.
+    ///
+    /// Every variant ends with an `id` tie-break so pages don't overlap.
+    pub(crate) fn order_by(&self) -> &'static str {
+        match self {
+            TaskSort::CreatedDesc => "created_at DESC, id DESC",
+            TaskSort::CreatedAsc => "created_at ASC, id ASC",
+            TaskSort::DueDateAsc => "due_date IS NULL, due_date ASC, id ASC",
+            TaskSort::DueDateDesc => "due_date IS NULL, due_date DESC, id DESC",
+        }
+    }
+}
+
+impl fmt::Display for TaskSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TaskSort {
+    type Err = AppError;
+
+    /// Parse the snake_case form, e.g. `"due_date_asc"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created_desc" => Ok(TaskSort::CreatedDesc),
+            "created_asc" => Ok(TaskSort::CreatedAsc),
+            "due_date_asc" => Ok(TaskSort::DueDateAsc),
+            "due_date_desc" => Ok(TaskSort::DueDateDesc),
+            other => Err(AppError::Validation(format!(
+                "Unknown sort order: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Criteria for [`TaskRepository::find_filtered`](crate::db::TaskRepository::find_filtered).
+///
+/// Every criterion is optional and they combine with AND. A filter with
+/// nothing set matches all of the user's tasks.
+///
+/// # Example
+/// ```
+/// use chrono::{Duration, Utc};
+/// use shared::db::TaskFilter;
+/// use shared::models::{TaskPriority, TaskStatus};
+///
+/// let filter = TaskFilter::new(1)
+///     .status(TaskStatus::InProgress)
+///     .priority(TaskPriority::High)
+///     .due_before(Utc::now() + Duration::days(7));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskFilter {
+    /// Owner of the tasks (always applied)
+    pub user_id: i64,
+
+    /// Only tasks with this status
+    pub status: Option<TaskStatus>,
+
+    /// Only tasks with one of these priorities; empty means any priority
+    pub priorities: Vec<TaskPriority>,
+
+    /// Only tasks due strictly before this instant (excludes undated tasks)
+    pub due_before: Option<DateTime<Utc>>,
+
+    /// Only tasks whose title or description contains this text (ASCII case-insensitive)
+    pub search: Option<String>,
+
+    /// Row order (newest first by default)
+    pub sort: TaskSort,
+
+    /// Maximum rows to return (1 to `MAX_PAGE_SIZE`); `None` returns all
+    pub limit: Option<i64>,
+
+    /// Rows to skip before the first one returned
+    pub offset: Option<i64>,
+}
+
+impl TaskFilter {
+    /// Start a filter matching all tasks owned by `user_id`.
+    pub fn new(user_id: i64) -> Self {
+        Self {
+            user_id,
+            ..Self::default()
+        }
+    }
+
+    /// Restrict to a single status.
+    #[must_use]
+    pub fn status(mut self, status: TaskStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Restrict to a single priority.
+    #[must_use]
+    pub fn priority(mut self, priority: TaskPriority) -> Self {
+        self.priorities = vec![priority];
+        self
+    }
+
+    /// Restrict to any of `priorities`; an empty list lifts the restriction.
+    #[must_use]
+    pub fn priorities(mut self, priorities: impl IntoIterator<Item = TaskPriority>) -> Self {
+        self.priorities = priorities.into_iter().collect();
+        self
+    }
+
+    /// Restrict to tasks due before `due_before`.
+    #[must_use]
+    pub fn due_before(mut self, due_before: DateTime<Utc>) -> Self {
+        self.due_before = Some(due_before);
+        self
+    }
+
+    /// Restrict to tasks mentioning `text` in the title or description.
+    #[must_use]
+    pub fn search(mut self, text: impl Into<String>) -> Self {
+        self.search = Some(text.into());
+        self
+    }
+
+    /// Order the results.
+    #[must_use]
+    pub fn sort(mut self, sort: TaskSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Return at most `limit` rows, skipping the first `offset`.
+    #[must_use]
+    pub fn page(mut self, limit: i64, offset: i64) -> Self {
+        self.limit = Some(limit);
+        self.offset = Some(offset);
+        self
+    }
+}
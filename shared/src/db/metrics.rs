@@ -0,0 +1,54 @@
+//! Optional call metrics for the repository layer.
+//!
+//! With the `metrics` feature enabled, every `TaskRepository` method
+//! records a call counter and a latency histogram through the `metrics`
+//! facade, labelled with the method name. Whatever recorder the binary
+//! installs (e.g. a Prometheus exporter) picks them up.
+//!
+//! Without the feature, `timed!` only applies the query timeout, so
+//! there's nothing left to cost anything at runtime.
+//!
+//! Either way, `timed!` gives each method body the process's query
+//! timeout (see [`query_timeout`](crate::db::query_timeout)).
+
+/// Counter of repository calls, labelled by `op` and `outcome` (`ok`/`error`).
+pub const CALLS_METRIC: &str = "task_repository_calls_total";
+
+/// Histogram of repository call latency in seconds, labelled by `op`.
+pub const DURATION_METRIC: &str = "task_repository_duration_seconds";
+
+/// Gauge of the fraction of the pool's connections in use, set by the
+/// pool monitor (see [`spawn_pool_monitor`](crate::db::spawn_pool_monitor)).
+pub const POOL_SATURATION_METRIC: &str = "db_pool_saturation";
+
+/// Run a repository method body under the query timeout, recording its
+/// metrics under `$op`.
+///
+/// The body runs inside an `async` block so `?` and `return` still
+/// produce the method's result instead of skipping the recording.
+#[cfg(feature = "metrics")]
+macro_rules! timed {
+    ($op:literal, $body:block) => {{
+        let start = ::std::time::Instant::now();
+        let result =
+            $crate::db::with_timeout($crate::db::query_timeout(), async move { $body }).await;
+        $crate::db::metrics::record($op, start.elapsed(), result.is_ok());
+        result
+    }};
+}
+
+/// Without the `metrics` feature, just the body under the query timeout.
+#[cfg(not(feature = "metrics"))]
+macro_rules! timed {
+    ($op:literal, $body:block) => {
+        $crate::db::with_timeout($crate::db::query_timeout(), async move { $body }).await
+    };
+}
+
+/// Record one call of `op` that took `elapsed`.
+#[cfg(feature = "metrics")]
+pub(crate) fn record(op: &'static str, elapsed: std::time::Duration, ok: bool) {
+    let outcome = if ok { "ok" } else { "error" };
+    metrics::counter!(CALLS_METRIC, "op" => op, "outcome" => outcome).increment(1);
+    metrics::histogram!(DURATION_METRIC, "op" => op).record(elapsed.as_secs_f64());
+}
@@ -0,0 +1,170 @@
+//! In-memory [`TaskStore`] for tests (see the `testing` feature).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::Utc;
+
+use crate::db::TaskStore;
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateTask, Task, UpdateTask};
+
+/// A [`TaskStore`] that keeps tasks in a `HashMap`, for tests that
+/// shouldn't need a database.
+///
+/// Clones share the same tasks, like clones of a pool share a database.
+/// Nothing checks that users or projects exist, and no audit entries are
+/// written.
+#[derive(Debug, Clone, Default)]
+pub struct MockTaskStore {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    tasks: HashMap<i64, Task>,
+    last_id: i64,
+}
+
+impl MockTaskStore {
+    /// An empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How many tasks the store holds.
+    pub fn len(&self) -> usize {
+        self.state().tasks.len()
+    }
+
+    /// Whether the store holds no tasks.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // A test that panicked mid-update has failed already; keep going
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl TaskStore for MockTaskStore {
+    async fn create(&self, task: CreateTask) -> AppResult<Task> {
+        let mut state = self.state();
+        state.last_id += 1;
+        let now = Utc::now();
+        let task = Task {
+            id: state.last_id,
+            title: task.title,
+            description: task.description,
+            status: task.status,
+            priority: task.priority,
+            due_date: task.due_date,
+            user_id: task.user_id,
+            recurrence: task.recurrence,
+            estimated_minutes: task.estimated_minutes,
+            actual_minutes: task.actual_minutes,
+            project_id: task.project_id,
+            parent_id: task.parent_id,
+            position: 0,
+            version: 1,
+            created_at: now,
+            updated_at: now,
+        };
+        state.tasks.insert(task.id, task.clone());
+
+        Ok(task)
+    }
+
+    async fn find_by_id(&self, id: i64) -> AppResult<Task> {
+        self.state()
+            .tasks
+            .get(&id)
+            .cloned()
+            .ok_or(AppError::TaskNotFound(id))
+    }
+
+    async fn find_by_user(&self, user_id: i64) -> AppResult<Vec<Task>> {
+        let mut tasks: Vec<Task> = self
+            .state()
+            .tasks
+            .values()
+            .filter(|task| task.user_id == user_id)
+            .cloned()
+            .collect();
+        tasks.sort_by_key(|task| std::cmp::Reverse(task.id));
+
+        Ok(tasks)
+    }
+
+    async fn update(&self, id: i64, _user_id: i64, update: UpdateTask) -> AppResult<Task> {
+        if update.estimated_minutes.is_some_and(|m| m < 0)
+            || update.actual_minutes.is_some_and(|m| m < 0)
+        {
+            return Err(AppError::Validation(
+                "Minutes cannot be negative".to_string(),
+            ));
+        }
+
+        let mut state = self.state();
+        let task = state.tasks.get_mut(&id).ok_or(AppError::TaskNotFound(id))?;
+        if let Some(version) = update.version {
+            if version != task.version {
+                return Err(AppError::Conflict(format!(
+                    "Task {} was changed by someone else (now at version {}, not {})",
+                    id, task.version, version
+                )));
+            }
+        }
+
+        if let Some(title) = update.title {
+            task.title = title;
+        }
+        if let Some(description) = update.description {
+            task.description = description;
+        }
+        if let Some(status) = update.status {
+            task.status = status;
+        }
+        if let Some(priority) = update.priority {
+            task.priority = priority;
+        }
+        if let Some(due_date) = update.due_date {
+            task.due_date = due_date;
+        }
+        if let Some(minutes) = update.estimated_minutes {
+            task.estimated_minutes = Some(minutes);
+        }
+        if let Some(minutes) = update.actual_minutes {
+            task.actual_minutes = Some(minutes);
+        }
+        if let Some(project_id) = update.project_id {
+            task.project_id = project_id;
+        }
+        if let Some(parent_id) = update.parent_id {
+            task.parent_id = parent_id;
+        }
+        task.version += 1;
+        task.updated_at = Utc::now();
+
+        Ok(task.clone())
+    }
+
+    async fn delete(&self, id: i64, _user_id: i64) -> AppResult<()> {
+        self.state()
+            .tasks
+            .remove(&id)
+            .map(|_| ())
+            .ok_or(AppError::TaskNotFound(id))
+    }
+
+    async fn belongs_to_user(&self, task_id: i64, user_id: i64) -> AppResult<bool> {
+        Ok(self
+            .state()
+            .tasks
+            .get(&task_id)
+            .is_some_and(|task| task.user_id == user_id))
+    }
+}
@@ -3,15 +3,68 @@
 //! This module provides:
 //! - Database connection pooling
 //! - Repository pattern for data access
+//! - A mockable `TaskStore` trait over the task CRUD operations
+//! - A background monitor that warns when the pool runs out of connections
+//! - Shutdown helpers that close the pool cleanly and pick the exit code
+//! - Retrying operations that hit a busy or locked database
+//! - A read-only mode that blocks writes during maintenance
 //! - Transaction support
+//! - Online backup and restore
 //!
 //! The database layer is organized around the repository pattern
 //! which provides a clean abstraction over data persistence.
 
 // Declare submodules
+pub mod attachment_repository;
+pub mod backup;
+pub mod comment_repository;
 pub mod connection;
+pub mod filter;
+// Must come before the repositories so they can use its `timed!` macro
+#[macro_use]
+pub mod metrics;
+pub mod pool_monitor;
+pub mod project_repository;
+pub mod read_only;
 pub mod repository;
+pub mod retry;
+#[cfg(feature = "dev")]
+pub mod seed;
+pub mod shutdown;
+pub mod store;
+pub mod user_repository;
+
+// Test-only helpers (see the `testing` feature in Cargo.toml)
+#[cfg(any(test, feature = "testing"))]
+mod mock;
+#[cfg(any(test, feature = "testing"))]
+mod testing;
 
 // Re-export commonly used types
-pub use connection::{create_pool, run_migrations, DbPool};
+pub use attachment_repository::AttachmentRepository;
+pub use backup::{backup, restore};
+pub use comment_repository::CommentRepository;
+pub use connection::{
+    check_health, check_health_detailed, create_pool, create_pool_with_config,
+    migrations_up_to_date, query_timeout, run_migrations, set_query_timeout, warmup, with_timeout,
+    DatabaseUrl, DbPool, HealthStatus, PoolConfig,
+};
+pub use filter::{TaskFilter, TaskSort};
+#[cfg(any(test, feature = "testing"))]
+pub use mock::MockTaskStore;
+pub use pool_monitor::{spawn_pool_monitor, PoolStats};
+pub use project_repository::ProjectRepository;
+#[cfg(unix)]
+pub use read_only::spawn_read_only_toggle;
+pub use read_only::{ensure_writable, is_read_only, set_read_only};
 pub use repository::TaskRepository;
+pub use retry::retry;
+#[cfg(feature = "dev")]
+pub use seed::seed;
+pub use shutdown::{close_pool, exit_code, finish_shutdown, shutdown_signal, ShutdownCause};
+pub use store::{SqlxTaskRepository, TaskStore};
+#[cfg(test)]
+pub(crate) use testing::fake_db_error;
+#[cfg(any(test, feature = "testing"))]
+pub use testing::{create_test_pool, create_test_task, create_test_user, new_test_task};
+pub use user_repository::UserRepository;
@@ -0,0 +1,200 @@
+//! Background check for connection pool exhaustion.
+//!
+//! When every connection is busy, sqlx quietly queues new requests until
+//! `acquire_timeout` fails them. [`spawn_pool_monitor`] samples the pool
+//! periodically and logs a warning once it has been exhausted for longer
+//! than a threshold, so the queueing shows up in the logs before the
+//! timeouts do. With the `metrics` feature it also keeps a saturation
+//! gauge up to date.
+//!
+//! The decision itself lives in [`SaturationTracker`], which only sees
+//! [`PoolStats`] and timestamps, so it can be tested without a pool.
+
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::db::DbPool;
+
+/// How often the monitor samples the pool by default.
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long the pool may stay exhausted before the monitor warns, by default.
+pub const DEFAULT_WARN_AFTER: Duration = Duration::from_secs(10);
+
+/// A snapshot of the pool's connection counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Most connections the pool will open
+    pub max: u32,
+    /// Connections currently open, idle or in use
+    pub size: u32,
+    /// Open connections not in use
+    pub idle: usize,
+}
+
+impl PoolStats {
+    /// Read the current counts from `pool`.
+    pub fn of(pool: &DbPool) -> Self {
+        Self {
+            max: pool.options().get_max_connections(),
+            size: pool.size(),
+            idle: pool.num_idle(),
+        }
+    }
+
+    /// Fraction of the maximum connections in use, from 0.0 to 1.0.
+    pub fn saturation(&self) -> f64 {
+        if self.max == 0 {
+            return 0.0;
+        }
+        let in_use = (self.size as usize).saturating_sub(self.idle);
+        in_use as f64 / f64::from(self.max)
+    }
+
+    /// Whether a new request would have to wait for a connection: none
+    /// are idle and the pool can't open more.
+    pub fn is_exhausted(&self) -> bool {
+        self.idle == 0 && self.size >= self.max
+    }
+}
+
+/// Decides when pool exhaustion has lasted long enough to warn about.
+///
+/// Feed it a sample at a time with [`observe`](Self::observe). It warns
+/// once per stretch of exhaustion, and resets when a connection frees up.
+#[derive(Debug, Clone)]
+pub struct SaturationTracker {
+    warn_after: Duration,
+    exhausted_since: Option<Instant>,
+    warned: bool,
+}
+
+/// What the monitor should do after a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Nothing worth logging
+    Ok,
+    /// The pool has been exhausted for this long; warn
+    Exhausted(Duration),
+    /// A stretch of exhaustion that was warned about has ended
+    Recovered,
+}
+
+impl SaturationTracker {
+    /// Warn once the pool has been exhausted for longer than `warn_after`.
+    pub fn new(warn_after: Duration) -> Self {
+        Self {
+            warn_after,
+            exhausted_since: None,
+            warned: false,
+        }
+    }
+
+    /// Take a sample taken at `now` into account.
+    pub fn observe(&mut self, stats: PoolStats, now: Instant) -> Verdict {
+        if !stats.is_exhausted() {
+            self.exhausted_since = None;
+            return if std::mem::take(&mut self.warned) {
+                Verdict::Recovered
+            } else {
+                Verdict::Ok
+            };
+        }
+
+        let since = *self.exhausted_since.get_or_insert(now);
+        let exhausted_for = now.saturating_duration_since(since);
+        if exhausted_for > self.warn_after && !self.warned {
+            self.warned = true;
+            return Verdict::Exhausted(exhausted_for);
+        }
+        Verdict::Ok
+    }
+}
+
+/// Start sampling `pool` every `interval` on the Tokio runtime, warning
+/// when it's been exhausted for longer than `warn_after`.
+///
+/// Runs until the returned handle is aborted or the runtime shuts down.
+pub fn spawn_pool_monitor(
+    pool: DbPool,
+    interval: Duration,
+    warn_after: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tracker = SaturationTracker::new(warn_after);
+        let mut ticks = tokio::time::interval(interval);
+
+        loop {
+            ticks.tick().await;
+            let stats = PoolStats::of(&pool);
+
+            #[cfg(feature = "metrics")]
+            ::metrics::gauge!(crate::db::metrics::POOL_SATURATION_METRIC).set(stats.saturation());
+
+            match tracker.observe(stats, Instant::now()) {
+                Verdict::Ok => {}
+                Verdict::Exhausted(exhausted_for) => warn!(
+                    "Database pool exhausted for {:?}: all {} connections busy, requests are queueing",
+                    exhausted_for, stats.max
+                ),
+                Verdict::Recovered => info!("Database pool has idle connections again"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUSY: PoolStats = PoolStats {
+        max: 5,
+        size: 5,
+        idle: 0,
+    };
+    const FREE: PoolStats = PoolStats {
+        max: 5,
+        size: 5,
+        idle: 2,
+    };
+
+    #[test]
+    fn test_warns_once_exhaustion_outlasts_threshold() {
+        let mut tracker = SaturationTracker::new(Duration::from_secs(10));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert_eq!(tracker.observe(BUSY, at(0)), Verdict::Ok);
+        assert_eq!(tracker.observe(BUSY, at(10)), Verdict::Ok);
+        assert_eq!(
+            tracker.observe(BUSY, at(15)),
+            Verdict::Exhausted(Duration::from_secs(15))
+        );
+        // Only once per stretch
+        assert_eq!(tracker.observe(BUSY, at(20)), Verdict::Ok);
+        assert_eq!(tracker.observe(FREE, at(25)), Verdict::Recovered);
+
+        // A short blip after recovering starts the clock over
+        assert_eq!(tracker.observe(BUSY, at(30)), Verdict::Ok);
+        assert_eq!(tracker.observe(FREE, at(35)), Verdict::Ok);
+        assert_eq!(tracker.observe(BUSY, at(40)), Verdict::Ok);
+        assert_eq!(tracker.observe(BUSY, at(45)), Verdict::Ok);
+    }
+
+    #[test]
+    fn test_pool_still_growing_is_not_exhausted() {
+        let growing = PoolStats {
+            max: 5,
+            size: 3,
+            idle: 0,
+        };
+
+        assert!(!growing.is_exhausted());
+        assert!(BUSY.is_exhausted());
+        assert_eq!(growing.saturation(), 0.6);
+        assert_eq!(FREE.saturation(), 0.6);
+        assert_eq!(BUSY.saturation(), 1.0);
+    }
+}
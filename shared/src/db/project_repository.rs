@@ -0,0 +1,351 @@
+//! Repository for projects.
+//!
+//! Mirrors [`TaskRepository`](crate::db::TaskRepository): a unit struct with
+//! async associated functions that take the pool explicitly. A project's
+//! tasks are listed with `TaskRepository::find_by_project`.
+
+use tracing::instrument;
+
+use crate::db::{ensure_writable, DbPool};
+use crate::error::{AppError, AppResult};
+use crate::models::{CreateProject, Project, UpdateProject};
+
+/// Repository for project entity operations.
+pub struct ProjectRepository;
+
+impl ProjectRepository {
+    /// Create a new project.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `project` - Project data to insert
+    ///
+    /// # Returns
+    /// * `AppResult<Project>` - Created project with generated ID and timestamps
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the name or color is invalid
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip_all, level = "debug", fields(user_id = project.user_id))]
+    pub async fn create(pool: &DbPool, project: CreateProject) -> AppResult<Project> {
+        ensure_writable()?;
+        project.validate()?;
+
+        let project = sqlx::query_as::<_, Project>(
+            r#"
+            INSERT INTO projects (user_id, name, color)
+            VALUES (?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(project.user_id)
+        .bind(&project.name)
+        .bind(&project.color)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(project)
+    }
+
+    /// Find a project by its ID.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - Project ID to search for
+    ///
+    /// # Returns
+    /// * `AppResult<Project>` - Found project
+    ///
+    /// # Errors
+    /// * `AppError::NotFound` - If no project has the given ID
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_by_id(pool: &DbPool, id: i64) -> AppResult<Project> {
+        let project = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+        project.ok_or(AppError::NotFound {
+            entity: "Project",
+            id,
+        })
+    }
+
+    /// List a user's projects by name.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user whose projects to retrieve
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Project>>` - List of projects (empty vec if none)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_by_user(pool: &DbPool, user_id: i64) -> AppResult<Vec<Project>> {
+        let projects = sqlx::query_as::<_, Project>(
+            r#"
+            SELECT * FROM projects
+            WHERE user_id = ?
+            ORDER BY name ASC, id ASC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(projects)
+    }
+
+    /// Rename or recolor a project.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of project to update
+    /// * `project` - Fields to update (None fields are not updated)
+    ///
+    /// # Returns
+    /// * `AppResult<Project>` - Updated project
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If a provided field is invalid
+    /// * `AppError::NotFound` - If project doesn't exist
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, project), level = "debug")]
+    pub async fn update(pool: &DbPool, id: i64, project: UpdateProject) -> AppResult<Project> {
+        ensure_writable()?;
+        project.validate()?;
+
+        let project = sqlx::query_as::<_, Project>(
+            r#"
+            UPDATE projects
+            SET name = COALESCE(?, name), color = COALESCE(?, color),
+                updated_at = datetime('now')
+            WHERE id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(&project.name)
+        .bind(&project.color)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        project.ok_or(AppError::NotFound {
+            entity: "Project",
+            id,
+        })
+    }
+
+    /// Delete a project by ID.
+    ///
+    /// The project's tasks are kept; the database clears their
+    /// `project_id` (`ON DELETE SET NULL`).
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of project to delete
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::NotFound` - If project doesn't exist
+    /// * `AppError::Database` - If database deletion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn delete(pool: &DbPool, id: i64) -> AppResult<()> {
+        ensure_writable()?;
+        let result = sqlx::query("DELETE FROM projects WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound {
+                entity: "Project",
+                id,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        create_test_pool, create_test_task, create_test_user, new_test_task, TaskRepository,
+    };
+    use crate::models::{CreateTask, UpdateTask};
+
+    async fn new_project(pool: &DbPool, user_id: i64, name: &str) -> Project {
+        ProjectRepository::create(
+            pool,
+            CreateProject {
+                name: name.to_string(),
+                color: "#3366cc".to_string(),
+                user_id,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_create_update_and_list_projects() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let work = new_project(&pool, alice, "Work").await;
+        new_project(&pool, alice, "Home").await;
+
+        let renamed = ProjectRepository::update(
+            &pool,
+            work.id,
+            UpdateProject {
+                name: Some("Office".to_string()),
+                color: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(renamed.name, "Office");
+        assert_eq!(renamed.color, "#3366cc");
+
+        let names: Vec<String> = ProjectRepository::find_by_user(&pool, alice)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["Home", "Office"]);
+        assert!(matches!(
+            ProjectRepository::find_by_id(&pool, 9999).await,
+            Err(AppError::NotFound {
+                entity: "Project",
+                id: 9999
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_project_orphans_its_tasks() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let project = new_project(&pool, alice, "Launch").await;
+        let first = TaskRepository::create(
+            &pool,
+            CreateTask {
+                project_id: Some(project.id),
+                ..new_test_task(alice, "Grouped")
+            },
+        )
+        .await
+        .unwrap();
+        let second = create_test_task(&pool, alice, "Grouped").await.unwrap();
+        TaskRepository::update(
+            &pool,
+            second.id,
+            second.user_id,
+            UpdateTask::builder().project_id(project.id).build(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            TaskRepository::find_by_project(&pool, alice, project.id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        ProjectRepository::delete(&pool, project.id).await.unwrap();
+
+        // The tasks survive, just without a project
+        for id in [first.id, second.id] {
+            let task = TaskRepository::find_by_id(&pool, id).await.unwrap();
+            assert_eq!(task.project_id, None);
+        }
+        assert!(TaskRepository::find_by_project(&pool, alice, project.id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            ProjectRepository::delete(&pool, project.id).await,
+            Err(AppError::NotFound {
+                entity: "Project",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tasks_only_join_their_owners_projects() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let bobs = new_project(&pool, bob, "Secret").await;
+        TaskRepository::create(
+            &pool,
+            CreateTask {
+                project_id: Some(bobs.id),
+                ..new_test_task(bob, "Grouped")
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_test_task(&pool, alice, "Grouped").await.unwrap();
+
+        let err = TaskRepository::create(
+            &pool,
+            CreateTask {
+                project_id: Some(bobs.id),
+                ..new_test_task(alice, "Grouped")
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+        let err = TaskRepository::update(
+            &pool,
+            task.id,
+            task.user_id,
+            UpdateTask::builder().project_id(bobs.id).build(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+        assert!(matches!(
+            TaskRepository::create(
+                &pool,
+                CreateTask {
+                    project_id: Some(9999),
+                    ..new_test_task(alice, "Grouped")
+                }
+            )
+            .await,
+            Err(AppError::NotFound {
+                entity: "Project",
+                id: 9999
+            })
+        ));
+
+        // Alice can't list Bob's project by guessing its ID
+        assert!(TaskRepository::find_by_project(&pool, alice, bobs.id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            TaskRepository::find_by_project(&pool, bob, bobs.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Read-only mode for maintenance windows.
+//!
+//! While migrations or backups run, ops can keep the services up for
+//! reads but block writes: every mutating repository method starts with
+//! [`ensure_writable`], which fails with `AppError::ReadOnly` (503) while
+//! the flag is on. The flag starts from `READ_ONLY` in the config and can
+//! be flipped at runtime with SIGUSR1 (see [`spawn_read_only_toggle`]).
+//!
+//! Logins still go through, so the failed-attempt counters used for
+//! lockout keep being written.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{AppError, AppResult};
+
+/// Whether writes are currently blocked.
+///
+/// Process-wide rather than per pool, like the query timeout, since
+/// `DbPool` is sqlx's own type and can't carry it.
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// True while the service is in read-only mode.
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Turn read-only mode on or off.
+pub fn set_read_only(on: bool) {
+    READ_ONLY.store(on, Ordering::Relaxed);
+}
+
+/// Fail if the service is in read-only mode.
+///
+/// # Errors
+/// * `AppError::ReadOnly` - If read-only mode is on
+pub fn ensure_writable() -> AppResult<()> {
+    if is_read_only() {
+        Err(AppError::ReadOnly)
+    } else {
+        Ok(())
+    }
+}
+
+/// Toggle read-only mode each time the process receives SIGUSR1.
+///
+/// e.g. `kill -USR1 <pid>` before a backup and again after it.
+///
+/// # Errors
+/// * `AppError::Internal` - If the signal handler can't be installed
+#[cfg(unix)]
+pub fn spawn_read_only_toggle() -> AppResult<tokio::task::JoinHandle<()>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut signals = signal(SignalKind::user_defined1())
+        .map_err(|e| AppError::Internal(format!("Failed to install SIGUSR1 handler: {}", e)))?;
+
+    Ok(tokio::spawn(async move {
+        while signals.recv().await.is_some() {
+            let on = !is_read_only();
+            set_read_only(on);
+            tracing::warn!(
+                "Read-only mode {} (SIGUSR1)",
+                if on { "enabled" } else { "disabled" }
+            );
+        }
+    }))
+}
@@ -4,11 +4,76 @@
 //! abstraction over database operations. Each repository handles CRUD
 //! operations for a specific entity.
 
-use sqlx::{QueryBuilder, Sqlite};
+use std::collections::{HashMap, HashSet};
 
-use crate::db::DbPool;
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde_json::{Map, Value};
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection};
+use tracing::instrument;
+
+use crate::constants::{MAX_ACTIVITY_DAYS, MAX_PAGE_SIZE, MAX_TITLE_LENGTH};
+use crate::db::{ensure_writable, DbPool, TaskFilter, UserRepository};
 use crate::error::{AppError, AppResult};
-use crate::models::{CreateTask, Task, TaskPriority, TaskStatus, UpdateTask};
+use crate::models::{
+    normalize_tag, AuditAction, AuditEntry, CreateTask, Task, TaskPriority, TaskStats, TaskStatus,
+    TaskSummary, UpdateTask,
+};
+
+/// SQL condition matching overdue tasks.
+///
+/// A task is overdue when it has a due date that has passed and it isn't
+/// done. Expects the current time bound as the single `?` parameter.
+/// Shared by `find_overdue` and `stats_for_user` so they always agree.
+const OVERDUE_CONDITION: &str = "(due_date IS NOT NULL AND due_date < ? AND status != 'done')";
+
+/// SQL expression ranking `priority` from 0 (low) to 3 (urgent).
+///
+/// Priorities are stored as text, which would sort alphabetically.
+const PRIORITY_RANK: &str =
+    "(CASE priority WHEN 'urgent' THEN 3 WHEN 'high' THEN 2 WHEN 'medium' THEN 1 ELSE 0 END)";
+
+/// Task fields left out of audit entries: identity and bookkeeping, not content.
+const UNAUDITED_FIELDS: [&str; 5] = ["id", "position", "version", "created_at", "updated_at"];
+
+/// Pairs per statement in `set_priorities`. Each pair binds three
+/// parameters, keeping well under SQLite's default limit of 32766.
+const PRIORITY_BATCH_SIZE: usize = 1000;
+
+/// Task IDs per statement in `add_tag_to_many`.
+const TAG_BATCH_SIZE: usize = 1000;
+
+/// Columns bound per row in `bulk_insert`.
+const BULK_INSERT_COLUMNS: usize = 11;
+
+/// Rows per statement in `bulk_insert`, keeping the bound parameters
+/// under 999, the limit of SQLite builds before 3.32.
+const BULK_INSERT_BATCH_SIZE: usize = 999 / BULK_INSERT_COLUMNS;
+
+/// Lowest trigram similarity (0.0 to 1.0) at which `find_similar_titles`
+/// counts two titles as alike. Low enough for a word added or dropped,
+/// high enough that sharing one common word isn't a match.
+const SIMILAR_TITLE_THRESHOLD: f64 = 0.5;
+
+/// Deepest subtask level `complete_with_subtasks` will walk to. Real trees
+/// are a few levels deep; the bound stops a corrupt (cyclic) tree from
+/// recursing forever.
+const MAX_SUBTASK_DEPTH: i64 = 32;
+
+/// Appended to a title by `duplicate`.
+const COPY_SUFFIX: &str = " (copy)";
+
+/// A task and everything under it, as `subtree(id, depth)` with the task
+/// itself at depth 0. Binds the task ID, then the deepest depth to walk to.
+const SUBTREE_CTE: &str = r#"
+    WITH RECURSIVE subtree(id, depth) AS (
+        SELECT id, 0 FROM tasks WHERE id = ?
+        UNION ALL
+        SELECT tasks.id, subtree.depth + 1
+        FROM tasks JOIN subtree ON tasks.parent_id = subtree.id
+        WHERE subtree.depth < ?
+    )
+"#;
 
 /// Repository for task entity operations.
 ///
@@ -19,6 +84,8 @@ pub struct TaskRepository;
 impl TaskRepository {
     /// Create a new Task in the database.
     ///
+    /// Records an `AuditAction::Create` entry in the same transaction.
+    ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `task` - Task data to insert
@@ -27,26 +94,335 @@ impl TaskRepository {
     /// * `AppResult<Task>` - Created task with generated ID and timestamps
     ///
     /// # Errors
+    /// * `AppError::NotFound` - If `project_id` names no project
+    /// * `AppError::Forbidden` - If `project_id` is another user's project,
+    ///   or `parent_id` another user's task
+    /// * `AppError::TaskNotFound` - If `parent_id` names no task
     /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip_all, level = "debug", fields(user_id = task.user_id))]
     pub async fn create(pool: &DbPool, task: CreateTask) -> AppResult<Task> {
-        // Insert the task and get the inserted row back
-        let task = sqlx::query_as::<_, Task>(
-            r#"
-            INSERT INTO tasks (title, description, status, priority, due_date, user_id)
-            VALUES (?, ?, ?, ?, ?, ?)
-            RETURNING *
-            "#,
-        )
-        .bind(&task.title)
-        .bind(&task.description)
-        .bind(&task.status)
-        .bind(&task.priority)
-        .bind(&task.due_date)
-        .bind(task.user_id)
-        .fetch_one(pool)
-        .await?;
+        ensure_writable()?;
+        timed!("create", {
+            let mut tx = pool.begin().await?;
+            ensure_project_owner(&mut tx, task.project_id, task.user_id).await?;
+            ensure_task_owner(&mut tx, task.parent_id, task.user_id).await?;
+
+            // Insert the task and get the inserted row back
+            let task = sqlx::query_as::<_, Task>(
+                r#"
+                INSERT INTO tasks (title, description, status, priority, due_date, user_id, recurrence,
+                                   estimated_minutes, actual_minutes, project_id, parent_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                RETURNING *
+                "#,
+            )
+            .bind(&task.title)
+            .bind(&task.description)
+            .bind(&task.status)
+            .bind(task.priority)
+            .bind(task.due_date)
+            .bind(task.user_id)
+            .bind(task.recurrence)
+            .bind(task.estimated_minutes)
+            .bind(task.actual_minutes)
+            .bind(task.project_id)
+            .bind(task.parent_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let fields = audit_fields(&task)?;
+            record_audit(
+                &mut tx,
+                &task,
+                task.user_id,
+                AuditAction::Create,
+                None,
+                Some(fields),
+            )
+            .await?;
+            tx.commit().await?;
+
+            Ok(task)
+        })
+    }
+
+    /// Create a task only if the user has no open task with the same title.
+    ///
+    /// Intended for automation that runs repeatedly (e.g. reminders), where
+    /// creating the same task twice would be noise.
+    ///
+    /// # Matching rule
+    /// An existing task matches when it belongs to `user_id`, its status is
+    /// not `Done`, and its title equals `task.title` ignoring ASCII case
+    /// (SQLite's `lower()`). Completed tasks never match, so a new task is
+    /// created once the previous one is done.
+    ///
+    /// The lookup and insert run inside an `IMMEDIATE` transaction, which takes
+    /// the write lock up front so two concurrent callers cannot both miss the
+    /// lookup and insert duplicates. A created task gets an
+    /// `AuditAction::Create` entry; returning an existing one records nothing.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - Owner of the task (overrides `task.user_id`)
+    /// * `task` - Task data to insert if no match exists
+    ///
+    /// # Returns
+    /// * `AppResult<(Task, bool)>` - The existing or new task, and `true` if it was created
+    ///
+    /// # Errors
+    /// * `AppError::NotFound` - If `project_id` names no project
+    /// * `AppError::Forbidden` - If `project_id` is another user's project,
+    ///   or `parent_id` another user's task
+    /// * `AppError::TaskNotFound` - If `parent_id` names no task
+    /// * `AppError::Database` - If the lookup or insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, task), level = "debug")]
+    pub async fn create_if_absent(
+        pool: &DbPool,
+        user_id: i64,
+        task: CreateTask,
+    ) -> AppResult<(Task, bool)> {
+        ensure_writable()?;
+        timed!("create_if_absent", {
+            let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+
+            let existing = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ? AND status != ? AND lower(title) = lower(?)
+                ORDER BY created_at ASC, id ASC
+                LIMIT 1
+                "#,
+            )
+            .bind(user_id)
+            .bind(TaskStatus::Done)
+            .bind(&task.title)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            if let Some(existing) = existing {
+                tx.commit().await?;
+                return Ok((existing, false));
+            }
+            ensure_project_owner(&mut tx, task.project_id, user_id).await?;
+            ensure_task_owner(&mut tx, task.parent_id, user_id).await?;
+
+            let created = sqlx::query_as::<_, Task>(
+                r#"
+                INSERT INTO tasks (title, description, status, priority, due_date, user_id, recurrence,
+                                   estimated_minutes, actual_minutes, project_id, parent_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                RETURNING *
+                "#,
+            )
+            .bind(&task.title)
+            .bind(&task.description)
+            .bind(&task.status)
+            .bind(task.priority)
+            .bind(task.due_date)
+            .bind(user_id)
+            .bind(task.recurrence)
+            .bind(task.estimated_minutes)
+            .bind(task.actual_minutes)
+            .bind(task.project_id)
+            .bind(task.parent_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let fields = audit_fields(&created)?;
+            record_audit(
+                &mut tx,
+                &created,
+                user_id,
+                AuditAction::Create,
+                None,
+                Some(fields),
+            )
+            .await?;
+            tx.commit().await?;
+
+            Ok((created, true))
+        })
+    }
+
+    /// Import a batch of tasks for a user from a JSON array.
+    ///
+    /// Each element has the same shape as [`CreateTask`]; any `user_id` in the
+    /// JSON is ignored and replaced with `user_id`. Every task is validated
+    /// and the inserts run in one transaction, so a single bad record rolls
+    /// back the whole batch. Each task gets an `AuditAction::Create` entry.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - Owner of the imported tasks
+    /// * `json` - JSON array of tasks
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - The created tasks, in input order
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the JSON is malformed or any task is invalid
+    /// * `AppError::NotFound` - If a task names a project that doesn't exist
+    /// * `AppError::Forbidden` - If a task names another user's project or
+    ///   parent task
+    /// * `AppError::TaskNotFound` - If a task names a parent that doesn't exist
+    /// * `AppError::Database` - If an insert fails (nothing is imported)
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, json), level = "debug")]
+    pub async fn import_json(pool: &DbPool, user_id: i64, json: &str) -> AppResult<Vec<Task>> {
+        ensure_writable()?;
+        timed!("import_json", {
+            let tasks: Vec<CreateTask> = serde_json::from_str(json)
+                .map_err(|e| AppError::Validation(format!("Invalid import JSON: {}", e)))?;
+
+            for (index, task) in tasks.iter().enumerate() {
+                task.validate().map_err(|e| match e {
+                    AppError::Validation(msg) => {
+                        AppError::Validation(format!("Task {}: {}", index + 1, msg))
+                    }
+                    other => other,
+                })?;
+            }
+
+            // Dropping the transaction without committing rolls it back
+            let mut tx = pool.begin().await?;
+            let mut created = Vec::with_capacity(tasks.len());
+
+            for task in tasks {
+                ensure_project_owner(&mut tx, task.project_id, user_id).await?;
+                ensure_task_owner(&mut tx, task.parent_id, user_id).await?;
+                let row = sqlx::query_as::<_, Task>(
+                    r#"
+                    INSERT INTO tasks (title, description, status, priority, due_date, user_id, recurrence,
+                                       estimated_minutes, actual_minutes, project_id, parent_id)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    RETURNING *
+                    "#,
+                )
+                .bind(&task.title)
+                .bind(&task.description)
+                .bind(&task.status)
+                .bind(task.priority)
+                .bind(task.due_date)
+                .bind(user_id)
+                .bind(task.recurrence)
+                .bind(task.estimated_minutes)
+                .bind(task.actual_minutes)
+                .bind(task.project_id)
+                .bind(task.parent_id)
+                .fetch_one(&mut *tx)
+                .await?;
+                let fields = audit_fields(&row)?;
+                record_audit(
+                    &mut tx,
+                    &row,
+                    user_id,
+                    AuditAction::Create,
+                    None,
+                    Some(fields),
+                )
+                .await?;
+                created.push(row);
+            }
+
+            tx.commit().await?;
+
+            Ok(created)
+        })
+    }
+
+    /// Insert many tasks with one multi-row `INSERT` per batch.
+    ///
+    /// Much faster than calling [`create`](Self::create) in a loop for
+    /// thousands of tasks. Every task is validated first, then the batches
+    /// run in one transaction, so a bad record or a failed batch inserts
+    /// nothing. Unlike `import_json`, each task keeps its own `user_id`
+    /// and the created rows aren't returned. An empty slice does nothing.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `tasks` - Tasks to insert
+    ///
+    /// # Returns
+    /// * `AppResult<u64>` - Number of tasks inserted
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If any task is invalid
+    /// * `AppError::NotFound` - If a task names a project that doesn't exist
+    /// * `AppError::Forbidden` - If a task names a project or parent task its
+    ///   user doesn't own
+    /// * `AppError::TaskNotFound` - If a task names a parent that doesn't
+    ///   exist (yet; parents can't be in the same batch)
+    /// * `AppError::Database` - If an insert fails, e.g. for an unknown user
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip_all, level = "debug", fields(count = tasks.len()))]
+    pub async fn bulk_insert(pool: &DbPool, tasks: &[CreateTask]) -> AppResult<u64> {
+        ensure_writable()?;
+        timed!("bulk_insert", {
+            if tasks.is_empty() {
+                return Ok(0);
+            }
+
+            for (index, task) in tasks.iter().enumerate() {
+                task.validate().map_err(|e| match e {
+                    AppError::Validation(msg) => {
+                        AppError::Validation(format!("Task {}: {}", index + 1, msg))
+                    }
+                    other => other,
+                })?;
+            }
+
+            let mut tx = pool.begin().await?;
+            // One lookup per distinct project and owner, not per task
+            let projects: HashSet<(i64, i64)> = tasks
+                .iter()
+                .filter_map(|task| Some((task.project_id?, task.user_id)))
+                .collect();
+            for (project_id, user_id) in projects {
+                ensure_project_owner(&mut tx, Some(project_id), user_id).await?;
+            }
+            // Parents have to exist already, not come later in `tasks`
+            let parents: HashSet<(i64, i64)> = tasks
+                .iter()
+                .filter_map(|task| Some((task.parent_id?, task.user_id)))
+                .collect();
+            for (parent_id, user_id) in parents {
+                ensure_task_owner(&mut tx, Some(parent_id), user_id).await?;
+            }
+            let mut inserted = 0;
+
+            for batch in tasks.chunks(BULK_INSERT_BATCH_SIZE) {
+                let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+                    "INSERT INTO tasks (title, description, status, priority, due_date, user_id, \
+                     recurrence, estimated_minutes, actual_minutes, project_id, parent_id) ",
+                );
+                query_builder.push_values(batch, |mut row, task| {
+                    row.push_bind(&task.title)
+                        .push_bind(&task.description)
+                        .push_bind(&task.status)
+                        .push_bind(task.priority)
+                        .push_bind(task.due_date)
+                        .push_bind(task.user_id)
+                        .push_bind(task.recurrence)
+                        .push_bind(task.estimated_minutes)
+                        .push_bind(task.actual_minutes)
+                        .push_bind(task.project_id)
+                        .push_bind(task.parent_id);
+                });
+
+                inserted += query_builder
+                    .build()
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            }
 
-        Ok(task)
+            tx.commit().await?;
+
+            Ok(inserted)
+        })
     }
 
     /// Find a task by its ID.
@@ -61,19 +437,22 @@ impl TaskRepository {
     /// # Errors
     /// * `AppError::TaskNotFound` - If task with given ID doesn't exist
     /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
     pub async fn find_by_id(pool: &DbPool, id: i64) -> AppResult<Task> {
-        let task = sqlx::query_as::<_, Task>(
-            r#"
-            SELECT * FROM tasks
-            WHERE id = ?
-            "#,
-        )
-        .bind(id)
-        .fetch_optional(pool)
-        .await?;
+        timed!("find_by_id", {
+            let task = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE id = ?
+                "#,
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
 
-        // Convert Option<Task> to Result<Task, AppError>
-        task.ok_or(AppError::TaskNotFound(id))
+            // Convert Option<Task> to Result<Task, AppError>
+            task.ok_or(AppError::TaskNotFound(id))
+        })
     }
 
     /// Find all tasks for a specific user.
@@ -87,19 +466,175 @@ impl TaskRepository {
     ///
     /// # Errors
     /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
     pub async fn find_by_user(pool: &DbPool, user_id: i64) -> AppResult<Vec<Task>> {
-        let tasks = sqlx::query_as::<_, Task>(
-            r#"
-            SELECT * FROM tasks
-            WHERE user_id = ?
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(user_id)
-        .fetch_all(pool)
-        .await?;
+        timed!("find_by_user", {
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ?
+                ORDER BY created_at DESC
+                "#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// Find all of a user's tasks in their manual order (see [`reorder`](Self::reorder)).
+    ///
+    /// Tasks are sorted by `position`; tasks created since the last reorder
+    /// have position 0, so they come first, newest first.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user whose tasks to retrieve
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - List of tasks (empty vec if none found)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_by_user_ordered(pool: &DbPool, user_id: i64) -> AppResult<Vec<Task>> {
+        timed!("find_by_user_ordered", {
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ?
+                ORDER BY position ASC, created_at DESC, id DESC
+                "#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// List a user's tasks for display, newest first, loading only the
+    /// columns in [`TaskSummary`].
+    ///
+    /// Same order as [`find_by_user`](Self::find_by_user), but without the
+    /// descriptions, so large lists stay cheap.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user whose tasks to list
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<TaskSummary>>` - List of summaries (empty vec if none found)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn list_summaries(pool: &DbPool, user_id: i64) -> AppResult<Vec<TaskSummary>> {
+        timed!("list_summaries", {
+            let summaries = sqlx::query_as::<_, TaskSummary>(
+                r#"
+                SELECT id, title, status, priority, due_date FROM tasks
+                WHERE user_id = ?
+                ORDER BY created_at DESC
+                "#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(summaries)
+        })
+    }
+
+    /// Find a user's tasks in a project, newest first.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    /// * `project_id` - ID of the project
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - List of tasks (empty vec if none found,
+    ///   including when the project doesn't exist or isn't the user's)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_by_project(
+        pool: &DbPool,
+        user_id: i64,
+        project_id: i64,
+    ) -> AppResult<Vec<Task>> {
+        timed!("find_by_project", {
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ? AND project_id = ?
+                ORDER BY created_at DESC, id DESC
+                "#,
+            )
+            .bind(user_id)
+            .bind(project_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// Fetch one page of a user's tasks using a cursor.
+    ///
+    /// Tasks are returned newest first. Pass `None` for the first page, then
+    /// the `id` of the last task you received as `after_id` to get the next
+    /// one. Because the cursor is an id rather than an offset, tasks created
+    /// or deleted while paging don't shift rows between pages. A page with
+    /// fewer than `limit` rows is the last one.
+    ///
+    /// IDs are assigned in creation order, so ordering by `id DESC` is the
+    /// same as newest first and gives a stable tie-break.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    /// * `after_id` - Last id from the previous page, or `None` to start
+    /// * `limit` - Page size (1 to `MAX_PAGE_SIZE`)
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - Up to `limit` tasks
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `limit` is out of range
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_by_user_after(
+        pool: &DbPool,
+        user_id: i64,
+        after_id: Option<i64>,
+        limit: i64,
+    ) -> AppResult<Vec<Task>> {
+        timed!("find_by_user_after", {
+            validate_limit(limit)?;
 
-        Ok(tasks)
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ? AND (? IS NULL OR id < ?)
+                ORDER BY id DESC
+                LIMIT ?
+                "#,
+            )
+            .bind(user_id)
+            .bind(after_id)
+            .bind(after_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tasks)
+        })
     }
 
     /// Find tasks by user with status filter.
@@ -114,24 +649,27 @@ impl TaskRepository {
     ///
     /// # Errors
     /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool, status), level = "debug")]
     pub async fn find_by_user_and_status(
         pool: &DbPool,
         user_id: i64,
         status: TaskStatus,
     ) -> AppResult<Vec<Task>> {
-        let tasks = sqlx::query_as::<_, Task>(
-            r#"
-            SELECT * FROM tasks
-            WHERE user_id = ? AND status = ?
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(user_id)
-        .bind(status)
-        .fetch_all(pool)
-        .await?;
+        timed!("find_by_user_and_status", {
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ? AND status = ?
+                ORDER BY created_at DESC
+                "#,
+            )
+            .bind(user_id)
+            .bind(status)
+            .fetch_all(pool)
+            .await?;
 
-        Ok(tasks)
+            Ok(tasks)
+        })
     }
 
     /// Find tasks by user with priority filter.
@@ -146,195 +684,3716 @@ impl TaskRepository {
     ///
     /// # Errors
     /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool, priority), level = "debug")]
     pub async fn find_by_user_and_priority(
         pool: &DbPool,
         user_id: i64,
         priority: TaskPriority,
     ) -> AppResult<Vec<Task>> {
-        let tasks = sqlx::query_as::<_, Task>(
-            r#"
-            SELECT * FROM tasks
-            WHERE user_id = ? AND priority = ?
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(user_id)
-        .bind(priority)
-        .fetch_all(pool)
-        .await?;
+        timed!("find_by_user_and_priority", {
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ? AND priority = ?
+                ORDER BY created_at DESC
+                "#,
+            )
+            .bind(user_id)
+            .bind(priority)
+            .fetch_all(pool)
+            .await?;
 
-        Ok(tasks)
+            Ok(tasks)
+        })
     }
 
-    /// Update an existing task.
+    /// Find a user's tasks with any of the given priorities, newest first.
     ///
-    /// Only updates fields that are provided (not None).
+    /// An empty `priorities` slice applies no priority filter and returns
+    /// all of the user's tasks.
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
-    /// * `id` - ID of task to update
-    /// * `task` - Fields to update (None fields are not updated)
+    /// * `user_id` - ID of the user
+    /// * `priorities` - Priorities to match
     ///
     /// # Returns
-    /// * `AppResult<Task>` - Updated task
+    /// * `AppResult<Vec<Task>>` - List of matching tasks (empty vec if none)
     ///
     /// # Errors
-    /// * `AppError::TaskNotFound` - If task doesn't exist
-    /// * `AppError::Database` - If database update fails
-    pub async fn update(pool: &DbPool, id: i64, task: UpdateTask) -> AppResult<Task> {
-        // First, verify the task exists
-        let existing = Self::find_by_id(pool, id).await?;
-
-        // Build dynamic UPDATE query based on which fields are provided
-        let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE tasks SET ");
-        let mut has_updates = false;
-
-        // Add title if provided
-        if let Some(title) = &task.title {
-            query_builder.push("title = ");
-            query_builder.push_bind(title);
-            has_updates = true;
-        }
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_by_priorities(
+        pool: &DbPool,
+        user_id: i64,
+        priorities: &[TaskPriority],
+    ) -> AppResult<Vec<Task>> {
+        let filter = TaskFilter::new(user_id).priorities(priorities.iter().copied());
+        Self::find_filtered(pool, filter).await
+    }
 
-        // Add description if provided
-        if let Some(description) = &task.description {
-            if has_updates {
-                query_builder.push(", ");
+    /// Find a user's tasks matching every criterion set on `filter`.
+    ///
+    /// Builds the WHERE clause dynamically, adding (and binding) a condition
+    /// only for the fields that are set. Results are ordered by
+    /// `filter.sort` (newest first by default) and paged by `filter.limit`
+    /// and `filter.offset` when set.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `filter` - Criteria to apply (see [`TaskFilter`])
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - Matching tasks (empty vec if none)
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `limit` is out of range or `offset` is negative
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip_all, level = "debug", fields(user_id = filter.user_id))]
+    pub async fn find_filtered(pool: &DbPool, filter: TaskFilter) -> AppResult<Vec<Task>> {
+        timed!("find_filtered", {
+            if let Some(limit) = filter.limit {
+                validate_limit(limit)?;
+            }
+            if let Some(offset) = filter.offset {
+                validate_offset(offset)?;
             }
-            query_builder.push("description = ");
-            query_builder.push_bind(description);
-            has_updates = true;
-        }
 
-        // Add status if provided
-        if let Some(status) = &task.status {
-            if has_updates {
-                query_builder.push(", ");
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("SELECT * FROM tasks WHERE user_id = ");
+            query_builder.push_bind(filter.user_id);
+
+            if let Some(status) = filter.status {
+                query_builder.push(" AND status = ");
+                query_builder.push_bind(status);
             }
-            query_builder.push("status = ");
-            query_builder.push_bind(status);
-            has_updates = true;
-        }
 
-        // Add priority if provided
-        if let Some(priority) = &task.priority {
-            if has_updates {
-                query_builder.push(", ");
+            if !filter.priorities.is_empty() {
+                query_builder.push(" AND priority IN (");
+                let mut priorities = query_builder.separated(", ");
+                for priority in filter.priorities {
+                    priorities.push_bind(priority);
+                }
+                query_builder.push(")");
             }
-            query_builder.push("priority = ");
-            query_builder.push_bind(priority);
-            has_updates = true;
-        }
 
-        // Add due_date if provided (including None to clear it)
-        if task.due_date.is_some() {
-            if has_updates {
-                query_builder.push(", ");
+            if let Some(due_before) = filter.due_before {
+                query_builder.push(" AND due_date IS NOT NULL AND due_date < ");
+                query_builder.push_bind(due_before);
             }
-            query_builder.push("due_date = ");
-            query_builder.push_bind(&task.due_date);
-            has_updates = true;
-        }
 
-        // Update the updated_at timestamp
-        if has_updates {
-            query_builder.push(", ");
-        }
-        query_builder.push("updated_at = datetime('now')");
+            if let Some(search) = filter.search {
+                // Escape LIKE wildcards so the text is matched literally
+                let pattern = format!(
+                    "%{}%",
+                    search
+                        .replace('\\', "\\\\")
+                        .replace('%', "\\%")
+                        .replace('_', "\\_")
+                );
+                query_builder.push(" AND (title LIKE ");
+                query_builder.push_bind(pattern.clone());
+                query_builder.push(" ESCAPE '\\' OR description LIKE ");
+                query_builder.push_bind(pattern);
+                query_builder.push(" ESCAPE '\\')");
+            }
 
-        // Add WHERE clause
-        query_builder.push(" WHERE id = ");
-        query_builder.push_bind(id);
+            query_builder.push(" ORDER BY ");
+            query_builder.push(filter.sort.order_by());
 
-        // Execute the update
-        query_builder.build().execute(pool).await?;
+            // SQLite only allows OFFSET after a LIMIT; -1 means no limit
+            query_builder.push(" LIMIT ");
+            query_builder.push_bind(filter.limit.unwrap_or(-1));
+            if let Some(offset) = filter.offset {
+                query_builder.push(" OFFSET ");
+                query_builder.push_bind(offset);
+            }
+
+            let tasks = query_builder
+                .build_query_as::<Task>()
+                .fetch_all(pool)
+                .await?;
 
-        // Fetch and return the updated task
-        Self::find_by_id(pool, id).await
+            Ok(tasks)
+        })
     }
 
-    /// Delete a task by ID.
+    /// Find a user's overdue tasks.
+    ///
+    /// A task is overdue when its due date has passed and it isn't done.
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
-    /// * `id` - ID of task to delete
+    /// * `user_id` - ID of the user
     ///
     /// # Returns
-    /// * `AppResult<()>` - Success or error
+    /// * `AppResult<Vec<Task>>` - Overdue tasks, most overdue first
     ///
     /// # Errors
-    /// * `AppError::TaskNotFound` - If task doesn't exist
-    /// * `AppError::Database` - If database deletion fails
-    pub async fn delete(pool: &DbPool, id: i64) -> AppResult<()> {
-        let result = sqlx::query(
-            r#"
-            DELETE FROM tasks
-            WHERE id = ?
-            "#,
-        )
-        .bind(id)
-        .execute(pool)
-        .await?;
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_overdue(pool: &DbPool, user_id: i64) -> AppResult<Vec<Task>> {
+        timed!("find_overdue", {
+            let sql = format!(
+                "SELECT * FROM tasks WHERE user_id = ? AND {} ORDER BY due_date ASC",
+                OVERDUE_CONDITION
+            );
 
-        // Check if any rows were affected
-        if result.rows_affected() == 0 {
-            return Err(AppError::TaskNotFound(id));
-        }
+            let tasks = sqlx::query_as::<_, Task>(&sql)
+                .bind(user_id)
+                .bind(Utc::now())
+                .fetch_all(pool)
+                .await?;
 
-        Ok(())
+            Ok(tasks)
+        })
     }
 
-    /// Count total tasks for a user.
+    /// Find a user's tasks due within a date range, for calendar views.
+    ///
+    /// Both ends of the range are inclusive. Tasks without a due date are
+    /// never included.
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
     /// * `user_id` - ID of the user
+    /// * `from` - Start of the range
+    /// * `to` - End of the range
     ///
     /// # Returns
-    /// * `AppResult<i64>` - Total count of tasks
+    /// * `AppResult<Vec<Task>>` - Matching tasks, soonest due first
     ///
     /// # Errors
+    /// * `AppError::Validation` - If `from` is after `to`
     /// * `AppError::Database` - If database query fails
-    pub async fn count_by_user(pool: &DbPool, user_id: i64) -> AppResult<i64> {
-        let count: (i64,) = sqlx::query_as(
-            r#"
-            SELECT COUNT(*) FROM tasks
-            WHERE user_id = ?
-            "#,
-        )
-        .bind(user_id)
-        .fetch_one(pool)
-        .await?;
+    #[instrument(skip(pool, from, to), level = "debug")]
+    pub async fn find_by_due_range(
+        pool: &DbPool,
+        user_id: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> AppResult<Vec<Task>> {
+        timed!("find_by_due_range", {
+            if from > to {
+                return Err(AppError::Validation(
+                    "Range start must not be after its end".to_string(),
+                ));
+            }
+
+            let tasks = sqlx::query_as::<_, Task>(
+                "SELECT * FROM tasks WHERE user_id = ? AND due_date IS NOT NULL AND due_date >= ? AND due_date <= ? ORDER BY due_date ASC, id ASC",
+            )
+            .bind(user_id)
+            .bind(from)
+            .bind(to)
+            .fetch_all(pool)
+            .await?;
 
-        Ok(count.0)
+            Ok(tasks)
+        })
     }
 
-    /// Check if a task belongs to a specific user.
+    /// Find a user's tasks that have no due date yet, for triage.
     ///
-    /// Useful for authorization checks.
+    /// The complement of the due-date queries: done tasks are left out,
+    /// since they no longer need scheduling.
     ///
     /// # Arguments
     /// * `pool` - Database connection pool
-    /// * `task_id` - ID of the task
     /// * `user_id` - ID of the user
     ///
     /// # Returns
-    /// * `AppResult<bool>` - True if task belongs to user
+    /// * `AppResult<Vec<Task>>` - Open tasks without a due date, highest
+    ///   priority first, then oldest first
     ///
     /// # Errors
     /// * `AppError::Database` - If database query fails
-    pub async fn belongs_to_user(pool: &DbPool, task_id: i64, user_id: i64) -> AppResult<bool> {
-        let exists: (i64,) = sqlx::query_as(
-            r#"
-            SELECT COUNT(*) FROM tasks
-            WHERE id = ? AND user_id = ?
-            "#,
-        )
-        .bind(task_id)
-        .bind(user_id)
-        .fetch_one(pool)
-        .await?;
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_unscheduled(pool: &DbPool, user_id: i64) -> AppResult<Vec<Task>> {
+        timed!("find_unscheduled", {
+            let sql = format!(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ? AND due_date IS NULL AND status != ?
+                ORDER BY {} DESC, created_at ASC, id ASC
+                "#,
+                PRIORITY_RANK
+            );
+
+            let tasks = sqlx::query_as::<_, Task>(&sql)
+                .bind(user_id)
+                .bind(TaskStatus::Done)
+                .fetch_all(pool)
+                .await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// Find a user's tasks with titles similar to `title`, for "you may
+    /// already have this" hints before creating a task.
+    ///
+    /// Titles are compared after normalizing case, punctuation, and
+    /// spacing, by the share of three-letter sequences they have in common,
+    /// so "Buy milk" matches "buy milk!" and "Buy the milk" but not
+    /// "Buy stamps". Tasks of any status are considered.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    /// * `title` - The title about to be used
+    /// * `limit` - Most tasks to return (1 to `MAX_PAGE_SIZE`)
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - Similar tasks, closest first (ties by ID);
+    ///   empty if `title` has no letters or digits
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `limit` is out of range
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_similar_titles(
+        pool: &DbPool,
+        user_id: i64,
+        title: &str,
+        limit: i64,
+    ) -> AppResult<Vec<Task>> {
+        timed!("find_similar_titles", {
+            validate_limit(limit)?;
+            let wanted = trigrams(&normalize_title(title));
+            if wanted.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            // Score on titles alone, then load only the rows that made the cut
+            let titles: Vec<(i64, String)> = sqlx::query_as(
+                r#"
+                SELECT id, title FROM tasks
+                WHERE user_id = ?
+                ORDER BY id ASC
+                "#,
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+            let mut scored: Vec<(f64, i64)> = titles
+                .into_iter()
+                .map(|(id, title)| (similarity(&wanted, &trigrams(&normalize_title(&title))), id))
+                .filter(|(score, _)| *score >= SIMILAR_TITLE_THRESHOLD)
+                .collect();
+            // Stable sort, so equal scores stay in ID order
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+            scored.truncate(limit as usize);
+            if scored.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let mut query_builder: QueryBuilder<Sqlite> =
+                QueryBuilder::new("SELECT * FROM tasks WHERE id IN (");
+            let mut ids = query_builder.separated(", ");
+            for (_, id) in &scored {
+                ids.push_bind(*id);
+            }
+            query_builder.push(")");
+            let mut tasks: HashMap<i64, Task> = query_builder
+                .build_query_as::<Task>()
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(|task| (task.id, task))
+                .collect();
+
+            // A task deleted since the first query is just left out
+            Ok(scored
+                .into_iter()
+                .filter_map(|(_, id)| tasks.remove(&id))
+                .collect())
+        })
+    }
+
+    /// Find a user's tasks due today in their own timezone.
+    ///
+    /// "Today" runs from midnight to midnight in `User::timezone` (UTC if
+    /// unset), so a task due at 23:00 local time counts even when that's
+    /// already tomorrow in UTC. Done tasks are included.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - Matching tasks, soonest due first
+    ///
+    /// # Errors
+    /// * `AppError::UserNotFound` - If no user has the given ID
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_due_today(pool: &DbPool, user_id: i64) -> AppResult<Vec<Task>> {
+        Self::find_due_today_at(pool, user_id, Utc::now()).await
+    }
+
+    /// Like [`find_due_today`](Self::find_due_today), but for the user's
+    /// local day containing `now`.
+    ///
+    /// # Errors
+    /// * `AppError::UserNotFound` - If no user has the given ID
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_due_today_at(
+        pool: &DbPool,
+        user_id: i64,
+        now: DateTime<Utc>,
+    ) -> AppResult<Vec<Task>> {
+        timed!("find_due_today", {
+            let tz = UserRepository::find_by_id(pool, user_id).await?.tz();
+            let today = now.with_timezone(&tz).date_naive();
+            let start = start_of_local_day(tz, today);
+            let end = start_of_local_day(tz, today + chrono::Days::new(1));
+
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ? AND due_date IS NOT NULL AND due_date >= ? AND due_date < ?
+                ORDER BY due_date ASC, id ASC
+                "#,
+            )
+            .bind(user_id)
+            .bind(start)
+            .bind(end)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// Find a user's unfinished tasks due in the next `hours` hours, for reminders.
+    ///
+    /// Tasks that are already overdue aren't included; `find_overdue`
+    /// covers those.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    /// * `hours` - How far ahead to look
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - Matching tasks, soonest due first
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `hours` is negative
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_due_within(pool: &DbPool, user_id: i64, hours: i64) -> AppResult<Vec<Task>> {
+        timed!("find_due_within", {
+            if hours < 0 {
+                return Err(AppError::Validation(
+                    "hours must not be negative".to_string(),
+                ));
+            }
+
+            let now = Utc::now();
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ? AND status != ?
+                  AND due_date IS NOT NULL AND due_date >= ? AND due_date <= ?
+                ORDER BY due_date ASC, id ASC
+                "#,
+            )
+            .bind(user_id)
+            .bind(TaskStatus::Done)
+            .bind(now)
+            .bind(now + chrono::Duration::hours(hours))
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// A user's recently created or edited tasks, for an activity feed.
+    ///
+    /// A task is included if its `created_at` or `updated_at` is within the
+    /// last `days` days, and appears once however often it was edited.
+    /// Tasks are ordered by their latest activity (the later of the two
+    /// timestamps), newest first.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    /// * `days` - How far back to look (1 to `MAX_ACTIVITY_DAYS`)
+    /// * `limit` - Most tasks to return (1 to `MAX_PAGE_SIZE`)
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - Up to `limit` tasks
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `days` or `limit` is out of range
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn recent_activity(
+        pool: &DbPool,
+        user_id: i64,
+        days: i64,
+        limit: i64,
+    ) -> AppResult<Vec<Task>> {
+        timed!("recent_activity", {
+            if !(1..=MAX_ACTIVITY_DAYS).contains(&days) {
+                return Err(AppError::Validation(format!(
+                    "days must be between 1 and {}",
+                    MAX_ACTIVITY_DAYS
+                )));
+            }
+            validate_limit(limit)?;
+
+            // Timestamps are stored by datetime('now'), so compare against
+            // the same format rather than a bound DateTime
+            let since = format!("-{} days", days);
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT * FROM tasks
+                WHERE user_id = ?
+                  AND (created_at >= datetime('now', ?) OR updated_at >= datetime('now', ?))
+                ORDER BY max(created_at, updated_at) DESC, id DESC
+                LIMIT ?
+                "#,
+            )
+            .bind(user_id)
+            .bind(&since)
+            .bind(&since)
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// Count a user's tasks created on each of the last `days` days, for a
+    /// creation chart.
+    ///
+    /// The window ends today (UTC) and includes it. Every day in the window
+    /// gets a row, with a count of zero if nothing was created, so the
+    /// chart has no gaps.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    /// * `days` - How many days to cover (1 to `MAX_ACTIVITY_DAYS`)
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<(String, i64)>>` - `days` pairs of a `YYYY-MM-DD`
+    ///   date and its count, oldest first
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `days` is out of range
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn created_per_day(
+        pool: &DbPool,
+        user_id: i64,
+        days: i64,
+    ) -> AppResult<Vec<(String, i64)>> {
+        timed!("created_per_day", {
+            if !(1..=MAX_ACTIVITY_DAYS).contains(&days) {
+                return Err(AppError::Validation(format!(
+                    "days must be between 1 and {}",
+                    MAX_ACTIVITY_DAYS
+                )));
+            }
+
+            // The recursive CTE lists every day in the window; the LEFT JOIN
+            // leaves days without tasks at COUNT(tasks.id) = 0
+            let first_day = format!("-{} days", days - 1);
+            let counts: Vec<(String, i64)> = sqlx::query_as(
+                r#"
+                WITH RECURSIVE days(day) AS (
+                    SELECT date('now', ?)
+                    UNION ALL
+                    SELECT date(day, '+1 day') FROM days WHERE day < date('now')
+                )
+                SELECT days.day, COUNT(tasks.id)
+                FROM days
+                LEFT JOIN tasks
+                  ON tasks.user_id = ? AND date(tasks.created_at) = days.day
+                GROUP BY days.day
+                ORDER BY days.day ASC
+                "#,
+            )
+            .bind(&first_day)
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(counts)
+        })
+    }
+
+    /// Compute dashboard statistics for a user.
+    ///
+    /// Uses a single grouped query: one row per (status, priority) pair,
+    /// each carrying its task count and how many of those are overdue.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    ///
+    /// # Returns
+    /// * `AppResult<TaskStats>` - Counts by status and priority, plus overdue
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn stats_for_user(pool: &DbPool, user_id: i64) -> AppResult<TaskStats> {
+        timed!("stats_for_user", {
+            let sql = format!(
+                r#"
+                SELECT status, priority, COUNT(*),
+                       SUM(CASE WHEN {} THEN 1 ELSE 0 END)
+                FROM tasks
+                WHERE user_id = ?
+                GROUP BY status, priority
+                "#,
+                OVERDUE_CONDITION
+            );
+
+            let rows: Vec<(TaskStatus, TaskPriority, i64, i64)> = sqlx::query_as(&sql)
+                .bind(Utc::now())
+                .bind(user_id)
+                .fetch_all(pool)
+                .await?;
+
+            // Start every priority at zero so dashboards always get all four keys
+            let mut stats = TaskStats {
+                by_priority: [
+                    TaskPriority::Low,
+                    TaskPriority::Medium,
+                    TaskPriority::High,
+                    TaskPriority::Urgent,
+                ]
+                .into_iter()
+                .map(|priority| (priority, 0))
+                .collect(),
+                ..TaskStats::default()
+            };
+
+            for (status, priority, count, overdue) in rows {
+                stats.total += count;
+                stats.overdue += overdue;
+                match status {
+                    TaskStatus::Todo => stats.todo += count,
+                    TaskStatus::InProgress => stats.in_progress += count,
+                    TaskStatus::Done => stats.done += count,
+                }
+                *stats.by_priority.entry(priority).or_insert(0) += count;
+            }
+
+            Ok(stats)
+        })
+    }
+
+    /// Update an existing task.
+    ///
+    /// Only updates fields that are provided (not None). If `task.version`
+    /// is set, the update only applies while the task is still at that
+    /// version; either way the version goes up by one. Records an
+    /// `AuditAction::Update` entry holding just the fields whose values
+    /// actually changed, in the same transaction; an update that changes
+    /// nothing isn't recorded. The due date may be set to a time that has
+    /// already passed, which `CreateTask::validate_new` rejects for new tasks.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of task to update
+    /// * `actor_id` - User making the change, recorded in the audit entry
+    /// * `task` - Fields to update (None fields are not updated)
+    ///
+    /// # Returns
+    /// * `AppResult<Task>` - Updated task
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If a minutes field is negative, or
+    ///   `parent_id` is the task itself or one of its subtasks
+    /// * `AppError::NotFound` - If `project_id` names no project
+    /// * `AppError::Forbidden` - If `project_id` is another user's project,
+    ///   or `parent_id` another user's task
+    /// * `AppError::TaskNotFound` - If the task or its new parent doesn't exist
+    /// * `AppError::Conflict` - If the task is no longer at `task.version`;
+    ///   refetch it and retry
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, task), level = "debug")]
+    pub async fn update(
+        pool: &DbPool,
+        id: i64,
+        actor_id: i64,
+        task: UpdateTask,
+    ) -> AppResult<Task> {
+        ensure_writable()?;
+        timed!("update", {
+            if task.estimated_minutes.is_some_and(|m| m < 0)
+                || task.actual_minutes.is_some_and(|m| m < 0)
+            {
+                return Err(AppError::Validation(
+                    "Minutes cannot be negative".to_string(),
+                ));
+            }
+
+            // Take the write lock up front so the row can't change between
+            // reading the old values and writing the new ones
+            let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+
+            // First, verify the task exists and keep its old values for the audit
+            let old = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or(AppError::TaskNotFound(id))?;
+            if let Some(project_id) = task.project_id {
+                ensure_project_owner(&mut tx, project_id, old.user_id).await?;
+            }
+            if let Some(parent_id) = task.parent_id {
+                ensure_task_owner(&mut tx, parent_id, old.user_id).await?;
+                ensure_not_own_subtask(&mut tx, id, parent_id).await?;
+            }
+
+            // Build dynamic UPDATE query based on which fields are provided
+            let mut query_builder: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE tasks SET ");
+            let mut has_updates = false;
+
+            // Add title if provided
+            if let Some(title) = &task.title {
+                query_builder.push("title = ");
+                query_builder.push_bind(title);
+                has_updates = true;
+            }
+
+            // Add description if provided
+            if let Some(description) = &task.description {
+                if has_updates {
+                    query_builder.push(", ");
+                }
+                query_builder.push("description = ");
+                query_builder.push_bind(description);
+                has_updates = true;
+            }
+
+            // Add status if provided
+            if let Some(status) = &task.status {
+                if has_updates {
+                    query_builder.push(", ");
+                }
+                query_builder.push("status = ");
+                query_builder.push_bind(status);
+                has_updates = true;
+            }
+
+            // Add priority if provided
+            if let Some(priority) = &task.priority {
+                if has_updates {
+                    query_builder.push(", ");
+                }
+                query_builder.push("priority = ");
+                query_builder.push_bind(priority);
+                has_updates = true;
+            }
+
+            // Add due_date if provided; Some(None) clears it to NULL
+            if let Some(due_date) = task.due_date {
+                if has_updates {
+                    query_builder.push(", ");
+                }
+                query_builder.push("due_date = ");
+                query_builder.push_bind(due_date);
+                has_updates = true;
+            }
+
+            // Add the time tracking fields if provided
+            if let Some(minutes) = task.estimated_minutes {
+                if has_updates {
+                    query_builder.push(", ");
+                }
+                query_builder.push("estimated_minutes = ");
+                query_builder.push_bind(minutes);
+                has_updates = true;
+            }
+            if let Some(minutes) = task.actual_minutes {
+                if has_updates {
+                    query_builder.push(", ");
+                }
+                query_builder.push("actual_minutes = ");
+                query_builder.push_bind(minutes);
+                has_updates = true;
+            }
+
+            // Add project_id if provided; Some(None) takes the task out of its project
+            if let Some(project_id) = task.project_id {
+                if has_updates {
+                    query_builder.push(", ");
+                }
+                query_builder.push("project_id = ");
+                query_builder.push_bind(project_id);
+                has_updates = true;
+            }
+
+            // Add parent_id if provided; Some(None) makes it a top-level task
+            if let Some(parent_id) = task.parent_id {
+                if has_updates {
+                    query_builder.push(", ");
+                }
+                query_builder.push("parent_id = ");
+                query_builder.push_bind(parent_id);
+                has_updates = true;
+            }
+
+            // Update the updated_at timestamp
+            if has_updates {
+                query_builder.push(", ");
+            }
+            query_builder.push("updated_at = datetime('now'), version = version + 1");
+
+            // Add WHERE clause, guarded by the version the client read
+            query_builder.push(" WHERE id = ");
+            query_builder.push_bind(id);
+            if let Some(version) = task.version {
+                query_builder.push(" AND version = ");
+                query_builder.push_bind(version);
+            }
+
+            // Execute the update; no row means the version guard failed
+            let result = query_builder.build().execute(&mut *tx).await?;
+            if result.rows_affected() == 0 {
+                return Err(AppError::Conflict(format!(
+                    "Task {} was changed by someone else (now at version {}, not {})",
+                    id,
+                    old.version,
+                    task.version.unwrap_or_default()
+                )));
+            }
+
+            let new = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+
+            let (before, after) = audit_diff(&old, &new)?;
+            if !after.is_empty() {
+                record_audit(
+                    &mut tx,
+                    &new,
+                    actor_id,
+                    AuditAction::Update,
+                    Some(before),
+                    Some(after),
+                )
+                .await?;
+            }
+            tx.commit().await?;
+
+            Ok(new)
+        })
+    }
+
+    /// Move a task to a different owner.
+    ///
+    /// Only `user_id` (and `updated_at`) change. Anything that references the
+    /// task by `task_id` rather than by owner stays attached to it
+    /// automatically.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of task to move
+    /// * `new_user_id` - ID of the new owner
+    ///
+    /// # Returns
+    /// * `AppResult<Task>` - The task with its new owner
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::UserNotFound` - If the new owner doesn't exist
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn reassign(pool: &DbPool, task_id: i64, new_user_id: i64) -> AppResult<Task> {
+        ensure_writable()?;
+        timed!("reassign", {
+            Self::find_by_id(pool, task_id).await?;
+
+            // Check the target up front for a clear error instead of a foreign key failure
+            let (user_exists,): (bool,) =
+                sqlx::query_as("SELECT EXISTS (SELECT 1 FROM users WHERE id = ?)")
+                    .bind(new_user_id)
+                    .fetch_one(pool)
+                    .await?;
+            if !user_exists {
+                return Err(AppError::UserNotFound(new_user_id));
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE tasks
+                SET user_id = ?, updated_at = datetime('now'), version = version + 1
+                WHERE id = ?
+                "#,
+            )
+            .bind(new_user_id)
+            .bind(task_id)
+            .execute(pool)
+            .await?;
+
+            Self::find_by_id(pool, task_id).await
+        })
+    }
+
+    /// Create a copy of an existing task.
+    ///
+    /// The copy keeps the description, priority, due date, owner,
+    /// recurrence rule, time estimate, and tags; its title gets a " (copy)"
+    /// suffix (cutting the original short if the result would be longer
+    /// than `MAX_TITLE_LENGTH`), its status resets to `Todo`, it has no
+    /// time logged, and it gets fresh timestamps. (Tasks have no
+    /// `completed_at` yet, so there's nothing else to clear.)
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of the task to copy
+    ///
+    /// # Returns
+    /// * `AppResult<Task>` - The new task
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If the source task doesn't exist
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn duplicate(pool: &DbPool, id: i64) -> AppResult<Task> {
+        ensure_writable()?;
+        timed!("duplicate", {
+            let mut tx = pool.begin().await?;
+
+            let task = sqlx::query_as::<_, Task>(
+                r#"
+                INSERT INTO tasks (title, description, status, priority, due_date, user_id, recurrence,
+                                   estimated_minutes, project_id)
+                SELECT substr(title, 1, ?) || ?, description, ?, priority, due_date, user_id,
+                       recurrence, estimated_minutes, project_id
+                FROM tasks
+                WHERE id = ?
+                RETURNING *
+                "#,
+            )
+            // substr counts characters, as the title validation does
+            .bind((MAX_TITLE_LENGTH - COPY_SUFFIX.len()) as i64)
+            .bind(COPY_SUFFIX)
+            .bind(TaskStatus::Todo)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::TaskNotFound(id))?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO task_tags (task_id, tag_id)
+                SELECT ?, tag_id FROM task_tags WHERE task_id = ?
+                "#,
+            )
+            .bind(task.id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+
+            Ok(task)
+        })
+    }
+
+    /// Mark a task as done.
+    ///
+    /// Shortcut for an `UpdateTask` that only sets the status, done in a
+    /// single statement. Tasks have no `completed_at` column and status
+    /// changes aren't restricted, so any task can be marked done. For
+    /// recurring tasks, use `complete_and_reschedule` instead.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<Task>` - The updated task
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn mark_done(pool: &DbPool, id: i64) -> AppResult<Task> {
+        ensure_writable()?;
+        timed!("mark_done", {
+            Self::set_status(pool, id, TaskStatus::Done).await
+        })
+    }
+
+    /// Mark a task as in progress.
+    ///
+    /// Same as `mark_done`, but sets `InProgress`.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<Task>` - The updated task
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn mark_in_progress(pool: &DbPool, id: i64) -> AppResult<Task> {
+        ensure_writable()?;
+        timed!("mark_in_progress", {
+            Self::set_status(pool, id, TaskStatus::InProgress).await
+        })
+    }
+
+    /// Set the priorities of many tasks at once, e.g. after reordering a sprint.
+    ///
+    /// Every `(id, priority)` pair is applied inside one transaction, using a
+    /// `CASE` expression per batch of pairs. IDs that don't exist are
+    /// skipped rather than failing the whole call, so the valid pairs still
+    /// apply; compare the returned count with `updates.len()` to detect
+    /// them. Any database error rolls back every change. If an ID appears
+    /// more than once, its last pair wins. An empty slice does nothing.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `updates` - `(task ID, new priority)` pairs
+    ///
+    /// # Returns
+    /// * `AppResult<u64>` - Number of tasks updated
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip_all, level = "debug", fields(count = updates.len()))]
+    pub async fn set_priorities(pool: &DbPool, updates: &[(i64, TaskPriority)]) -> AppResult<u64> {
+        ensure_writable()?;
+        timed!("set_priorities", {
+            if updates.is_empty() {
+                return Ok(0);
+            }
+
+            let mut tx = pool.begin().await?;
+            let mut updated = 0;
+
+            for batch in updates.chunks(PRIORITY_BATCH_SIZE) {
+                let mut query_builder: QueryBuilder<Sqlite> =
+                    QueryBuilder::new("UPDATE tasks SET priority = CASE id");
+                // CASE takes the first match, so go backwards for "last wins"
+                for (id, priority) in batch.iter().rev() {
+                    query_builder.push(" WHEN ");
+                    query_builder.push_bind(*id);
+                    query_builder.push(" THEN ");
+                    query_builder.push_bind(*priority);
+                }
+                query_builder.push(
+                    " END, updated_at = datetime('now'), version = version + 1 WHERE id IN (",
+                );
+                let mut ids = query_builder.separated(", ");
+                for (id, _) in batch {
+                    ids.push_bind(*id);
+                }
+                query_builder.push(")");
+
+                updated += query_builder
+                    .build()
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            }
+
+            tx.commit().await?;
+
+            Ok(updated)
+        })
+    }
+
+    /// Set the manual order of a user's tasks.
+    ///
+    /// The tasks in `ordered_ids` get positions 1, 2, 3, ... in that order,
+    /// inside one transaction; tasks left out keep their positions.
+    /// Positions are presentation only, so `version` and `updated_at` are
+    /// left alone. If any ID isn't one of the user's tasks, nothing changes.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user whose tasks are being ordered
+    /// * `ordered_ids` - Task IDs, first to last
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If an ID appears more than once
+    /// * `AppError::Unauthorized` - If an ID isn't one of the user's tasks
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, ordered_ids), level = "debug", fields(count = ordered_ids.len()))]
+    pub async fn reorder(pool: &DbPool, user_id: i64, ordered_ids: &[i64]) -> AppResult<()> {
+        ensure_writable()?;
+        timed!("reorder", {
+            let mut seen = HashSet::with_capacity(ordered_ids.len());
+            if let Some(id) = ordered_ids.iter().find(|id| !seen.insert(**id)) {
+                return Err(AppError::Validation(format!(
+                    "Task {} appears more than once in the new order",
+                    id
+                )));
+            }
+
+            let mut tx = pool.begin().await?;
+
+            for (position, id) in (1_i64..).zip(ordered_ids) {
+                let result =
+                    sqlx::query("UPDATE tasks SET position = ? WHERE id = ? AND user_id = ?")
+                        .bind(position)
+                        .bind(id)
+                        .bind(user_id)
+                        .execute(&mut *tx)
+                        .await?;
+
+                // Dropping the transaction rolls back the positions set so far
+                if result.rows_affected() == 0 {
+                    return Err(AppError::Unauthorized(format!(
+                        "Task {} does not belong to user {}",
+                        id, user_id
+                    )));
+                }
+            }
+
+            tx.commit().await?;
+
+            Ok(())
+        })
+    }
+
+    /// Tag many tasks at once.
+    ///
+    /// The tag is normalized with [`normalize_tag`] and created if it doesn't
+    /// exist yet; then every listed task gets it, all inside one
+    /// transaction. Tasks that already carry the tag, and IDs that don't
+    /// exist, are skipped. An empty slice does nothing (and creates no tag).
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_ids` - IDs of the tasks to tag
+    /// * `tag` - Tag name, before normalization
+    ///
+    /// # Returns
+    /// * `AppResult<u64>` - Number of new task/tag associations
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the tag is blank or too long
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, task_ids), level = "debug", fields(count = task_ids.len()))]
+    pub async fn add_tag_to_many(pool: &DbPool, task_ids: &[i64], tag: &str) -> AppResult<u64> {
+        ensure_writable()?;
+        timed!("add_tag_to_many", {
+            if task_ids.is_empty() {
+                return Ok(0);
+            }
+            let tag = normalize_tag(tag)?;
+
+            let mut tx = pool.begin().await?;
+
+            // The no-op update makes RETURNING yield the id for an existing tag too
+            let (tag_id,): (i64,) = sqlx::query_as(
+                r#"
+                INSERT INTO tags (name) VALUES (?)
+                ON CONFLICT (name) DO UPDATE SET name = excluded.name
+                RETURNING id
+                "#,
+            )
+            .bind(&tag)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let mut added = 0;
+            for batch in task_ids.chunks(TAG_BATCH_SIZE) {
+                // Selecting from tasks skips IDs that don't exist
+                let mut query_builder: QueryBuilder<Sqlite> =
+                    QueryBuilder::new("INSERT INTO task_tags (task_id, tag_id) SELECT id, ");
+                query_builder.push_bind(tag_id);
+                query_builder.push(" FROM tasks WHERE id IN (");
+                let mut ids = query_builder.separated(", ");
+                for id in batch {
+                    ids.push_bind(*id);
+                }
+                query_builder.push(") ON CONFLICT (task_id, tag_id) DO NOTHING");
+
+                added += query_builder
+                    .build()
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            }
+
+            tx.commit().await?;
+
+            Ok(added)
+        })
+    }
+
+    /// A task's tags, alphabetically.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<String>>` - Normalized tag names (empty vec if none)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn tags_for_task(pool: &DbPool, task_id: i64) -> AppResult<Vec<String>> {
+        timed!("tags_for_task", {
+            let tags: Vec<(String,)> = sqlx::query_as(
+                r#"
+                SELECT tags.name FROM tags
+                JOIN task_tags ON task_tags.tag_id = tags.id
+                WHERE task_tags.task_id = ?
+                ORDER BY tags.name ASC
+                "#,
+            )
+            .bind(task_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tags.into_iter().map(|(name,)| name).collect())
+        })
+    }
+
+    /// Add time spent on a task to its logged minutes.
+    ///
+    /// A task with no time logged yet starts from zero.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of the task
+    /// * `minutes` - Minutes spent
+    ///
+    /// # Returns
+    /// * `AppResult<Task>` - The task with its new `actual_minutes`
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `minutes` is negative, or the total
+    ///   would overflow
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn log_time(pool: &DbPool, id: i64, minutes: i32) -> AppResult<Task> {
+        ensure_writable()?;
+        timed!("log_time", {
+            if minutes < 0 {
+                return Err(AppError::Validation(
+                    "Logged time cannot be negative".to_string(),
+                ));
+            }
+
+            // Hold the write lock so no other log lands between the read
+            // and the write
+            let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+
+            let (logged,): (Option<i32>,) =
+                sqlx::query_as("SELECT actual_minutes FROM tasks WHERE id = ?")
+                    .bind(id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or(AppError::TaskNotFound(id))?;
+            let total = logged
+                .unwrap_or(0)
+                .checked_add(minutes)
+                .ok_or_else(|| AppError::Validation("Logged time is too large".to_string()))?;
+
+            let task = sqlx::query_as::<_, Task>(
+                r#"
+                UPDATE tasks
+                SET actual_minutes = ?, updated_at = datetime('now'), version = version + 1
+                WHERE id = ?
+                RETURNING *
+                "#,
+            )
+            .bind(total)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+
+            Ok(task)
+        })
+    }
+
+    /// Set a task's status and return the updated row.
+    async fn set_status(pool: &DbPool, id: i64, status: TaskStatus) -> AppResult<Task> {
+        let task = sqlx::query_as::<_, Task>(
+            r#"
+            UPDATE tasks SET status = ?, updated_at = datetime('now'), version = version + 1
+            WHERE id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(status)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        task.ok_or(AppError::TaskNotFound(id))
+    }
+
+    /// Mark a task done, and if it recurs, schedule its next occurrence.
+    ///
+    /// For a recurring task this inserts a fresh `Todo` copy (same title,
+    /// description, priority, owner, rule, and estimate) due at the next occurrence
+    /// after the current due date, or after now if it had none. Both writes
+    /// happen in one transaction. Tasks that are already done are returned
+    /// unchanged, so calling this twice never spawns two copies.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of the task to complete
+    ///
+    /// # Returns
+    /// * `AppResult<Task>` - The completed task (find the new occurrence via
+    ///   the user's task list)
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::Database` - If database operation fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn complete_and_reschedule(pool: &DbPool, id: i64) -> AppResult<Task> {
+        ensure_writable()?;
+        timed!("complete_and_reschedule", {
+            let mut tx = pool.begin().await?;
+
+            let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or(AppError::TaskNotFound(id))?;
+
+            if task.status == TaskStatus::Done {
+                return Ok(task);
+            }
+
+            let completed = sqlx::query_as::<_, Task>(
+                r#"
+                UPDATE tasks SET status = ?, updated_at = datetime('now'), version = version + 1
+                WHERE id = ?
+                RETURNING *
+                "#,
+            )
+            .bind(TaskStatus::Done)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if let Some(rule) = task.recurrence {
+                let next_due = rule.next_after(task.due_date.unwrap_or_else(Utc::now));
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO tasks (title, description, status, priority, due_date, user_id, recurrence,
+                                       estimated_minutes, project_id)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(&task.title)
+                .bind(&task.description)
+                .bind(TaskStatus::Todo)
+                .bind(task.priority)
+                .bind(next_due)
+                .bind(task.user_id)
+                .bind(rule)
+                .bind(task.estimated_minutes)
+                .bind(task.project_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+
+            Ok(completed)
+        })
+    }
+
+    /// Mark a task and all of its subtasks, at every level, as done.
+    ///
+    /// Walks the `parent_id` tree down from `id` and updates everything in
+    /// one transaction. Tasks already done are left as they are, so their
+    /// version doesn't change. Like `mark_done`, this doesn't schedule
+    /// recurring tasks' next occurrences. Only `user_id`'s tasks are
+    /// touched: a subtask since reassigned to someone else stays as it is.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of the top task
+    /// * `user_id` - The user completing it, who must own it
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - The task and all of the user's subtasks
+    ///   under it, now done, ordered by ID
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::Forbidden` - If the task belongs to another user
+    /// * `AppError::Validation` - If the tree is more than
+    ///   `MAX_SUBTASK_DEPTH` levels deep (or, if corrupt, cyclic); nothing
+    ///   is changed
+    /// * `AppError::Database` - If database operation fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn complete_with_subtasks(
+        pool: &DbPool,
+        id: i64,
+        user_id: i64,
+    ) -> AppResult<Vec<Task>> {
+        ensure_writable()?;
+        timed!("complete_with_subtasks", {
+            let mut tx = pool.begin().await?;
+            ensure_task_owner(&mut tx, Some(id), user_id).await?;
+
+            // Walk one level past the limit, so a tree that's too deep shows up
+            let deepest: Option<i64> =
+                sqlx::query_scalar(&format!("{} SELECT max(depth) FROM subtree", SUBTREE_CTE))
+                    .bind(id)
+                    .bind(MAX_SUBTASK_DEPTH + 1)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            match deepest {
+                None => return Err(AppError::TaskNotFound(id)),
+                Some(depth) if depth > MAX_SUBTASK_DEPTH => {
+                    return Err(AppError::Validation(format!(
+                        "Task {} has subtasks more than {} levels deep",
+                        id, MAX_SUBTASK_DEPTH
+                    )));
+                }
+                Some(_) => {}
+            }
+
+            sqlx::query(&format!(
+                r#"
+                {}
+                UPDATE tasks SET status = ?, updated_at = datetime('now'), version = version + 1
+                WHERE id IN (SELECT id FROM subtree) AND user_id = ? AND status != ?
+                "#,
+                SUBTREE_CTE
+            ))
+            .bind(id)
+            .bind(MAX_SUBTASK_DEPTH)
+            .bind(TaskStatus::Done)
+            .bind(user_id)
+            .bind(TaskStatus::Done)
+            .execute(&mut *tx)
+            .await?;
+
+            let tasks = sqlx::query_as::<_, Task>(&format!(
+                "{} SELECT * FROM tasks WHERE id IN (SELECT id FROM subtree) AND user_id = ? ORDER BY id",
+                SUBTREE_CTE
+            ))
+            .bind(id)
+            .bind(MAX_SUBTASK_DEPTH)
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// Delete a task by ID.
+    ///
+    /// Records an `AuditAction::Delete` entry holding the deleted values in
+    /// the same transaction. The task's audit history is kept.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of task to delete
+    /// * `actor_id` - User deleting it, recorded in the audit entry
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::Database` - If database deletion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn delete(pool: &DbPool, id: i64, actor_id: i64) -> AppResult<()> {
+        ensure_writable()?;
+        timed!("delete", {
+            let mut tx = pool.begin().await?;
+
+            let deleted = sqlx::query_as::<_, Task>(
+                r#"
+                DELETE FROM tasks
+                WHERE id = ?
+                RETURNING *
+                "#,
+            )
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::TaskNotFound(id))?;
+
+            let fields = audit_fields(&deleted)?;
+            record_audit(
+                &mut tx,
+                &deleted,
+                actor_id,
+                AuditAction::Delete,
+                Some(fields),
+                None,
+            )
+            .await?;
+            tx.commit().await?;
+
+            Ok(())
+        })
+    }
+
+    /// Count total tasks for a user.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    ///
+    /// # Returns
+    /// * `AppResult<i64>` - Total count of tasks
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn count_by_user(pool: &DbPool, user_id: i64) -> AppResult<i64> {
+        timed!("count_by_user", {
+            let count: (i64,) = sqlx::query_as(
+                r#"
+                SELECT COUNT(*) FROM tasks
+                WHERE user_id = ?
+                "#,
+            )
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+
+            Ok(count.0)
+        })
+    }
+
+    /// Check if a task belongs to a specific user.
+    ///
+    /// Useful for authorization checks.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    /// * `user_id` - ID of the user
+    ///
+    /// # Returns
+    /// * `AppResult<bool>` - True if task belongs to user
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn belongs_to_user(pool: &DbPool, task_id: i64, user_id: i64) -> AppResult<bool> {
+        timed!("belongs_to_user", {
+            let exists: (i64,) = sqlx::query_as(
+                r#"
+                SELECT COUNT(*) FROM tasks
+                WHERE id = ? AND user_id = ?
+                "#,
+            )
+            .bind(task_id)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+
+            Ok(exists.0 > 0)
+        })
+    }
+
+    /// Add a user to a task's watchers.
+    ///
+    /// Watching is separate from ownership: any user can watch any task to
+    /// be told about its changes. Adding an existing watcher does nothing.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task to watch
+    /// * `user_id` - ID of the watcher
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::UserNotFound` - If user doesn't exist
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn add_watcher(pool: &DbPool, task_id: i64, user_id: i64) -> AppResult<()> {
+        ensure_writable()?;
+        timed!("add_watcher", {
+            Self::find_by_id(pool, task_id).await?;
+
+            // Check the user up front for a clear error instead of a foreign key failure
+            let (user_exists,): (bool,) =
+                sqlx::query_as("SELECT EXISTS (SELECT 1 FROM users WHERE id = ?)")
+                    .bind(user_id)
+                    .fetch_one(pool)
+                    .await?;
+            if !user_exists {
+                return Err(AppError::UserNotFound(user_id));
+            }
+
+            // The (task_id, user_id) primary key turns a repeat add into a no-op
+            sqlx::query("INSERT OR IGNORE INTO task_watchers (task_id, user_id) VALUES (?, ?)")
+                .bind(task_id)
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+
+            Ok(())
+        })
+    }
+
+    /// Remove a user from a task's watchers.
+    ///
+    /// Removing someone who isn't watching does nothing.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    /// * `user_id` - ID of the watcher
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database deletion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn remove_watcher(pool: &DbPool, task_id: i64, user_id: i64) -> AppResult<()> {
+        ensure_writable()?;
+        timed!("remove_watcher", {
+            sqlx::query("DELETE FROM task_watchers WHERE task_id = ? AND user_id = ?")
+                .bind(task_id)
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+
+            Ok(())
+        })
+    }
+
+    /// List the IDs of a task's watchers, lowest first.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<i64>>` - Watcher user IDs (empty vec if none)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn watchers_for_task(pool: &DbPool, task_id: i64) -> AppResult<Vec<i64>> {
+        timed!("watchers_for_task", {
+            let watchers: Vec<(i64,)> = sqlx::query_as(
+                r#"
+                SELECT user_id FROM task_watchers
+                WHERE task_id = ?
+                ORDER BY user_id ASC
+                "#,
+            )
+            .bind(task_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(watchers.into_iter().map(|(user_id,)| user_id).collect())
+        })
+    }
+
+    /// Record that `task_id` can't start until `depends_on_id` is done.
+    ///
+    /// Dependencies must form no loops: if `depends_on_id` already depends
+    /// on `task_id`, directly or through other tasks, the new one is
+    /// rejected. Adding an existing dependency does nothing. The check and
+    /// insert share an `IMMEDIATE` transaction, so two concurrent calls
+    /// can't close a loop between them.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the blocked task
+    /// * `depends_on_id` - ID of the task it waits on
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the dependency would form a loop
+    ///   (including a task depending on itself)
+    /// * `AppError::TaskNotFound` - If either task doesn't exist
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn add_dependency(pool: &DbPool, task_id: i64, depends_on_id: i64) -> AppResult<()> {
+        ensure_writable()?;
+        timed!("add_dependency", {
+            if task_id == depends_on_id {
+                return Err(AppError::Validation(
+                    "A task cannot depend on itself".to_string(),
+                ));
+            }
+
+            let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+
+            // Check both tasks up front for a clear error instead of a foreign key failure
+            for id in [task_id, depends_on_id] {
+                let (exists,): (bool,) =
+                    sqlx::query_as("SELECT EXISTS (SELECT 1 FROM tasks WHERE id = ?)")
+                        .bind(id)
+                        .fetch_one(&mut *tx)
+                        .await?;
+                if !exists {
+                    return Err(AppError::TaskNotFound(id));
+                }
+            }
+
+            // Walk everything depends_on_id waits on; reaching task_id means a loop.
+            // UNION (not UNION ALL) drops repeats, so the walk always ends.
+            let (creates_cycle,): (bool,) = sqlx::query_as(
+                r#"
+                WITH RECURSIVE upstream(id) AS (
+                    SELECT depends_on_id FROM task_dependencies WHERE task_id = ?
+                    UNION
+                    SELECT d.depends_on_id FROM task_dependencies d
+                    JOIN upstream u ON d.task_id = u.id
+                )
+                SELECT EXISTS (SELECT 1 FROM upstream WHERE id = ?)
+                "#,
+            )
+            .bind(depends_on_id)
+            .bind(task_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            if creates_cycle {
+                return Err(AppError::Validation(format!(
+                    "Task {} already depends on task {}, so this would form a loop",
+                    depends_on_id, task_id
+                )));
+            }
+
+            // The (task_id, depends_on_id) primary key turns a repeat add into a no-op
+            sqlx::query(
+                "INSERT OR IGNORE INTO task_dependencies (task_id, depends_on_id) VALUES (?, ?)",
+            )
+            .bind(task_id)
+            .bind(depends_on_id)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            Ok(())
+        })
+    }
+
+    /// Remove a dependency.
+    ///
+    /// Removing one that doesn't exist does nothing.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the blocked task
+    /// * `depends_on_id` - ID of the task it waits on
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database deletion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn remove_dependency(
+        pool: &DbPool,
+        task_id: i64,
+        depends_on_id: i64,
+    ) -> AppResult<()> {
+        ensure_writable()?;
+        timed!("remove_dependency", {
+            sqlx::query("DELETE FROM task_dependencies WHERE task_id = ? AND depends_on_id = ?")
+                .bind(task_id)
+                .bind(depends_on_id)
+                .execute(pool)
+                .await?;
+
+            Ok(())
+        })
+    }
+
+    /// List the tasks `task_id` directly depends on, lowest ID first.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<Task>>` - The tasks it waits on (empty vec if none)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn dependencies_for(pool: &DbPool, task_id: i64) -> AppResult<Vec<Task>> {
+        timed!("dependencies_for", {
+            let tasks = sqlx::query_as::<_, Task>(
+                r#"
+                SELECT t.* FROM tasks t
+                JOIN task_dependencies d ON d.depends_on_id = t.id
+                WHERE d.task_id = ?
+                ORDER BY t.id ASC
+                "#,
+            )
+            .bind(task_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(tasks)
+        })
+    }
+
+    /// Check whether a task is waiting on any unfinished dependency.
+    ///
+    /// Only direct dependencies count: a dependency that is done unblocks
+    /// the task even if it had unfinished dependencies of its own. A task
+    /// with no dependencies (or that doesn't exist) is not blocked.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<bool>` - True if any dependency isn't `Done`
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn is_blocked(pool: &DbPool, task_id: i64) -> AppResult<bool> {
+        timed!("is_blocked", {
+            let (blocked,): (bool,) = sqlx::query_as(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM task_dependencies d
+                    JOIN tasks t ON t.id = d.depends_on_id
+                    WHERE d.task_id = ? AND t.status != ?
+                )
+                "#,
+            )
+            .bind(task_id)
+            .bind(TaskStatus::Done)
+            .fetch_one(pool)
+            .await?;
+
+            Ok(blocked)
+        })
+    }
+
+    /// List every recorded change to a task, oldest first.
+    ///
+    /// History outlives the task, so a deleted task's trail can still be read.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `task_id` - ID of the task
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<AuditEntry>>` - The audit trail (empty vec if none)
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn audit_history(pool: &DbPool, task_id: i64) -> AppResult<Vec<AuditEntry>> {
+        timed!("audit_history", {
+            let entries = sqlx::query_as::<_, AuditEntry>(
+                r#"
+                SELECT * FROM task_audit
+                WHERE task_id = ?
+                ORDER BY at ASC, id ASC
+                "#,
+            )
+            .bind(task_id)
+            .fetch_all(pool)
+            .await?;
+
+            Ok(entries)
+        })
+    }
+}
+
+/// The audited fields of `task` as a JSON object keyed by field name.
+fn audit_fields(task: &Task) -> AppResult<Map<String, Value>> {
+    match serde_json::to_value(task) {
+        Ok(Value::Object(mut fields)) => {
+            for field in UNAUDITED_FIELDS {
+                fields.remove(field);
+            }
+            Ok(fields)
+        }
+        _ => Err(AppError::Internal(
+            "Task did not serialize to a JSON object".to_string(),
+        )),
+    }
+}
+
+/// The before and after values of just the fields that differ.
+fn audit_diff(old: &Task, new: &Task) -> AppResult<(Map<String, Value>, Map<String, Value>)> {
+    let mut before = audit_fields(old)?;
+    let mut after = audit_fields(new)?;
+    before.retain(|field, value| after.get(field) != Some(value));
+    after.retain(|field, _| before.contains_key(field));
+    Ok((before, after))
+}
+
+/// Write one audit entry for `task`, attributed to `actor_id`, the user
+/// who made the change (not necessarily the task's owner).
+///
+/// Takes the caller's connection so the entry commits (or rolls back)
+/// together with the change it describes.
+async fn record_audit(
+    conn: &mut SqliteConnection,
+    task: &Task,
+    actor_id: i64,
+    action: AuditAction,
+    old_value: Option<Map<String, Value>>,
+    new_value: Option<Map<String, Value>>,
+) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO task_audit (task_id, user_id, action, old_value, new_value)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(task.id)
+    .bind(actor_id)
+    .bind(action)
+    .bind(old_value.map(Value::Object))
+    .bind(new_value.map(Value::Object))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Check that `project_id`, if set, is one of `user_id`'s projects, so a
+/// task can't be filed under (and listed with) someone else's project.
+///
+/// # Errors
+/// * `AppError::NotFound` - If the project doesn't exist
+/// * `AppError::Forbidden` - If it belongs to another user
+async fn ensure_project_owner(
+    conn: &mut SqliteConnection,
+    project_id: Option<i64>,
+    user_id: i64,
+) -> AppResult<()> {
+    let Some(project_id) = project_id else {
+        return Ok(());
+    };
+    let owner: Option<i64> = sqlx::query_scalar("SELECT user_id FROM projects WHERE id = ?")
+        .bind(project_id)
+        .fetch_optional(conn)
+        .await?;
+
+    match owner {
+        Some(owner) if owner == user_id => Ok(()),
+        Some(_) => Err(AppError::Forbidden(format!(
+            "Project {} belongs to another user",
+            project_id
+        ))),
+        None => Err(AppError::NotFound {
+            entity: "Project",
+            id: project_id,
+        }),
+    }
+}
+
+/// Check that `task_id`, if set, is one of `user_id`'s tasks, e.g. so a
+/// subtask can't be hung under someone else's task.
+///
+/// # Errors
+/// * `AppError::TaskNotFound` - If the task doesn't exist
+/// * `AppError::Forbidden` - If it belongs to another user
+async fn ensure_task_owner(
+    conn: &mut SqliteConnection,
+    task_id: Option<i64>,
+    user_id: i64,
+) -> AppResult<()> {
+    let Some(task_id) = task_id else {
+        return Ok(());
+    };
+    let owner: Option<i64> = sqlx::query_scalar("SELECT user_id FROM tasks WHERE id = ?")
+        .bind(task_id)
+        .fetch_optional(conn)
+        .await?;
+
+    match owner {
+        None => Err(AppError::TaskNotFound(task_id)),
+        Some(owner) if owner != user_id => Err(AppError::Forbidden(format!(
+            "Task {} belongs to another user",
+            task_id
+        ))),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Check that moving task `id` under `parent_id` keeps the tree a tree:
+/// the new parent can't be the task itself or one of its subtasks.
+///
+/// # Errors
+/// * `AppError::Validation` - If `parent_id` is in `id`'s subtree
+async fn ensure_not_own_subtask(
+    conn: &mut SqliteConnection,
+    id: i64,
+    parent_id: Option<i64>,
+) -> AppResult<()> {
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+    let in_subtree: bool = sqlx::query_scalar(&format!(
+        "{} SELECT EXISTS(SELECT 1 FROM subtree WHERE id = ?)",
+        SUBTREE_CTE
+    ))
+    .bind(id)
+    .bind(MAX_SUBTASK_DEPTH)
+    .bind(parent_id)
+    .fetch_one(conn)
+    .await?;
+
+    if in_subtree {
+        return Err(AppError::Validation(format!(
+            "Task {} can't be a subtask of itself or of its own subtasks",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Check a page size is between 1 and `MAX_PAGE_SIZE`.
+pub(crate) fn validate_limit(limit: i64) -> AppResult<()> {
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(AppError::Validation(format!(
+            "limit must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// Check a page offset isn't negative.
+pub(crate) fn validate_offset(offset: i64) -> AppResult<()> {
+    if offset < 0 {
+        return Err(AppError::Validation(
+            "offset must not be negative".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Lowercase `title`, drop punctuation, and collapse runs of whitespace,
+/// e.g. `"  Buy  MILK!"` becomes `"buy milk"`.
+fn normalize_title(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The three-character sequences of a normalized title, with each word
+/// padded so short words and word starts still produce some.
+fn trigrams(normalized: &str) -> HashSet<[char; 3]> {
+    let mut grams = HashSet::new();
+    for word in normalized.split(' ').filter(|w| !w.is_empty()) {
+        let padded: Vec<char> = "  ".chars().chain(word.chars()).chain([' ']).collect();
+        grams.extend(padded.windows(3).map(|w| [w[0], w[1], w[2]]));
+    }
+    grams
+}
+
+/// Share of trigrams two titles have in common (Jaccard index), from 0.0
+/// for nothing shared to 1.0 for the same set.
+fn similarity(a: &HashSet<[char; 3]>, b: &HashSet<[char; 3]>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// The UTC instant `date` begins in `tz`.
+///
+/// Where a DST change skips midnight, the day starts at the first local
+/// time that exists; where midnight happens twice, at the first one.
+fn start_of_local_day(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    (0..=3)
+        .find_map(|hour| {
+            tz.from_local_datetime(&(midnight + chrono::Duration::hours(hour)))
+                .earliest()
+        })
+        .map(|start| start.with_timezone(&Utc))
+        // No zone skips more than a few hours; treat it as UTC if one does
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        create_test_pool, create_test_task, create_test_user, new_test_task, TaskSort,
+    };
+    use crate::models::RecurrenceRule;
+    use chrono::{DateTime, Duration};
+
+    /// Insert a task with a given status, priority, and due date.
+    async fn insert(
+        pool: &DbPool,
+        user_id: i64,
+        status: TaskStatus,
+        priority: TaskPriority,
+        due_date: Option<DateTime<Utc>>,
+    ) -> Task {
+        TaskRepository::create(
+            pool,
+            CreateTask {
+                status,
+                priority,
+                due_date,
+                ..new_test_task(user_id, "task")
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_create_increments_call_counter() {
+        use crate::db::metrics::CALLS_METRIC;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        // A current-thread runtime keeps the whole call on this thread,
+        // where the local recorder is installed
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        ::metrics::with_local_recorder(&recorder, || {
+            runtime.block_on(async {
+                let pool = create_test_pool().await.unwrap();
+                let user_id = create_test_user(&pool, "alice").await.unwrap();
+                create_test_task(&pool, user_id, "Counted").await.unwrap();
+            })
+        });
+
+        let creates: Vec<u64> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .filter_map(|(key, _, _, value)| {
+                let key = key.key();
+                let is_create = key.name() == CALLS_METRIC
+                    && key
+                        .labels()
+                        .any(|l| l.key() == "op" && l.value() == "create");
+                match value {
+                    DebugValue::Counter(n) if is_create => Some(n),
+                    _ => None,
+                }
+            })
+            .collect();
+        assert_eq!(creates, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_stats_for_user_counts_every_field() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let other = create_test_user(&pool, "bob").await.unwrap();
+        let past = Some(Utc::now() - Duration::days(2));
+        let future = Some(Utc::now() + Duration::days(2));
+
+        use TaskPriority::*;
+        use TaskStatus::*;
+        insert(&pool, user_id, Todo, Low, past).await; // overdue
+        insert(&pool, user_id, Todo, High, None).await;
+        insert(&pool, user_id, InProgress, High, past).await; // overdue
+        insert(&pool, user_id, InProgress, Urgent, future).await;
+        insert(&pool, user_id, Done, High, past).await; // done, so not overdue
+        insert(&pool, other, Todo, Low, past).await; // someone else's
+
+        let stats = TaskRepository::stats_for_user(&pool, user_id)
+            .await
+            .unwrap();
+
+        assert_eq!(stats.total, 5);
+        assert_eq!(stats.todo, 2);
+        assert_eq!(stats.in_progress, 2);
+        assert_eq!(stats.done, 1);
+        assert_eq!(stats.overdue, 2);
+        assert_eq!(stats.by_priority[&Low], 1);
+        assert_eq!(stats.by_priority[&Medium], 0);
+        assert_eq!(stats.by_priority[&High], 3);
+        assert_eq!(stats.by_priority[&Urgent], 1);
+
+        // Same definition of overdue as find_overdue
+        let overdue = TaskRepository::find_overdue(&pool, user_id).await.unwrap();
+        assert_eq!(overdue.len() as i64, stats.overdue);
+    }
+
+    /// Seed a user with a mix of tasks for the filter tests.
+    async fn seed_filter_tasks(pool: &DbPool, user_id: i64) {
+        let soon = Some(Utc::now() + Duration::days(3));
+        let later = Some(Utc::now() + Duration::days(30));
+        for (title, status, priority, due_date) in [
+            (
+                "Write report",
+                TaskStatus::InProgress,
+                TaskPriority::High,
+                soon,
+            ),
+            (
+                "Review report",
+                TaskStatus::InProgress,
+                TaskPriority::High,
+                later,
+            ),
+            (
+                "Plan offsite",
+                TaskStatus::InProgress,
+                TaskPriority::Low,
+                soon,
+            ),
+            ("File taxes", TaskStatus::Todo, TaskPriority::High, soon),
+        ] {
+            TaskRepository::create(
+                pool,
+                CreateTask {
+                    status,
+                    priority,
+                    due_date,
+                    ..new_test_task(user_id, title)
+                },
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_filtered_two_filters() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        seed_filter_tasks(&pool, user_id).await;
+
+        let filter = TaskFilter::new(user_id)
+            .status(TaskStatus::InProgress)
+            .priority(TaskPriority::High);
+        let tasks = TaskRepository::find_filtered(&pool, filter).await.unwrap();
+
+        let mut titles: Vec<_> = tasks.iter().map(|t| t.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, ["Review report", "Write report"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_filtered_three_filters() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        seed_filter_tasks(&pool, user_id).await;
+
+        let filter = TaskFilter::new(user_id)
+            .status(TaskStatus::InProgress)
+            .priority(TaskPriority::High)
+            .due_before(Utc::now() + Duration::days(7));
+        let tasks = TaskRepository::find_filtered(&pool, filter.clone())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Write report");
+
+        // Search is case-insensitive and also combines with the rest
+        let tasks = TaskRepository::find_filtered(&pool, filter.search("WRITE"))
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_find_filtered_without_criteria_returns_all_user_tasks() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let other = create_test_user(&pool, "bob").await.unwrap();
+        seed_filter_tasks(&pool, user_id).await;
+        seed_filter_tasks(&pool, other).await;
+
+        let tasks = TaskRepository::find_filtered(&pool, TaskFilter::new(user_id))
+            .await
+            .unwrap();
+
+        assert_eq!(tasks.len(), 4);
+        assert!(tasks.iter().all(|t| t.user_id == user_id));
+    }
+
+    #[tokio::test]
+    async fn test_find_filtered_sorts_and_pages() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        seed_filter_tasks(&pool, user_id).await;
+        insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, None).await;
+
+        let filter = TaskFilter::new(user_id).sort(TaskSort::DueDateAsc);
+        let tasks = TaskRepository::find_filtered(&pool, filter.clone())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 5);
+        assert!(tasks
+            .windows(2)
+            .all(|w| w[1].due_date.is_none() || w[0].due_date <= w[1].due_date));
+        assert_eq!(tasks.last().unwrap().due_date, None);
+
+        // Pages line up with the unpaged order
+        let page = TaskRepository::find_filtered(&pool, filter.clone().page(2, 1))
+            .await
+            .unwrap();
+        let ids: Vec<i64> = page.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![tasks[1].id, tasks[2].id]);
+
+        for bad in [filter.clone().page(0, 0), filter.page(10, -1)] {
+            let err = TaskRepository::find_filtered(&pool, bad).await.unwrap_err();
+            assert!(err.is_validation());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cursor_paging_is_stable_across_inserts() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let mut expected = Vec::new();
+        for i in 0..5 {
+            let task = create_test_task(&pool, user_id, &format!("Task {}", i))
+                .await
+                .unwrap();
+            expected.push(task.id);
+        }
+        expected.reverse(); // newest first
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = TaskRepository::find_by_user_after(&pool, user_id, cursor, 2)
+                .await
+                .unwrap();
+            seen.extend(page.iter().map(|t| t.id));
+
+            // A task created mid-scroll must not appear or shift later pages
+            if cursor.is_none() {
+                create_test_task(&pool, user_id, "Late arrival")
+                    .await
+                    .unwrap();
+            }
+
+            if page.len() < 2 {
+                break;
+            }
+            cursor = page.last().map(|t| t.id);
+        }
+
+        assert_eq!(seen, expected);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_paging_rejects_bad_limit() {
+        let pool = create_test_pool().await.unwrap();
+
+        for limit in [0, -1, MAX_PAGE_SIZE + 1] {
+            let err = TaskRepository::find_by_user_after(&pool, 1, None, limit)
+                .await
+                .unwrap_err();
+            assert!(err.is_validation());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reassign_moves_task_to_new_owner() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let task = create_test_task(&pool, alice, "Handover").await.unwrap();
+
+        let moved = TaskRepository::reassign(&pool, task.id, bob).await.unwrap();
+
+        assert_eq!(moved.id, task.id);
+        assert_eq!(moved.user_id, bob);
+        assert!(TaskRepository::find_by_user(&pool, alice)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reassign_not_found() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Handover").await.unwrap();
+
+        let err = TaskRepository::reassign(&pool, 9999, alice)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::TaskNotFound(9999)));
+
+        let err = TaskRepository::reassign(&pool, task.id, 9999)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::UserNotFound(9999)));
+    }
+
+    #[tokio::test]
+    async fn test_dependency_loops_rejected() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let mut ids = Vec::new();
+        for title in ["Design", "Build", "Ship"] {
+            let task = create_test_task(&pool, alice, title).await.unwrap();
+            ids.push(task.id);
+        }
+        let (design, build, ship) = (ids[0], ids[1], ids[2]);
+
+        // Ship waits on Build, which waits on Design
+        TaskRepository::add_dependency(&pool, ship, build)
+            .await
+            .unwrap();
+        TaskRepository::add_dependency(&pool, build, design)
+            .await
+            .unwrap();
+        // Repeats are harmless
+        TaskRepository::add_dependency(&pool, build, design)
+            .await
+            .unwrap();
+
+        for (task_id, depends_on_id) in [(design, ship), (design, build), (ship, ship)] {
+            let err = TaskRepository::add_dependency(&pool, task_id, depends_on_id)
+                .await
+                .unwrap_err();
+            assert!(err.is_validation(), "{} -> {}", task_id, depends_on_id);
+        }
+        let err = TaskRepository::add_dependency(&pool, ship, 9999)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::TaskNotFound(9999)));
+
+        // Once the chain is broken, the reverse edge is allowed
+        TaskRepository::remove_dependency(&pool, build, design)
+            .await
+            .unwrap();
+        TaskRepository::add_dependency(&pool, design, ship)
+            .await
+            .unwrap();
+        let deps: Vec<i64> = TaskRepository::dependencies_for(&pool, design)
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(deps, vec![ship]);
+    }
+
+    #[tokio::test]
+    async fn test_is_blocked_until_dependencies_done() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let blocked = insert(&pool, alice, TaskStatus::Todo, TaskPriority::High, None).await;
+        let first = insert(
+            &pool,
+            alice,
+            TaskStatus::InProgress,
+            TaskPriority::Low,
+            None,
+        )
+        .await;
+        let second = insert(&pool, alice, TaskStatus::Done, TaskPriority::Low, None).await;
+
+        assert!(!TaskRepository::is_blocked(&pool, blocked.id).await.unwrap());
+
+        for dep in [&first, &second] {
+            TaskRepository::add_dependency(&pool, blocked.id, dep.id)
+                .await
+                .unwrap();
+        }
+        assert!(TaskRepository::is_blocked(&pool, blocked.id).await.unwrap());
+
+        TaskRepository::mark_done(&pool, first.id).await.unwrap();
+        assert!(!TaskRepository::is_blocked(&pool, blocked.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_watchers_reflect_adds_and_removes() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let carol = create_test_user(&pool, "carol").await.unwrap();
+        let task = create_test_task(&pool, alice, "Watched").await.unwrap();
+
+        for user_id in [carol, bob, carol] {
+            TaskRepository::add_watcher(&pool, task.id, user_id)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            TaskRepository::watchers_for_task(&pool, task.id)
+                .await
+                .unwrap(),
+            vec![bob, carol]
+        );
+
+        TaskRepository::remove_watcher(&pool, task.id, carol)
+            .await
+            .unwrap();
+        TaskRepository::remove_watcher(&pool, task.id, alice)
+            .await
+            .unwrap();
+        assert_eq!(
+            TaskRepository::watchers_for_task(&pool, task.id)
+                .await
+                .unwrap(),
+            vec![bob]
+        );
+
+        let err = TaskRepository::add_watcher(&pool, 9999, bob)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::TaskNotFound(9999)));
+        let err = TaskRepository::add_watcher(&pool, task.id, 9999)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::UserNotFound(9999)));
+    }
+
+    #[tokio::test]
+    async fn test_audit_update_logs_only_changed_fields() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Draft").await.unwrap();
+
+        // The description is "set" to what it already was, so it's not a change
+        TaskRepository::update(
+            &pool,
+            task.id,
+            task.user_id,
+            UpdateTask::builder()
+                .title("Final")
+                .description(task.description.clone())
+                .priority(TaskPriority::Urgent)
+                .build(),
+        )
+        .await
+        .unwrap();
+        // Nothing changes at all, so nothing is recorded
+        TaskRepository::update(
+            &pool,
+            task.id,
+            task.user_id,
+            UpdateTask::builder().title("Final").build(),
+        )
+        .await
+        .unwrap();
+        TaskRepository::delete(&pool, task.id, task.user_id)
+            .await
+            .unwrap();
+
+        let history = TaskRepository::audit_history(&pool, task.id).await.unwrap();
+        let actions: Vec<_> = history.iter().map(|entry| entry.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::Create,
+                AuditAction::Update,
+                AuditAction::Delete
+            ]
+        );
+        assert!(history.iter().all(|entry| entry.user_id == alice));
+
+        let update = &history[1];
+        assert_eq!(
+            update.old_value,
+            Some(serde_json::json!({ "title": "Draft", "priority": "medium" }))
+        );
+        assert_eq!(
+            update.new_value,
+            Some(serde_json::json!({ "title": "Final", "priority": "urgent" }))
+        );
+
+        assert_eq!(history[0].old_value, None);
+        assert_eq!(history[0].new_value.as_ref().unwrap()["title"], "Draft");
+        assert_eq!(history[2].old_value.as_ref().unwrap()["title"], "Final");
+        assert_eq!(history[2].new_value, None);
+    }
+
+    #[tokio::test]
+    async fn test_audit_records_the_acting_user() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let admin = create_test_user(&pool, "admin").await.unwrap();
+        let (task, _) =
+            TaskRepository::create_if_absent(&pool, alice, new_test_task(alice, "Review"))
+                .await
+                .unwrap();
+        let json = r#"[
+            {"title": "Imported", "description": "", "status": "todo", "priority": "low", "due_date": null}
+        ]"#;
+        let imported = TaskRepository::import_json(&pool, alice, json)
+            .await
+            .unwrap();
+
+        TaskRepository::update(
+            &pool,
+            task.id,
+            admin,
+            UpdateTask::builder().title("Reviewed").build(),
+        )
+        .await
+        .unwrap();
+        TaskRepository::delete(&pool, task.id, admin).await.unwrap();
+
+        let history = TaskRepository::audit_history(&pool, task.id).await.unwrap();
+        let actors: Vec<_> = history
+            .iter()
+            .map(|entry| (entry.action, entry.user_id))
+            .collect();
+        assert_eq!(
+            actors,
+            vec![
+                (AuditAction::Create, alice),
+                (AuditAction::Update, admin),
+                (AuditAction::Delete, admin)
+            ]
+        );
+
+        let history = TaskRepository::audit_history(&pool, imported[0].id)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action, AuditAction::Create);
+        assert_eq!(history[0].user_id, alice);
+    }
+
+    #[tokio::test]
+    async fn test_find_due_today_uses_users_timezone() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        UserRepository::update(
+            &pool,
+            user_id,
+            crate::models::UpdateUser {
+                username: "alice".to_string(),
+                email: None,
+                timezone: Some("America/New_York".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        let at = |s: &str| Some(s.parse::<DateTime<Utc>>().unwrap());
+        let due = |s: &str| insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, at(s));
+
+        // 10:00 on Jan 15 in New York (UTC-5)
+        let now = "2024-01-15T15:00:00Z".parse().unwrap();
+        // 23:00 local: already Jan 16 in UTC, but today for the user
+        let late = due("2024-01-16T04:00:00Z").await;
+        let early = due("2024-01-15T05:30:00Z").await;
+        // 23:00 on Jan 14 local, and midnight starting Jan 16 local
+        due("2024-01-15T04:00:00Z").await;
+        due("2024-01-16T05:00:00Z").await;
+
+        let ids: Vec<i64> = TaskRepository::find_due_today_at(&pool, user_id, now)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, vec![early.id, late.id]);
+    }
+
+    #[tokio::test]
+    async fn test_set_priorities_in_bulk() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let mut tasks = Vec::new();
+        for _ in 0..4 {
+            tasks.push(insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Medium, None).await);
+        }
+
+        let updated = TaskRepository::set_priorities(
+            &pool,
+            &[
+                (tasks[0].id, TaskPriority::Urgent),
+                (tasks[1].id, TaskPriority::Low),
+                (tasks[2].id, TaskPriority::High),
+                (9999, TaskPriority::High),
+            ],
+        )
+        .await
+        .unwrap();
+
+        // The missing ID is skipped; the other three still apply
+        assert_eq!(updated, 3);
+        let mut priorities = Vec::new();
+        for task in &tasks {
+            let task = TaskRepository::find_by_id(&pool, task.id).await.unwrap();
+            priorities.push(task.priority);
+        }
+        assert_eq!(
+            priorities,
+            [
+                TaskPriority::Urgent,
+                TaskPriority::Low,
+                TaskPriority::High,
+                TaskPriority::Medium
+            ]
+        );
+        assert_eq!(TaskRepository::set_priorities(&pool, &[]).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_recent_activity_orders_by_latest_change() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let backdate = |id: i64, created: &'static str, updated: &'static str| {
+            sqlx::query(
+                "UPDATE tasks SET created_at = datetime('now', ?), updated_at = datetime('now', ?) WHERE id = ?",
+            )
+            .bind(created)
+            .bind(updated)
+            .bind(id)
+            .execute(&pool)
+        };
+        let edited = insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, None).await;
+        let created = insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, None).await;
+        let stale = insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, None).await;
+        // Created last month but edited yesterday, so newer than `created`
+        backdate(edited.id, "-30 days", "-1 days").await.unwrap();
+        backdate(created.id, "-3 days", "-3 days").await.unwrap();
+        backdate(stale.id, "-40 days", "-20 days").await.unwrap();
+
+        let ids: Vec<i64> = TaskRepository::recent_activity(&pool, user_id, 7, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, vec![edited.id, created.id]);
+
+        for (days, limit) in [(0, 10), (MAX_ACTIVITY_DAYS + 1, 10), (7, 0)] {
+            assert!(TaskRepository::recent_activity(&pool, user_id, days, limit)
+                .await
+                .unwrap_err()
+                .is_validation());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_created_per_day_fills_empty_days() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let created_ago = |id: i64, days: i64| {
+            sqlx::query("UPDATE tasks SET created_at = datetime('now', ?) WHERE id = ?")
+                .bind(format!("-{} days", days))
+                .bind(id)
+                .execute(&pool)
+        };
+        for days_ago in [0, 0, 2, 10] {
+            let task = insert(&pool, alice, TaskStatus::Todo, TaskPriority::Low, None).await;
+            created_ago(task.id, days_ago).await.unwrap();
+        }
+        // Another user's task on an otherwise empty day isn't counted
+        let task = insert(&pool, bob, TaskStatus::Todo, TaskPriority::Low, None).await;
+        created_ago(task.id, 1).await.unwrap();
+
+        let per_day = TaskRepository::created_per_day(&pool, alice, 4)
+            .await
+            .unwrap();
+
+        let today = Utc::now().date_naive();
+        let expected: Vec<(String, i64)> = [(3, 0), (2, 1), (1, 0), (0, 2)]
+            .into_iter()
+            .map(|(days_ago, count)| {
+                let day = today - Duration::days(days_ago);
+                (day.format("%Y-%m-%d").to_string(), count)
+            })
+            .collect();
+        assert_eq!(per_day, expected);
+
+        for days in [0, MAX_ACTIVITY_DAYS + 1] {
+            assert!(TaskRepository::created_per_day(&pool, alice, days)
+                .await
+                .unwrap_err()
+                .is_validation());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_with_subtasks_marks_whole_tree_done() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let parent_of = |child: i64, parent: i64| {
+            sqlx::query("UPDATE tasks SET parent_id = ? WHERE id = ?")
+                .bind(parent)
+                .bind(child)
+                .execute(&pool)
+        };
+        let root = insert(&pool, user_id, TaskStatus::Todo, TaskPriority::High, None).await;
+        let child = insert(
+            &pool,
+            user_id,
+            TaskStatus::InProgress,
+            TaskPriority::Low,
+            None,
+        )
+        .await;
+        let done_child = insert(&pool, user_id, TaskStatus::Done, TaskPriority::Low, None).await;
+        let grandchild = insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, None).await;
+        let unrelated = insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, None).await;
+        parent_of(child.id, root.id).await.unwrap();
+        parent_of(done_child.id, root.id).await.unwrap();
+        parent_of(grandchild.id, child.id).await.unwrap();
+
+        let completed = TaskRepository::complete_with_subtasks(&pool, root.id, user_id)
+            .await
+            .unwrap();
+
+        let ids: Vec<i64> = completed.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![root.id, child.id, done_child.id, grandchild.id]);
+        assert!(completed.iter().all(|t| t.status == TaskStatus::Done));
+        assert_eq!(completed[3].parent_id, Some(child.id));
+        // Already done, so left untouched
+        assert_eq!(completed[2].version, done_child.version);
+        let unrelated = TaskRepository::find_by_id(&pool, unrelated.id)
+            .await
+            .unwrap();
+        assert_eq!(unrelated.status, TaskStatus::Todo);
+
+        // A task can't be its own parent
+        assert!(parent_of(root.id, root.id).await.is_err());
+        assert!(matches!(
+            TaskRepository::complete_with_subtasks(&pool, 9999, user_id).await,
+            Err(AppError::TaskNotFound(9999))
+        ));
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        assert!(matches!(
+            TaskRepository::complete_with_subtasks(&pool, unrelated.id, bob).await,
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_parent_must_be_own_task_and_not_a_subtask() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let root = create_test_task(&pool, alice, "Move house").await.unwrap();
+        let bobs = create_test_task(&pool, bob, "Bob's").await.unwrap();
+
+        let child = TaskRepository::create(
+            &pool,
+            CreateTask {
+                parent_id: Some(root.id),
+                ..new_test_task(alice, "Pack books")
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(child.parent_id, Some(root.id));
+
+        let err = TaskRepository::create(
+            &pool,
+            CreateTask {
+                parent_id: Some(bobs.id),
+                ..new_test_task(alice, "Sneaky")
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+        let err = TaskRepository::update(
+            &pool,
+            child.id,
+            alice,
+            UpdateTask::builder().parent_id(9999).build(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::TaskNotFound(9999)));
+
+        // The root can't move under its own subtask, or itself
+        for parent in [child.id, root.id] {
+            let err = TaskRepository::update(
+                &pool,
+                root.id,
+                alice,
+                UpdateTask::builder().parent_id(parent).build(),
+            )
+            .await
+            .unwrap_err();
+            assert!(err.is_validation());
+        }
+
+        let moved = TaskRepository::update(
+            &pool,
+            child.id,
+            alice,
+            UpdateTask::builder().clear_parent().build(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(moved.parent_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_add_tag_to_many_skips_tasks_already_tagged() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(
+                insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Medium, None)
+                    .await
+                    .id,
+            );
+        }
+        TaskRepository::add_tag_to_many(&pool, &ids[..1], "Backend")
+            .await
+            .unwrap();
+
+        let added = TaskRepository::add_tag_to_many(&pool, &ids, "  backend ")
+            .await
+            .unwrap();
+
+        assert_eq!(added, 2);
+        for id in &ids {
+            assert_eq!(
+                TaskRepository::tags_for_task(&pool, *id).await.unwrap(),
+                ["backend"]
+            );
+        }
+        assert_eq!(
+            TaskRepository::add_tag_to_many(&pool, &[], "backend")
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_summaries_for_user() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let due = Utc::now() + Duration::days(2);
+        let first = insert(&pool, alice, TaskStatus::Todo, TaskPriority::Low, None).await;
+        let second = insert(
+            &pool,
+            alice,
+            TaskStatus::Done,
+            TaskPriority::Urgent,
+            Some(due),
+        )
+        .await;
+        insert(&pool, bob, TaskStatus::Todo, TaskPriority::Medium, None).await;
+
+        let mut summaries = TaskRepository::list_summaries(&pool, alice).await.unwrap();
+
+        // Both were created in the same second, so compare in id order
+        summaries.sort_by_key(|s| s.id);
+        assert_eq!(
+            summaries,
+            [TaskSummary::from(first), TaskSummary::from(second)]
+        );
+        assert!(summaries[1].due_date.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_find_by_priorities() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        for priority in [
+            TaskPriority::Low,
+            TaskPriority::Medium,
+            TaskPriority::High,
+            TaskPriority::Urgent,
+        ] {
+            insert(&pool, user_id, TaskStatus::Todo, priority, None).await;
+        }
+
+        let tasks = TaskRepository::find_by_priorities(
+            &pool,
+            user_id,
+            &[TaskPriority::High, TaskPriority::Urgent],
+        )
+        .await
+        .unwrap();
+        let mut priorities: Vec<_> = tasks.iter().map(|t| t.priority).collect();
+        priorities.sort();
+        assert_eq!(priorities, [TaskPriority::High, TaskPriority::Urgent]);
+
+        let all = TaskRepository::find_by_priorities(&pool, user_id, &[])
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_reorder_sets_positions() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(
+                insert(&pool, alice, TaskStatus::Todo, TaskPriority::Medium, None)
+                    .await
+                    .id,
+            );
+        }
+        let bobs = insert(&pool, bob, TaskStatus::Todo, TaskPriority::Medium, None).await;
+
+        let order = [ids[2], ids[0], ids[1]];
+        TaskRepository::reorder(&pool, alice, &order).await.unwrap();
+
+        let tasks = TaskRepository::find_by_user_ordered(&pool, alice)
+            .await
+            .unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), order);
+        assert_eq!(
+            tasks.iter().map(|t| t.position).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+
+        // Someone else's task aborts the whole reorder
+        let err = TaskRepository::reorder(&pool, alice, &[ids[0], bobs.id, ids[2]])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized(_)));
+        let tasks = TaskRepository::find_by_user_ordered(&pool, alice)
+            .await
+            .unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), order);
+
+        let err = TaskRepository::reorder(&pool, alice, &[ids[0], ids[0]])
+            .await
+            .unwrap_err();
+        assert!(err.is_validation());
+    }
+
+    #[tokio::test]
+    async fn test_listing_queries_use_composite_indexes() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        for _ in 0..200 {
+            insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Medium, None).await;
+        }
+
+        let indexes: Vec<(i64, String, bool, String, bool)> =
+            sqlx::query_as("PRAGMA index_list('tasks')")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let names: Vec<&str> = indexes.iter().map(|(_, name, ..)| name.as_str()).collect();
+        assert!(names.contains(&"idx_tasks_user_status"), "{:?}", names);
+        assert!(names.contains(&"idx_tasks_user_created"), "{:?}", names);
+
+        let plan = |sql: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let rows: Vec<(i64, i64, i64, String)> =
+                    sqlx::query_as(&format!("EXPLAIN QUERY PLAN {}", sql))
+                        .bind(user_id)
+                        .fetch_all(&pool)
+                        .await
+                        .unwrap();
+                rows.into_iter()
+                    .map(|(.., detail)| detail)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            }
+        };
+        let listing = plan("SELECT * FROM tasks WHERE user_id = ? ORDER BY created_at DESC").await;
+        assert!(listing.contains("idx_tasks_user_created"), "{}", listing);
+        assert!(!listing.contains("TEMP B-TREE"), "{}", listing);
+        let by_status = plan("SELECT * FROM tasks WHERE user_id = ? AND status = 'todo'").await;
+        assert!(by_status.contains("idx_tasks_user_status"), "{}", by_status);
+    }
+
+    #[tokio::test]
+    async fn test_find_due_within_window() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let in_hours = |h: i64| Some(Utc::now() + Duration::hours(h));
+        let soon = insert(
+            &pool,
+            user_id,
+            TaskStatus::Todo,
+            TaskPriority::Low,
+            in_hours(1),
+        )
+        .await;
+        let edge = insert(
+            &pool,
+            user_id,
+            TaskStatus::InProgress,
+            TaskPriority::Low,
+            in_hours(23),
+        )
+        .await;
+        // One hour past the window, already done, already overdue, or undated
+        insert(
+            &pool,
+            user_id,
+            TaskStatus::Todo,
+            TaskPriority::Low,
+            in_hours(25),
+        )
+        .await;
+        insert(
+            &pool,
+            user_id,
+            TaskStatus::Done,
+            TaskPriority::Low,
+            in_hours(2),
+        )
+        .await;
+        insert(
+            &pool,
+            user_id,
+            TaskStatus::Todo,
+            TaskPriority::Low,
+            in_hours(-1),
+        )
+        .await;
+        insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, None).await;
+
+        let due = TaskRepository::find_due_within(&pool, user_id, 24)
+            .await
+            .unwrap();
+
+        let ids: Vec<i64> = due.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![soon.id, edge.id]);
+        assert!(TaskRepository::find_due_within(&pool, user_id, -1)
+            .await
+            .unwrap_err()
+            .is_validation());
+    }
+
+    #[tokio::test]
+    async fn test_update_with_stale_version_conflicts() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Shared").await.unwrap();
+        assert_eq!(task.version, 1);
+
+        // Two clients read version 1; the first one to write wins
+        let first = TaskRepository::update(
+            &pool,
+            task.id,
+            task.user_id,
+            UpdateTask::builder().title("Mine").version(1).build(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.version, 2);
+
+        let err = TaskRepository::update(
+            &pool,
+            task.id,
+            task.user_id,
+            UpdateTask::builder().title("Theirs").version(1).build(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.is_conflict());
+        let current = TaskRepository::find_by_id(&pool, task.id).await.unwrap();
+        assert_eq!(current.title, "Mine");
+
+        // After refetching, the retry goes through
+        let retried = TaskRepository::update(
+            &pool,
+            task.id,
+            task.user_id,
+            UpdateTask::builder()
+                .title("Theirs")
+                .version(current.version)
+                .build(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(retried.title, "Theirs");
+        assert_eq!(retried.version, 3);
+    }
+
+    #[tokio::test]
+    async fn test_log_time_accumulates() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = TaskRepository::create(
+            &pool,
+            CreateTask {
+                estimated_minutes: Some(60),
+                ..new_test_task(alice, "Timed")
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(task.actual_minutes, None);
+        assert_eq!(task.time_variance(), None);
+
+        for minutes in [25, 0, 50] {
+            TaskRepository::log_time(&pool, task.id, minutes)
+                .await
+                .unwrap();
+        }
+        let task = TaskRepository::find_by_id(&pool, task.id).await.unwrap();
+        assert_eq!(task.actual_minutes, Some(75));
+        assert_eq!(task.time_variance(), Some(15));
+
+        let err = TaskRepository::log_time(&pool, task.id, -5)
+            .await
+            .unwrap_err();
+        assert!(err.is_validation());
+        let err = TaskRepository::log_time(&pool, 9999, 5).await.unwrap_err();
+        assert!(matches!(err, AppError::TaskNotFound(9999)));
+        assert_eq!(
+            TaskRepository::find_by_id(&pool, task.id)
+                .await
+                .unwrap()
+                .actual_minutes,
+            Some(75)
+        );
+
+        let err = TaskRepository::log_time(&pool, task.id, i32::MAX)
+            .await
+            .unwrap_err();
+        assert!(err.is_validation());
+        assert_eq!(
+            TaskRepository::find_by_id(&pool, task.id)
+                .await
+                .unwrap()
+                .actual_minutes,
+            Some(75)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_due_date_leave_vs_clear() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let due = Some(Utc::now() + Duration::days(1));
+        let task = insert(&pool, user_id, TaskStatus::Todo, TaskPriority::Low, due).await;
+
+        // Leaving due_date unset keeps the old value
+        let kept = TaskRepository::update(
+            &pool,
+            task.id,
+            task.user_id,
+            UpdateTask::builder().title("x").build(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(kept.due_date, task.due_date);
+
+        // Clearing writes NULL
+        let cleared = TaskRepository::update(
+            &pool,
+            task.id,
+            task.user_id,
+            UpdateTask::builder().clear_due_date().build(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(cleared.due_date, None);
+    }
+
+    #[tokio::test]
+    async fn test_find_by_due_range_is_inclusive() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let from = Utc::now() + Duration::days(1);
+        let to = from + Duration::days(7);
+
+        use TaskPriority::Medium;
+        use TaskStatus::Todo;
+        let at_start = insert(&pool, user_id, Todo, Medium, Some(from)).await;
+        let at_end = insert(&pool, user_id, Todo, Medium, Some(to)).await;
+        insert(
+            &pool,
+            user_id,
+            Todo,
+            Medium,
+            Some(from - Duration::seconds(1)),
+        )
+        .await;
+        insert(
+            &pool,
+            user_id,
+            Todo,
+            Medium,
+            Some(to + Duration::seconds(1)),
+        )
+        .await;
+        insert(&pool, user_id, Todo, Medium, None).await;
+
+        let tasks = TaskRepository::find_by_due_range(&pool, user_id, from, to)
+            .await
+            .unwrap();
+        let ids: Vec<i64> = tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![at_start.id, at_end.id]);
+    }
+
+    #[tokio::test]
+    async fn test_find_unscheduled_skips_done_and_dated_tasks() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let due = Some(Utc::now() + Duration::days(1));
+
+        let low = insert(&pool, alice, TaskStatus::Todo, TaskPriority::Low, None).await;
+        let urgent = insert(
+            &pool,
+            alice,
+            TaskStatus::InProgress,
+            TaskPriority::Urgent,
+            None,
+        )
+        .await;
+        let medium = insert(&pool, alice, TaskStatus::Todo, TaskPriority::Medium, None).await;
+        insert(&pool, alice, TaskStatus::Done, TaskPriority::Urgent, None).await;
+        insert(&pool, alice, TaskStatus::Todo, TaskPriority::Urgent, due).await;
+        insert(&pool, bob, TaskStatus::Todo, TaskPriority::Urgent, None).await;
+
+        let ids: Vec<i64> = TaskRepository::find_unscheduled(&pool, alice)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, vec![urgent.id, medium.id, low.id]);
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_titles_ignores_case_and_punctuation() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+
+        let mut ids = Vec::new();
+        for title in ["File taxes", "buy milk!", "Buy stamps", "Buy the milk"] {
+            let task = create_test_task(&pool, alice, title).await.unwrap();
+            ids.push(task.id);
+        }
+        create_test_task(&pool, bob, "Buy milk").await.unwrap();
+
+        let titles: Vec<String> = TaskRepository::find_similar_titles(&pool, alice, "Buy milk", 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(titles, vec!["buy milk!", "Buy the milk"]);
+
+        let closest = TaskRepository::find_similar_titles(&pool, alice, "Buy milk", 1)
+            .await
+            .unwrap();
+        assert_eq!(closest.len(), 1);
+        assert_eq!(closest[0].id, ids[1]);
+        assert!(
+            TaskRepository::find_similar_titles(&pool, alice, "Walk the dog", 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(TaskRepository::find_similar_titles(&pool, alice, "?!", 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(
+            TaskRepository::find_similar_titles(&pool, alice, "Buy milk", 0)
+                .await
+                .unwrap_err()
+                .is_validation()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_by_due_range_rejects_inverted_range() {
+        let pool = create_test_pool().await.unwrap();
+        let now = Utc::now();
+
+        let err = TaskRepository::find_by_due_range(&pool, 1, now, now - Duration::days(1))
+            .await
+            .unwrap_err();
+        assert!(err.is_validation());
+    }
+
+    #[tokio::test]
+    async fn test_import_json_forces_owner() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let json = r#"[
+            {"title": "One", "description": "", "status": "todo", "priority": "low", "due_date": null},
+            {"title": "Two", "description": "x", "status": "done", "priority": "high", "due_date": null, "user_id": 999}
+        ]"#;
+
+        let tasks = TaskRepository::import_json(&pool, alice, json)
+            .await
+            .unwrap();
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].title, "One");
+        assert_eq!(tasks[1].status, TaskStatus::Done);
+        assert!(tasks.iter().all(|t| t.user_id == alice));
+    }
+
+    #[tokio::test]
+    async fn test_import_json_round_trips_overdue_tasks() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let overdue = insert(
+            &pool,
+            alice,
+            TaskStatus::Todo,
+            TaskPriority::High,
+            Some(Utc::now() - Duration::days(3)),
+        )
+        .await;
+
+        let json =
+            serde_json::to_string(&TaskRepository::find_by_user(&pool, alice).await.unwrap())
+                .unwrap();
+        let imported = TaskRepository::import_json(&pool, bob, &json)
+            .await
+            .unwrap();
+
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].user_id, bob);
+        assert_eq!(imported[0].due_date, overdue.due_date);
+    }
+
+    #[tokio::test]
+    async fn test_import_json_rolls_back_on_invalid_task() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let json = r#"[
+            {"title": "Good", "description": "", "status": "todo", "priority": "low", "due_date": null},
+            {"title": "  ", "description": "", "status": "todo", "priority": "low", "due_date": null}
+        ]"#;
+
+        let err = TaskRepository::import_json(&pool, alice, json)
+            .await
+            .unwrap_err();
+        assert!(err.is_validation());
+        assert_eq!(
+            TaskRepository::count_by_user(&pool, alice).await.unwrap(),
+            0
+        );
+
+        // The first task is inserted and audited before the second's
+        // project check fails, so this only passes if the writes roll back
+        let json = r#"[
+            {"title": "Good", "description": "", "status": "todo", "priority": "low", "due_date": null},
+            {"title": "Lost", "description": "", "status": "todo", "priority": "low", "due_date": null, "project_id": 9999}
+        ]"#;
+        let err = TaskRepository::import_json(&pool, alice, json)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::NotFound {
+                entity: "Project",
+                id: 9999
+            }
+        ));
+        assert_eq!(
+            TaskRepository::count_by_user(&pool, alice).await.unwrap(),
+            0
+        );
+        let (audits,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM task_audit")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(audits, 0);
+
+        let err = TaskRepository::import_json(&pool, alice, "not json")
+            .await
+            .unwrap_err();
+        assert!(err.is_validation());
+
+        let json = r#"[{"title": "Bad date", "description": "", "status": "todo", "priority": "low", "due_date": "next tuesday"}]"#;
+        let err = TaskRepository::import_json(&pool, alice, json)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Validation(msg) if msg.contains("invalid due date")));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_insert_spans_batches() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+
+        let tasks: Vec<CreateTask> = (1..=2000)
+            .map(|i| new_test_task(alice, &format!("Imported {}", i)))
+            .collect();
+        let inserted = TaskRepository::bulk_insert(&pool, &tasks).await.unwrap();
+        assert_eq!(inserted, 2000);
+        assert_eq!(
+            TaskRepository::count_by_user(&pool, alice).await.unwrap(),
+            2000
+        );
+
+        // Exactly one batch, one row over, and one row short of two batches
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let mut total = 0;
+        for n in [
+            BULK_INSERT_BATCH_SIZE,
+            BULK_INSERT_BATCH_SIZE + 1,
+            2 * BULK_INSERT_BATCH_SIZE - 1,
+        ] {
+            let tasks: Vec<CreateTask> = (0..n)
+                .map(|i| new_test_task(bob, &format!("Task {}", i)))
+                .collect();
+            assert_eq!(
+                TaskRepository::bulk_insert(&pool, &tasks).await.unwrap(),
+                n as u64
+            );
+            total += n as i64;
+            assert_eq!(
+                TaskRepository::count_by_user(&pool, bob).await.unwrap(),
+                total
+            );
+        }
+
+        assert_eq!(TaskRepository::bulk_insert(&pool, &[]).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_insert_is_one_transaction() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+
+        // The last task's owner doesn't exist, so the final batch fails
+        // after the earlier ones have run
+        let mut tasks: Vec<CreateTask> = (1..=2000)
+            .map(|i| new_test_task(alice, &format!("Imported {}", i)))
+            .collect();
+        tasks.last_mut().unwrap().user_id = 9999;
+
+        let err = TaskRepository::bulk_insert(&pool, &tasks)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Database(_)));
+        assert_eq!(
+            TaskRepository::count_by_user(&pool, alice).await.unwrap(),
+            0
+        );
+
+        tasks.last_mut().unwrap().user_id = alice;
+        tasks[1500].title = "  ".to_string();
+        let err = TaskRepository::bulk_insert(&pool, &tasks)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, AppError::Validation(msg) if msg.starts_with("Task 1501:")));
+        assert_eq!(
+            TaskRepository::count_by_user(&pool, alice).await.unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_completing_weekly_task_spawns_next_week() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let due = Utc::now() + Duration::days(1);
+        let task = TaskRepository::create(
+            &pool,
+            CreateTask {
+                due_date: Some(due),
+                recurrence: Some(RecurrenceRule::Weekly),
+                ..new_test_task(alice, "Water plants")
+            },
+        )
+        .await
+        .unwrap();
+
+        let done = TaskRepository::complete_and_reschedule(&pool, task.id)
+            .await
+            .unwrap();
+        assert_eq!(done.status, TaskStatus::Done);
+
+        let tasks = TaskRepository::find_by_user(&pool, alice).await.unwrap();
+        assert_eq!(tasks.len(), 2);
+        let next = tasks.iter().find(|t| t.id != task.id).unwrap();
+        assert_eq!(next.status, TaskStatus::Todo);
+        assert_eq!(next.title, "Water plants");
+        assert_eq!(next.recurrence, Some(RecurrenceRule::Weekly));
+        assert_eq!(next.due_date, Some(due + Duration::days(7)));
+
+        // Completing again is a no-op
+        TaskRepository::complete_and_reschedule(&pool, task.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            TaskRepository::count_by_user(&pool, alice).await.unwrap(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_completing_one_off_task_just_marks_done() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Once").await.unwrap();
+
+        let done = TaskRepository::complete_and_reschedule(&pool, task.id)
+            .await
+            .unwrap();
+
+        assert_eq!(done.status, TaskStatus::Done);
+        assert_eq!(
+            TaskRepository::count_by_user(&pool, alice).await.unwrap(),
+            1
+        );
+        assert!(matches!(
+            TaskRepository::complete_and_reschedule(&pool, 9999).await,
+            Err(AppError::TaskNotFound(9999))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mark_done_and_in_progress() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Flip me").await.unwrap();
+
+        let started = TaskRepository::mark_in_progress(&pool, task.id)
+            .await
+            .unwrap();
+        assert_eq!(started.status, TaskStatus::InProgress);
+
+        let done = TaskRepository::mark_done(&pool, task.id).await.unwrap();
+        assert_eq!(done.status, TaskStatus::Done);
+        assert_eq!(done.title, "Flip me");
+    }
+
+    #[tokio::test]
+    async fn test_mark_status_not_found() {
+        let pool = create_test_pool().await.unwrap();
+
+        assert!(matches!(
+            TaskRepository::mark_done(&pool, 9999).await,
+            Err(AppError::TaskNotFound(9999))
+        ));
+        assert!(matches!(
+            TaskRepository::mark_in_progress(&pool, 9999).await,
+            Err(AppError::TaskNotFound(9999))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_is_independent_copy() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let due = Some(Utc::now() + Duration::days(2));
+        let original = insert(&pool, alice, TaskStatus::Done, TaskPriority::High, due).await;
+        for tag in ["home", "errand"] {
+            TaskRepository::add_tag_to_many(&pool, &[original.id], tag)
+                .await
+                .unwrap();
+        }
+
+        let copy = TaskRepository::duplicate(&pool, original.id).await.unwrap();
+
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.title, "task (copy)");
+        assert_eq!(copy.status, TaskStatus::Todo);
+        assert_eq!(copy.priority, TaskPriority::High);
+        assert_eq!(copy.due_date, original.due_date);
+        assert_eq!(copy.user_id, alice);
+        assert_eq!(
+            TaskRepository::tags_for_task(&pool, copy.id).await.unwrap(),
+            vec!["errand", "home"]
+        );
+
+        TaskRepository::update(
+            &pool,
+            copy.id,
+            copy.user_id,
+            UpdateTask::builder().title("Edited").build(),
+        )
+        .await
+        .unwrap();
+        TaskRepository::add_tag_to_many(&pool, &[copy.id], "urgent")
+            .await
+            .unwrap();
+        let reloaded = TaskRepository::find_by_id(&pool, original.id)
+            .await
+            .unwrap();
+        assert_eq!(reloaded.title, "task");
+        assert_eq!(reloaded.status, TaskStatus::Done);
+        assert_eq!(
+            TaskRepository::tags_for_task(&pool, original.id)
+                .await
+                .unwrap(),
+            vec!["errand", "home"]
+        );
+
+        assert!(matches!(
+            TaskRepository::duplicate(&pool, 9999).await,
+            Err(AppError::TaskNotFound(9999))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_keeps_long_titles_valid() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let title = "é".repeat(MAX_TITLE_LENGTH);
+        let original = create_test_task(&pool, alice, &title).await.unwrap();
+
+        let copy = TaskRepository::duplicate(&pool, original.id).await.unwrap();
 
-        Ok(exists.0 > 0)
+        assert_eq!(copy.title.chars().count(), MAX_TITLE_LENGTH);
+        assert!(copy.title.ends_with(" (copy)"));
+        // And the copy of the copy doesn't grow either
+        let again = TaskRepository::duplicate(&pool, copy.id).await.unwrap();
+        assert_eq!(again.title.chars().count(), MAX_TITLE_LENGTH);
     }
 }
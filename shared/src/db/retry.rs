@@ -0,0 +1,120 @@
+//! Retrying operations that fail on a busy or locked database.
+//!
+//! Even with a busy timeout, SQLite in WAL mode can still return
+//! "database is locked", e.g. when a read transaction tries to upgrade
+//! to a write. Repository write methods can be wrapped in [`retry`] so a
+//! brief lock costs a short delay instead of a failed request.
+
+use std::future::Future;
+use std::time::Duration;
+
+use tracing::warn;
+
+use crate::error::AppResult;
+
+/// Delay before the first retry; it doubles for each retry after that.
+pub const RETRY_BASE_DELAY: Duration = Duration::from_millis(10);
+
+/// Run `f` up to `max_attempts` times, backing off exponentially between
+/// attempts while it fails with a retryable error.
+///
+/// Only errors where [`AppError::is_retryable`](crate::AppError::is_retryable)
+/// is true are retried; anything else is returned at once. A `max_attempts`
+/// of zero is treated as one.
+///
+/// # Arguments
+/// * `max_attempts` - Most times to call `f`, including the first
+/// * `f` - Produces a fresh future for each attempt
+///
+/// # Returns
+/// * `AppResult<T>` - The first success
+///
+/// # Errors
+/// * The first non-retryable error, or the last retryable one once
+///   `max_attempts` is used up
+///
+/// # Example
+/// ```ignore
+/// let task = retry(3, || TaskRepository::mark_done(&pool, id)).await?;
+/// ```
+pub async fn retry<F, Fut, T>(max_attempts: u32, mut f: F) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    let max_attempts = max_attempts.max(1);
+    let mut delay = RETRY_BASE_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        match f().await {
+            Err(err) if err.is_retryable() && attempt < max_attempts => {
+                warn!(
+                    "Attempt {}/{} failed ({}), retrying in {:?}",
+                    attempt, max_attempts, err, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fake_db_error;
+    use crate::error::AppError;
+    use std::cell::Cell;
+
+    #[tokio::test]
+    async fn test_retries_locked_error_until_success() {
+        let calls = Cell::new(0);
+
+        let result = retry(5, || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                if attempt <= 2 {
+                    Err(fake_db_error("5", "database is locked"))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let calls = Cell::new(0);
+
+        let result: AppResult<()> = retry(2, || {
+            calls.set(calls.get() + 1);
+            async { Err(fake_db_error("5", "database is locked")) }
+        })
+        .await;
+
+        assert!(result.unwrap_err().is_retryable());
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_validation_error_is_not_retried() {
+        let calls = Cell::new(0);
+
+        let result: AppResult<()> = retry(5, || {
+            calls.set(calls.get() + 1);
+            async { Err(AppError::Validation("title is required".to_string())) }
+        })
+        .await;
+
+        assert!(result.unwrap_err().is_validation());
+        assert_eq!(calls.get(), 1);
+    }
+}
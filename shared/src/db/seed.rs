@@ -0,0 +1,185 @@
+//! Demo data for development databases (the `dev` feature).
+//!
+//! [`seed`] creates a `demo` user whose tasks cover every status and
+//! priority, so a fresh database has something to look at. It does
+//! nothing if the demo user already exists, so it's safe to call on every
+//! startup.
+
+use chrono::{Duration, Utc};
+use tracing::{info, instrument};
+
+use crate::db::{DbPool, TaskRepository, UserRepository};
+use crate::error::AppResult;
+use crate::models::{CreateTask, CreateUser, TaskPriority, TaskStatus};
+
+/// Username of the seeded account.
+pub const DEMO_USERNAME: &str = "demo";
+
+/// Password of the seeded account. Development only, obviously.
+pub const DEMO_PASSWORD: &str = "demo-password";
+
+/// The demo tasks: title, status, priority, and due date in days from now.
+const DEMO_TASKS: [(&str, TaskStatus, TaskPriority, Option<i64>); 12] = [
+    (
+        "Book dentist appointment",
+        TaskStatus::Todo,
+        TaskPriority::Low,
+        None,
+    ),
+    (
+        "Plan team offsite",
+        TaskStatus::Todo,
+        TaskPriority::Medium,
+        Some(14),
+    ),
+    (
+        "Review pull requests",
+        TaskStatus::Todo,
+        TaskPriority::High,
+        Some(1),
+    ),
+    (
+        "Renew TLS certificate",
+        TaskStatus::Todo,
+        TaskPriority::Urgent,
+        Some(-1),
+    ),
+    (
+        "Tidy up the wiki",
+        TaskStatus::InProgress,
+        TaskPriority::Low,
+        None,
+    ),
+    (
+        "Write release notes",
+        TaskStatus::InProgress,
+        TaskPriority::Medium,
+        Some(3),
+    ),
+    (
+        "Migrate CI to new runners",
+        TaskStatus::InProgress,
+        TaskPriority::High,
+        Some(7),
+    ),
+    (
+        "Fix login outage",
+        TaskStatus::InProgress,
+        TaskPriority::Urgent,
+        Some(0),
+    ),
+    ("Clean out inbox", TaskStatus::Done, TaskPriority::Low, None),
+    (
+        "Update onboarding docs",
+        TaskStatus::Done,
+        TaskPriority::Medium,
+        Some(-3),
+    ),
+    (
+        "Ship search feature",
+        TaskStatus::Done,
+        TaskPriority::High,
+        Some(-7),
+    ),
+    (
+        "Rotate leaked API key",
+        TaskStatus::Done,
+        TaskPriority::Urgent,
+        Some(-2),
+    ),
+];
+
+/// Fill the database with demo data, unless it's already there.
+///
+/// Creates the `demo` user (password [`DEMO_PASSWORD`]) and a task for
+/// every status/priority pair. If a `demo` user already exists, nothing
+/// is inserted.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+///
+/// # Returns
+/// * `AppResult<()>` - Success or error
+///
+/// # Errors
+/// * `AppError::Database` - If a query fails
+#[instrument(skip(pool), level = "debug")]
+pub async fn seed(pool: &DbPool) -> AppResult<()> {
+    if UserRepository::find_by_username(pool, DEMO_USERNAME)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let user = UserRepository::create(
+        pool,
+        CreateUser {
+            username: DEMO_USERNAME.to_string(),
+            password: DEMO_PASSWORD.to_string(),
+            email: None,
+        },
+    )
+    .await?;
+
+    let now = Utc::now();
+    for (title, status, priority, due_in_days) in DEMO_TASKS {
+        TaskRepository::create(
+            pool,
+            CreateTask {
+                title: title.to_string(),
+                description: String::new(),
+                status,
+                priority,
+                due_date: due_in_days.map(|days| now + Duration::days(days)),
+                user_id: user.id,
+                recurrence: None,
+                estimated_minutes: None,
+                actual_minutes: None,
+                project_id: None,
+                parent_id: None,
+            },
+        )
+        .await?;
+    }
+
+    info!(
+        "Seeded demo user '{}' with {} tasks",
+        DEMO_USERNAME,
+        DEMO_TASKS.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::create_test_pool;
+
+    #[tokio::test]
+    async fn test_seed_is_idempotent() {
+        let pool = create_test_pool().await.unwrap();
+
+        seed(&pool).await.unwrap();
+        seed(&pool).await.unwrap();
+
+        assert_eq!(UserRepository::count(&pool).await.unwrap(), 1);
+        let user = UserRepository::find_by_username(&pool, DEMO_USERNAME)
+            .await
+            .unwrap()
+            .unwrap();
+        let tasks = TaskRepository::find_by_user(&pool, user.id).await.unwrap();
+        assert_eq!(tasks.len(), DEMO_TASKS.len());
+        for status in [TaskStatus::Todo, TaskStatus::InProgress, TaskStatus::Done] {
+            assert!(tasks.iter().any(|t| t.status == status));
+        }
+        for priority in [
+            TaskPriority::Low,
+            TaskPriority::Medium,
+            TaskPriority::High,
+            TaskPriority::Urgent,
+        ] {
+            assert!(tasks.iter().any(|t| t.priority == priority));
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Shutdown path shared by the web and gRPC services.
+//!
+//! Both services stop accepting requests on [`shutdown_signal`], let
+//! in-flight ones finish, then call [`finish_shutdown`], which closes the
+//! pool with [`close_pool`] and picks the process exit code. Closing the
+//! pool closes every SQLite connection, and closing the last one
+//! checkpoints the WAL; a process that just exits can leave a large
+//! `-wal` file behind for the next start to replay.
+//!
+//! A service that stops on its own, e.g. because its server failed,
+//! exits non-zero so orchestrators restart it and count it as a crash.
+
+use std::fmt;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use tracing::{error, info};
+
+use crate::constants::POOL_CLOSE_TIMEOUT_SECS;
+use crate::db::DbPool;
+use crate::error::{AppError, AppResult};
+
+/// Why a service is shutting down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShutdownCause {
+    /// Ctrl+C or SIGTERM: a requested, clean stop
+    Signal,
+    /// The service can't keep running, e.g. its server stopped with an error
+    Fatal(String),
+}
+
+impl fmt::Display for ShutdownCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShutdownCause::Signal => f.write_str("shutdown signal"),
+            ShutdownCause::Fatal(reason) => write!(f, "fatal error: {}", reason),
+        }
+    }
+}
+
+/// The process exit code for a shutdown: 0 after a signal, 1 after a
+/// fatal error.
+pub fn exit_code(cause: &ShutdownCause) -> u8 {
+    match cause {
+        ShutdownCause::Signal => 0,
+        ShutdownCause::Fatal(_) => 1,
+    }
+}
+
+/// Wait for Ctrl+C, or SIGTERM on Unix (what container runtimes send).
+///
+/// # Panics
+/// If the signal handlers can't be installed, which only happens when
+/// the runtime is misconfigured.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+    info!("🛑 Received shutdown signal, cleaning up...");
+}
+
+/// Close every connection in the pool, waiting up to
+/// `POOL_CLOSE_TIMEOUT_SECS` for checked-out ones to be returned first.
+///
+/// Afterwards `pool.is_closed()` is true and queries fail with
+/// `AppError::Database(sqlx::Error::PoolClosed)`, even if closing timed out.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+///
+/// # Returns
+/// * `AppResult<u32>` - How many connections were open when closing started
+///
+/// # Errors
+/// * `AppError::Internal` - If connections were still checked out when the
+///   timeout ran out; their WAL changes may not be checkpointed
+pub async fn close_pool(pool: &DbPool) -> AppResult<u32> {
+    close_pool_within(pool, Duration::from_secs(POOL_CLOSE_TIMEOUT_SECS)).await
+}
+
+/// `close_pool` with the timeout as a parameter, so tests needn't wait.
+async fn close_pool_within(pool: &DbPool, timeout: Duration) -> AppResult<u32> {
+    let open = pool.size();
+    tokio::time::timeout(timeout, pool.close())
+        .await
+        .map_err(|_| {
+            AppError::Internal(format!(
+                "Timed out after {:?} closing the database pool ({} connection(s) still in use)",
+                timeout,
+                pool.size().saturating_sub(pool.num_idle() as u32)
+            ))
+        })?;
+    info!("🗄️  Closed {} database connection(s)", open);
+    Ok(open)
+}
+
+/// Log why `service` is stopping, close the pool, and return the exit code
+/// for `cause`.
+///
+/// A pool that fails to close is logged at ERROR but doesn't change the
+/// exit code, which always reflects the original cause.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `service` - Name for the log lines, e.g. "Web service"
+/// * `cause` - Why the service is stopping
+///
+/// # Returns
+/// * `ExitCode` - What `main` should return, from [`exit_code`]
+pub async fn finish_shutdown(pool: &DbPool, service: &str, cause: ShutdownCause) -> ExitCode {
+    match &cause {
+        ShutdownCause::Signal => info!(reason = %cause, "{} shutting down", service),
+        ShutdownCause::Fatal(_) => error!(reason = %cause, "{} shutting down", service),
+    }
+
+    // Close the pool so SQLite checkpoints the WAL before we exit
+    if let Err(err) = close_pool(pool).await {
+        error!("Failed to close the database pool: {}", err);
+    }
+
+    let code = exit_code(&cause);
+    if code == 0 {
+        info!("👋 {} stopped", service);
+    } else {
+        error!(exit_code = code, "{} stopped after a {}", service, cause);
+    }
+    ExitCode::from(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::create_test_pool;
+    use crate::error::AppError;
+
+    #[tokio::test]
+    async fn test_queries_fail_cleanly_after_close() {
+        let pool = create_test_pool().await.unwrap();
+
+        assert_eq!(close_pool(&pool).await.unwrap(), 1);
+
+        assert!(pool.is_closed());
+        assert_eq!(pool.size(), 0);
+        let err: AppError = sqlx::query("SELECT 1")
+            .execute(&pool)
+            .await
+            .unwrap_err()
+            .into();
+        assert!(matches!(err, AppError::Database(sqlx::Error::PoolClosed)));
+    }
+
+    #[test]
+    fn test_exit_code_by_cause() {
+        assert_eq!(exit_code(&ShutdownCause::Signal), 0);
+        assert_eq!(
+            exit_code(&ShutdownCause::Fatal("server error".to_string())),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_close_times_out_while_connection_is_held() {
+        let pool = create_test_pool().await.unwrap();
+        let held = pool.acquire().await.unwrap();
+
+        let err = close_pool_within(&pool, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, AppError::Internal(msg) if msg.contains("1 connection(s) still in use"))
+        );
+        // New queries are refused even though closing didn't finish
+        assert!(pool.is_closed());
+        drop(held);
+    }
+}
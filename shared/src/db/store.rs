@@ -0,0 +1,109 @@
+//! A mockable interface over task storage.
+//!
+//! [`TaskRepository`] is a unit struct with associated functions, which is
+//! convenient but can't be swapped out in tests. [`TaskStore`] covers the
+//! same CRUD operations as methods on a value, so code that only needs
+//! those can be generic over `S: TaskStore` and run against
+//! [`SqlxTaskRepository`] in production or
+//! [`MockTaskStore`](crate::db::MockTaskStore) (the `testing` feature) in
+//! tests.
+
+use std::future::Future;
+
+use crate::db::{DbPool, TaskRepository};
+use crate::error::AppResult;
+use crate::models::{CreateTask, Task, UpdateTask};
+
+/// Create, read, update, and delete tasks.
+///
+/// Implementations must behave like [`TaskRepository`]'s functions of the
+/// same names, including which `AppError` variants they return. Write
+/// the methods as `async fn`s; the trait spells out the `Send` futures
+/// warp handlers need, as `notify::Notifier` does.
+pub trait TaskStore: Send + Sync {
+    /// Create a new task; see [`TaskRepository::create`].
+    fn create(&self, task: CreateTask) -> impl Future<Output = AppResult<Task>> + Send;
+
+    /// Find a task by its ID; see [`TaskRepository::find_by_id`].
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If no task has the given ID
+    fn find_by_id(&self, id: i64) -> impl Future<Output = AppResult<Task>> + Send;
+
+    /// All of a user's tasks, newest first; see [`TaskRepository::find_by_user`].
+    fn find_by_user(&self, user_id: i64) -> impl Future<Output = AppResult<Vec<Task>>> + Send;
+
+    /// Apply a partial update on behalf of `user_id`; see
+    /// [`TaskRepository::update`].
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If a minutes field is negative
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    /// * `AppError::Conflict` - If `task.version` is set and stale
+    fn update(
+        &self,
+        id: i64,
+        user_id: i64,
+        task: UpdateTask,
+    ) -> impl Future<Output = AppResult<Task>> + Send;
+
+    /// Delete a task on behalf of `user_id`; see [`TaskRepository::delete`].
+    ///
+    /// # Errors
+    /// * `AppError::TaskNotFound` - If task doesn't exist
+    fn delete(&self, id: i64, user_id: i64) -> impl Future<Output = AppResult<()>> + Send;
+
+    /// Whether a task exists and belongs to `user_id`; see
+    /// [`TaskRepository::belongs_to_user`].
+    fn belongs_to_user(
+        &self,
+        task_id: i64,
+        user_id: i64,
+    ) -> impl Future<Output = AppResult<bool>> + Send;
+}
+
+/// [`TaskStore`] backed by the database, through [`TaskRepository`].
+///
+/// Cheap to clone: `DbPool` is reference-counted.
+#[derive(Debug, Clone)]
+pub struct SqlxTaskRepository {
+    pool: DbPool,
+}
+
+impl SqlxTaskRepository {
+    /// Store tasks in `pool`'s database.
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// The pool, for operations `TaskStore` doesn't cover.
+    pub fn pool(&self) -> &DbPool {
+        &self.pool
+    }
+}
+
+impl TaskStore for SqlxTaskRepository {
+    async fn create(&self, task: CreateTask) -> AppResult<Task> {
+        TaskRepository::create(&self.pool, task).await
+    }
+
+    async fn find_by_id(&self, id: i64) -> AppResult<Task> {
+        TaskRepository::find_by_id(&self.pool, id).await
+    }
+
+    async fn find_by_user(&self, user_id: i64) -> AppResult<Vec<Task>> {
+        TaskRepository::find_by_user(&self.pool, user_id).await
+    }
+
+    async fn update(&self, id: i64, user_id: i64, task: UpdateTask) -> AppResult<Task> {
+        TaskRepository::update(&self.pool, id, user_id, task).await
+    }
+
+    async fn delete(&self, id: i64, user_id: i64) -> AppResult<()> {
+        TaskRepository::delete(&self.pool, id, user_id).await
+    }
+
+    async fn belongs_to_user(&self, task_id: i64, user_id: i64) -> AppResult<bool> {
+        TaskRepository::belongs_to_user(&self.pool, task_id, user_id).await
+    }
+}
@@ -0,0 +1,158 @@
+//! Test helpers for an in-memory database.
+//!
+//! Compiled only for this crate's own tests or when the `testing` feature
+//! is enabled, so none of this ends up in production builds.
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+#[cfg(test)]
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use crate::db::{run_migrations, DbPool, TaskRepository};
+#[cfg(test)]
+use crate::error::AppError;
+use crate::error::AppResult;
+use crate::models::{CreateTask, Task, TaskPriority, TaskStatus};
+
+/// Create a fresh in-memory database with all migrations applied.
+///
+/// Uses the same embedded migrations as [`run_migrations`], so this works
+/// no matter which directory the test runs from.
+///
+/// # Returns
+/// * `AppResult<DbPool>` - Ready-to-use pool backed by `sqlite::memory:`
+pub async fn create_test_pool() -> AppResult<DbPool> {
+    let connect_options = SqliteConnectOptions::from_str("sqlite::memory:")?
+        .create_if_missing(true)
+        .foreign_keys(true);
+
+    // Every in-memory connection is a separate database, so the pool must
+    // hold exactly one connection and never close it
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .min_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(connect_options)
+        .await?;
+
+    run_migrations(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Insert a user with a placeholder password hash and return its ID.
+///
+/// Tasks have a foreign key to `users`, so most tests need an owner first.
+pub async fn create_test_user(pool: &DbPool, username: &str) -> AppResult<i64> {
+    let (id,): (i64,) = sqlx::query_as(
+        r#"
+        INSERT INTO users (username, password_hash)
+        VALUES (?, 'not-a-real-hash')
+        RETURNING id
+        "#,
+    )
+    .bind(username)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(id)
+}
+
+/// A valid `CreateTask` for `user_id`: a medium-priority to-do with just
+/// a title. Override fields with struct update syntax, e.g.
+/// `CreateTask { due_date: Some(due), ..new_test_task(user_id, "Pay rent") }`.
+pub fn new_test_task(user_id: i64, title: &str) -> CreateTask {
+    CreateTask {
+        title: title.to_string(),
+        description: String::new(),
+        status: TaskStatus::Todo,
+        priority: TaskPriority::Medium,
+        due_date: None,
+        user_id,
+        recurrence: None,
+        estimated_minutes: None,
+        actual_minutes: None,
+        project_id: None,
+        parent_id: None,
+    }
+}
+
+/// Insert [`new_test_task`]`(user_id, title)` and return the stored task.
+pub async fn create_test_task(pool: &DbPool, user_id: i64, title: &str) -> AppResult<Task> {
+    TaskRepository::create(pool, new_test_task(user_id, title)).await
+}
+
+/// Minimal stand-in for a driver error with a given code and message.
+#[cfg(test)]
+#[derive(Debug)]
+struct FakeDbError {
+    code: &'static str,
+    message: &'static str,
+}
+
+#[cfg(test)]
+impl std::fmt::Display for FakeDbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+#[cfg(test)]
+impl std::error::Error for FakeDbError {}
+
+#[cfg(test)]
+impl sqlx::error::DatabaseError for FakeDbError {
+    fn message(&self) -> &str {
+        self.message
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self.code))
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        sqlx::error::ErrorKind::Other
+    }
+}
+
+/// An `AppError::Database` carrying a driver error with the given SQLite
+/// result code and message, e.g. `("5", "database is locked")`.
+#[cfg(test)]
+pub(crate) fn fake_db_error(code: &'static str, message: &'static str) -> AppError {
+    AppError::Database(sqlx::Error::Database(Box::new(FakeDbError {
+        code,
+        message,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_and_fetch_task() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+
+        let created = create_test_task(&pool, user_id, "Smoke test")
+            .await
+            .unwrap();
+
+        let fetched = TaskRepository::find_by_id(&pool, created.id).await.unwrap();
+        assert_eq!(fetched.title, "Smoke test");
+        assert_eq!(fetched.user_id, user_id);
+    }
+}
@@ -0,0 +1,944 @@
+//! Repository for user accounts.
+//!
+//! Mirrors [`TaskRepository`](crate::db::TaskRepository): a unit struct with
+//! async associated functions that take the pool explicitly.
+
+use chrono::{DateTime, Duration, Utc};
+use tracing::{instrument, warn};
+
+use crate::auth::{
+    hash_password, hash_token, random_token, verify_dummy_password, verify_password,
+};
+use crate::config::LockoutConfig;
+use crate::constants::{
+    EMAIL_VERIFICATION_TTL_HOURS, MAX_USERNAME_LENGTH, MIN_PASSWORD_LENGTH, MIN_USERNAME_LENGTH,
+};
+use crate::db::repository::{validate_limit, validate_offset};
+use crate::db::{ensure_writable, DbPool};
+use crate::error::{map_unique_violation, AppError, AppResult};
+use crate::models::{CreateUser, UpdateUser, User, UserResponse};
+
+/// Repository for user entity operations.
+pub struct UserRepository;
+
+impl UserRepository {
+    /// Register a new user, hashing their password before storage.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user` - Registration data with the plain-text password
+    ///
+    /// # Returns
+    /// * `AppResult<User>` - Created user with generated ID and timestamps
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the username or password is too short/long
+    /// * `AppError::UsernameExists` - If the username is taken
+    /// * `AppError::Conflict` - If another unique value (e.g. email) is taken
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip_all, level = "debug", fields(username = %user.username))]
+    pub async fn create(pool: &DbPool, user: CreateUser) -> AppResult<User> {
+        ensure_writable()?;
+        validate_username(&user.username)?;
+        if user.password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AppError::Validation(format!(
+                "Password must be at least {} characters",
+                MIN_PASSWORD_LENGTH
+            )));
+        }
+
+        let password_hash = hash_password(&user.password)?;
+
+        let created = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (username, password_hash, email)
+            VALUES (?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(&user.username)
+        .bind(&password_hash)
+        .bind(&user.email)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| map_user_conflict(e, &user.username))?;
+
+        Ok(created)
+    }
+
+    /// Replace a user's username, email, and timezone.
+    ///
+    /// Changing the email clears `email_verified`, since the new address
+    /// hasn't been proven yet.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - ID of the user to update
+    /// * `user` - New values for every field
+    ///
+    /// # Returns
+    /// * `AppResult<User>` - The updated user
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the username is too short/long or the timezone is unknown
+    /// * `AppError::UserNotFound` - If no user has the given ID
+    /// * `AppError::UsernameExists` - If the username is taken
+    /// * `AppError::Conflict` - If another unique value (e.g. email) is taken
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, user), level = "debug")]
+    pub async fn update(pool: &DbPool, id: i64, user: UpdateUser) -> AppResult<User> {
+        ensure_writable()?;
+        validate_username(&user.username)?;
+        user.validate()?;
+
+        let updated = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET username = ?,
+                email_verified = CASE WHEN email IS ? THEN email_verified ELSE 0 END,
+                email = ?,
+                timezone = ?,
+                updated_at = datetime('now')
+            WHERE id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(&user.username)
+        .bind(&user.email)
+        .bind(&user.email)
+        .bind(&user.timezone)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| map_user_conflict(e, &user.username))?;
+
+        updated.ok_or(AppError::UserNotFound(id))
+    }
+
+    /// Find a user by their ID.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `id` - User ID to search for
+    ///
+    /// # Returns
+    /// * `AppResult<User>` - Found user
+    ///
+    /// # Errors
+    /// * `AppError::UserNotFound` - If no user has the given ID
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_by_id(pool: &DbPool, id: i64) -> AppResult<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            SELECT * FROM users
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        user.ok_or(AppError::UserNotFound(id))
+    }
+
+    /// Find a user by username, e.g. when logging in.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `username` - Exact username to look up
+    ///
+    /// # Returns
+    /// * `AppResult<Option<User>>` - The user, or `None` if no such username
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn find_by_username(pool: &DbPool, username: &str) -> AppResult<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            SELECT * FROM users
+            WHERE username = ?
+            "#,
+        )
+        .bind(username)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Check a login, counting wrong passwords towards a lockout.
+    ///
+    /// Once `lockout.max_failed_logins` wrong passwords have been tried in
+    /// a row, the next one locks the account for `lockout.lockout_minutes`.
+    /// While it's locked every attempt is refused, right password or not,
+    /// and doesn't count towards the next lock. A successful login resets
+    /// the count. Wrong passwords given to
+    /// [`change_password`](Self::change_password) count too.
+    ///
+    /// A locked account is refused with the same error as a wrong password,
+    /// so a guesser can't tell they've hit the lock; it's logged instead.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `username` - Username to log in as
+    /// * `password` - Plain-text password to check
+    /// * `lockout` - When to lock the account, and for how long
+    ///
+    /// # Returns
+    /// * `AppResult<User>` - The user, if the password is right
+    ///
+    /// # Errors
+    /// * `AppError::InvalidCredentials` - If there's no such user, the
+    ///   password is wrong, or the account is locked
+    /// * `AppError::Database` - If a query fails
+    #[instrument(skip(pool, password), level = "debug")]
+    pub async fn authenticate(
+        pool: &DbPool,
+        username: &str,
+        password: &str,
+        lockout: &LockoutConfig,
+    ) -> AppResult<User> {
+        let Some(user) = Self::find_by_username(pool, username).await? else {
+            // Take as long as a wrong password would, so response times
+            // don't reveal which usernames exist
+            verify_dummy_password(password);
+            return Err(AppError::InvalidCredentials);
+        };
+
+        check_password(pool, &user, password, lockout).await?;
+        Ok(user)
+    }
+
+    /// Change a user's password after checking their current one.
+    ///
+    /// A wrong `old_plain` counts towards the same lockout as a failed
+    /// [`authenticate`](Self::authenticate), so this can't be used to keep
+    /// guessing a password once logins are locked.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user
+    /// * `old_plain` - Current plain-text password
+    /// * `new_plain` - Desired plain-text password
+    /// * `lockout` - When to lock the account, and for how long
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::UserNotFound` - If no user has the given ID
+    /// * `AppError::InvalidCredentials` - If `old_plain` is wrong or the
+    ///   account is locked
+    /// * `AppError::Validation` - If `new_plain` is too short or the same as `old_plain`
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool, old_plain, new_plain), level = "debug")]
+    pub async fn change_password(
+        pool: &DbPool,
+        user_id: i64,
+        old_plain: &str,
+        new_plain: &str,
+        lockout: &LockoutConfig,
+    ) -> AppResult<()> {
+        ensure_writable()?;
+        let user = Self::find_by_id(pool, user_id).await?;
+
+        check_password(pool, &user, old_plain, lockout).await?;
+        if new_plain.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AppError::Validation(format!(
+                "Password must be at least {} characters",
+                MIN_PASSWORD_LENGTH
+            )));
+        }
+        if new_plain == old_plain {
+            return Err(AppError::Validation(
+                "New password must differ from the current one".to_string(),
+            ));
+        }
+
+        let password_hash = hash_password(new_plain)?;
+
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET password_hash = ?, updated_at = datetime('now')
+            WHERE id = ?
+            "#,
+        )
+        .bind(&password_hash)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Issue a token that proves ownership of the user's email when
+    /// presented to [`verify_email`](Self::verify_email).
+    ///
+    /// The token is meant to be emailed as part of a link. Only its hash is
+    /// stored, so the table alone can't verify anyone. It expires after
+    /// `EMAIL_VERIFICATION_TTL_HOURS`; issuing a new one doesn't revoke
+    /// older ones.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - ID of the user whose email to verify
+    ///
+    /// # Returns
+    /// * `AppResult<String>` - The random, URL-safe token
+    ///
+    /// # Errors
+    /// * `AppError::UserNotFound` - If no user has the given ID
+    /// * `AppError::Validation` - If the user has no email address
+    /// * `AppError::Database` - If database insertion fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn create_verification_token(pool: &DbPool, user_id: i64) -> AppResult<String> {
+        ensure_writable()?;
+        let user = Self::find_by_id(pool, user_id).await?;
+        if user.email.is_none() {
+            return Err(AppError::Validation(
+                "User has no email address to verify".to_string(),
+            ));
+        }
+
+        let token = random_token();
+        let expires_at = Utc::now() + Duration::hours(EMAIL_VERIFICATION_TTL_HOURS);
+
+        sqlx::query(
+            r#"
+            INSERT INTO email_verification_tokens (token_hash, user_id, expires_at)
+            VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(hash_token(&token))
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Mark a user's email as verified, consuming the token.
+    ///
+    /// Each token works once; an expired one is left unconsumed but never
+    /// accepted.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `token` - Token from `create_verification_token`
+    ///
+    /// # Returns
+    /// * `AppResult<()>` - Success or error
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the token is unknown, already used, or expired
+    /// * `AppError::Database` - If database update fails
+    /// * `AppError::ReadOnly` - If the service is in read-only mode
+    #[instrument(skip_all, level = "debug")]
+    pub async fn verify_email(pool: &DbPool, token: &str) -> AppResult<()> {
+        ensure_writable()?;
+        let token_hash = hash_token(token);
+        let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let (user_id, expires_at): (i64, DateTime<Utc>) = sqlx::query_as(
+            r#"
+            SELECT user_id, expires_at FROM email_verification_tokens
+            WHERE token_hash = ?
+            "#,
+        )
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| {
+            AppError::Validation("Invalid or already used verification token".to_string())
+        })?;
+
+        if expires_at <= Utc::now() {
+            return Err(AppError::Validation(
+                "Verification token has expired".to_string(),
+            ));
+        }
+
+        sqlx::query("DELETE FROM email_verification_tokens WHERE token_hash = ?")
+            .bind(&token_hash)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET email_verified = 1, updated_at = datetime('now')
+            WHERE id = ?
+            "#,
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// List users, oldest account first, for admin tooling.
+    ///
+    /// Selects only the public columns, so password hashes never leave
+    /// the database.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `limit` - Page size (1 to `MAX_PAGE_SIZE`)
+    /// * `offset` - Users to skip before the first one returned
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<UserResponse>>` - Up to `limit` users
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `limit` is out of range or `offset` is negative
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip(pool), level = "debug")]
+    pub async fn list(pool: &DbPool, limit: i64, offset: i64) -> AppResult<Vec<UserResponse>> {
+        validate_limit(limit)?;
+        validate_offset(offset)?;
+
+        let users = sqlx::query_as::<_, UserResponse>(
+            r#"
+            SELECT id, username, email, created_at FROM users
+            ORDER BY created_at ASC, id ASC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    /// List every user with the number of tasks they own, for an account
+    /// overview.
+    ///
+    /// One query: users are LEFT JOINed to their tasks and grouped, so
+    /// users without any tasks still appear with a count of 0. Only the
+    /// public columns are selected, never the password hash.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    ///
+    /// # Returns
+    /// * `AppResult<Vec<(UserResponse, i64)>>` - Each user and their task
+    ///   count, oldest account first
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip_all, level = "debug")]
+    pub async fn list_with_task_counts(pool: &DbPool) -> AppResult<Vec<(UserResponse, i64)>> {
+        let rows = sqlx::query_as::<_, (i64, String, Option<String>, DateTime<Utc>, i64)>(
+            r#"
+            SELECT u.id, u.username, u.email, u.created_at, COUNT(t.id)
+            FROM users u
+            LEFT JOIN tasks t ON t.user_id = u.id
+            GROUP BY u.id
+            ORDER BY u.created_at ASC, u.id ASC
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, username, email, created_at, count)| {
+                (
+                    UserResponse {
+                        id,
+                        username,
+                        email,
+                        created_at,
+                    },
+                    count,
+                )
+            })
+            .collect())
+    }
+
+    /// Count all registered users.
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    ///
+    /// # Returns
+    /// * `AppResult<i64>` - Total number of users
+    ///
+    /// # Errors
+    /// * `AppError::Database` - If database query fails
+    #[instrument(skip_all, level = "debug")]
+    pub async fn count(pool: &DbPool) -> AppResult<i64> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+            .fetch_one(pool)
+            .await?;
+
+        Ok(count)
+    }
+}
+
+/// Check a username against the length limits in [`crate::constants`].
+fn validate_username(username: &str) -> AppResult<()> {
+    let username_len = username.chars().count();
+    if !(MIN_USERNAME_LENGTH..=MAX_USERNAME_LENGTH).contains(&username_len) {
+        return Err(AppError::Validation(format!(
+            "Username must be between {} and {} characters",
+            MIN_USERNAME_LENGTH, MAX_USERNAME_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Map a unique violation on `users`, giving usernames their own variant
+/// so callers can say which field clashed without parsing the message.
+fn map_user_conflict(err: sqlx::Error, username: &str) -> AppError {
+    match map_unique_violation(err) {
+        AppError::Conflict(msg) if msg.starts_with("username") => {
+            AppError::UsernameExists(username.to_string())
+        }
+        other => other,
+    }
+}
+
+/// Check `password` against `user`'s hash under the lockout policy; see
+/// [`UserRepository::authenticate`].
+async fn check_password(
+    pool: &DbPool,
+    user: &User,
+    password: &str,
+    lockout: &LockoutConfig,
+) -> AppResult<()> {
+    let now = Utc::now();
+    if let Some(until) = user.locked_until.filter(|until| *until > now) {
+        // Still hash, so a locked account answers as slowly as any other
+        let _ = verify_password(password, &user.password_hash);
+        warn!(user_id = user.id, locked_until = %until, "Refused password for locked account");
+        return Err(AppError::InvalidCredentials);
+    }
+
+    if verify_password(password, &user.password_hash)? {
+        if user.failed_attempts != 0 || user.locked_until.is_some() {
+            sqlx::query("UPDATE users SET failed_attempts = 0, locked_until = NULL WHERE id = ?")
+                .bind(user.id)
+                .execute(pool)
+                .await?;
+        }
+        return Ok(());
+    }
+
+    // Count in SQL so concurrent failures can't overwrite each other
+    let (failed_attempts,): (i64,) = sqlx::query_as(
+        r#"
+        UPDATE users SET failed_attempts = failed_attempts + 1
+        WHERE id = ?
+        RETURNING failed_attempts
+        "#,
+    )
+    .bind(user.id)
+    .fetch_one(pool)
+    .await?;
+
+    if failed_attempts > i64::from(lockout.max_failed_logins) {
+        let locked_until = now + Duration::minutes(i64::from(lockout.lockout_minutes));
+        sqlx::query("UPDATE users SET failed_attempts = 0, locked_until = ? WHERE id = ?")
+            .bind(locked_until)
+            .bind(user.id)
+            .execute(pool)
+            .await?;
+        warn!(
+            user_id = user.id,
+            failed_attempts,
+            %locked_until,
+            "Locked account after repeated wrong passwords"
+        );
+    }
+
+    Err(AppError::InvalidCredentials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{create_test_pool, create_test_user};
+
+    fn new_user(username: &str, email: Option<&str>) -> CreateUser {
+        CreateUser {
+            username: username.to_string(),
+            password: "password123".to_string(),
+            email: email.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_hashes_password() {
+        let pool = create_test_pool().await.unwrap();
+
+        let user = UserRepository::create(&pool, new_user("alice", None))
+            .await
+            .unwrap();
+
+        assert_ne!(user.password_hash, "password123");
+        assert!(verify_password("password123", &user.password_hash).unwrap());
+        let fetched = UserRepository::find_by_id(&pool, user.id).await.unwrap();
+        assert_eq!(fetched.username, "alice");
+
+        let by_name = UserRepository::find_by_username(&pool, "alice")
+            .await
+            .unwrap();
+        assert_eq!(by_name.map(|u| u.id), Some(user.id));
+        assert!(UserRepository::find_by_username(&pool, "nobody")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_pages_through_users() {
+        let pool = create_test_pool().await.unwrap();
+        let names = ["alice", "bob", "carol", "dave", "erin"];
+        for name in names {
+            create_test_user(&pool, name).await.unwrap();
+        }
+
+        let first = UserRepository::list(&pool, 2, 0).await.unwrap();
+        let second = UserRepository::list(&pool, 2, 2).await.unwrap();
+        let last = UserRepository::list(&pool, 2, 4).await.unwrap();
+
+        let listed: Vec<String> = [first, second, last]
+            .concat()
+            .into_iter()
+            .map(|u| u.username)
+            .collect();
+        assert_eq!(listed, names);
+        assert_eq!(UserRepository::count(&pool).await.unwrap(), 5);
+
+        assert!(UserRepository::list(&pool, 0, 0)
+            .await
+            .unwrap_err()
+            .is_validation());
+        assert!(UserRepository::list(&pool, 2, -1)
+            .await
+            .unwrap_err()
+            .is_validation());
+    }
+
+    #[tokio::test]
+    async fn test_list_with_task_counts_includes_users_without_tasks() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        for title in ["One", "Two", "Three"] {
+            sqlx::query("INSERT INTO tasks (title, description, user_id) VALUES (?, '', ?)")
+                .bind(title)
+                .bind(bob)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let counts: Vec<(i64, String, i64)> = UserRepository::list_with_task_counts(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(user, count)| (user.id, user.username, count))
+            .collect();
+
+        assert_eq!(
+            counts,
+            vec![(alice, "alice".to_string(), 0), (bob, "bob".to_string(), 3)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_change_password_rejects_wrong_old_password() {
+        let pool = create_test_pool().await.unwrap();
+        let user = UserRepository::create(&pool, new_user("alice", None))
+            .await
+            .unwrap();
+
+        let err = UserRepository::change_password(
+            &pool,
+            user.id,
+            "not-my-password",
+            "brand new pass",
+            &LockoutConfig::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::InvalidCredentials));
+
+        // Too short, or unchanged, even with the right old password
+        for new_plain in ["short", "password123"] {
+            let err = UserRepository::change_password(
+                &pool,
+                user.id,
+                "password123",
+                new_plain,
+                &LockoutConfig::default(),
+            )
+            .await
+            .unwrap_err();
+            assert!(err.is_validation(), "{}", new_plain);
+        }
+
+        let stored = UserRepository::find_by_id(&pool, user.id).await.unwrap();
+        assert_eq!(stored.password_hash, user.password_hash);
+    }
+
+    #[tokio::test]
+    async fn test_change_password_success() {
+        let pool = create_test_pool().await.unwrap();
+        let user = UserRepository::create(&pool, new_user("alice", None))
+            .await
+            .unwrap();
+
+        UserRepository::change_password(
+            &pool,
+            user.id,
+            "password123",
+            "brand new pass",
+            &LockoutConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        let stored = UserRepository::find_by_id(&pool, user.id).await.unwrap();
+        assert!(verify_password("brand new pass", &stored.password_hash).unwrap());
+        assert!(!verify_password("password123", &stored.password_hash).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_email_is_conflict() {
+        let pool = create_test_pool().await.unwrap();
+        UserRepository::create(&pool, new_user("alice", Some("a@example.com")))
+            .await
+            .unwrap();
+
+        let err = UserRepository::create(&pool, new_user("bob", Some("a@example.com")))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::Conflict(_)), "got {:?}", err);
+        assert!(err.is_conflict());
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_username_is_username_exists() {
+        let pool = create_test_pool().await.unwrap();
+        UserRepository::create(&pool, new_user("alice", None))
+            .await
+            .unwrap();
+
+        let err = UserRepository::create(&pool, new_user("alice", None))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::UsernameExists(ref name) if name == "alice"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_consumes_token() {
+        let pool = create_test_pool().await.unwrap();
+        let user = UserRepository::create(&pool, new_user("alice", Some("a@example.com")))
+            .await
+            .unwrap();
+        assert!(!user.email_verified);
+
+        let token = UserRepository::create_verification_token(&pool, user.id)
+            .await
+            .unwrap();
+        UserRepository::verify_email(&pool, &token).await.unwrap();
+
+        let stored = UserRepository::find_by_id(&pool, user.id).await.unwrap();
+        assert!(stored.email_verified);
+        // Single use
+        assert!(UserRepository::verify_email(&pool, &token)
+            .await
+            .unwrap_err()
+            .is_validation());
+    }
+
+    #[tokio::test]
+    async fn test_verification_tokens_are_stored_hashed() {
+        let pool = create_test_pool().await.unwrap();
+        let user = UserRepository::create(&pool, new_user("alice", Some("a@example.com")))
+            .await
+            .unwrap();
+        let token = UserRepository::create_verification_token(&pool, user.id)
+            .await
+            .unwrap();
+
+        let (stored,): (String,) =
+            sqlx::query_as("SELECT token_hash FROM email_verification_tokens WHERE user_id = ?")
+                .bind(user.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_ne!(stored, token);
+        // The stored value itself doesn't verify
+        assert!(UserRepository::verify_email(&pool, &stored)
+            .await
+            .unwrap_err()
+            .is_validation());
+    }
+
+    #[tokio::test]
+    async fn test_verification_token_needs_an_email() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+
+        let err = UserRepository::create_verification_token(&pool, user_id)
+            .await
+            .unwrap_err();
+
+        assert!(err.is_validation());
+    }
+
+    #[tokio::test]
+    async fn test_verify_email_rejects_expired_token() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = UserRepository::create(&pool, new_user("alice", Some("a@example.com")))
+            .await
+            .unwrap()
+            .id;
+        let token = UserRepository::create_verification_token(&pool, user_id)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE email_verification_tokens SET expires_at = ? WHERE user_id = ?")
+            .bind(Utc::now() - Duration::minutes(1))
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let err = UserRepository::verify_email(&pool, &token)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::Validation(ref msg) if msg.contains("expired")));
+        let stored = UserRepository::find_by_id(&pool, user_id).await.unwrap();
+        assert!(!stored.email_verified);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failed_logins_lock_account() {
+        let pool = create_test_pool().await.unwrap();
+        UserRepository::create(&pool, new_user("alice", None))
+            .await
+            .unwrap();
+        let lockout = LockoutConfig {
+            max_failed_logins: 3,
+            lockout_minutes: 15,
+        };
+
+        for _ in 0..3 {
+            let err = UserRepository::authenticate(&pool, "alice", "wrong", &lockout)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::InvalidCredentials), "{:?}", err);
+        }
+        // The (N+1)th wrong password locks the account, though the caller
+        // can't tell from the error...
+        let err = UserRepository::authenticate(&pool, "alice", "wrong", &lockout)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::InvalidCredentials), "{:?}", err);
+        let stored = UserRepository::find_by_username(&pool, "alice")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.locked_until.is_some());
+        // ...and the right one is refused until the lock runs out
+        let err = UserRepository::authenticate(&pool, "alice", "password123", &lockout)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::InvalidCredentials), "{:?}", err);
+
+        sqlx::query("UPDATE users SET locked_until = ? WHERE username = 'alice'")
+            .bind(Utc::now() - Duration::minutes(1))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let user = UserRepository::authenticate(&pool, "alice", "password123", &lockout)
+            .await
+            .unwrap();
+        assert_eq!(user.username, "alice");
+        let stored = UserRepository::find_by_id(&pool, user.id).await.unwrap();
+        assert_eq!(stored.failed_attempts, 0);
+        assert_eq!(stored.locked_until, None);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_old_passwords_count_towards_lockout() {
+        let pool = create_test_pool().await.unwrap();
+        let user = UserRepository::create(&pool, new_user("alice", None))
+            .await
+            .unwrap();
+        let lockout = LockoutConfig {
+            max_failed_logins: 2,
+            lockout_minutes: 15,
+        };
+
+        for _ in 0..3 {
+            let err = UserRepository::change_password(
+                &pool,
+                user.id,
+                "guess",
+                "brand new pass",
+                &lockout,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(err, AppError::InvalidCredentials), "{:?}", err);
+        }
+
+        // Locked now, for logins and password changes alike
+        let err = UserRepository::authenticate(&pool, "alice", "password123", &lockout)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::InvalidCredentials), "{:?}", err);
+        let err = UserRepository::change_password(
+            &pool,
+            user.id,
+            "password123",
+            "brand new pass",
+            &lockout,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::InvalidCredentials), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_unknown_timezone() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let update = |timezone: &str| UpdateUser {
+            username: "alice".to_string(),
+            email: None,
+            timezone: Some(timezone.to_string()),
+        };
+
+        let err = UserRepository::update(&pool, user_id, update("Mars/Olympus_Mons"))
+            .await
+            .unwrap_err();
+        assert!(err.is_validation());
+
+        let user = UserRepository::update(&pool, user_id, update("Asia/Tokyo"))
+            .await
+            .unwrap();
+        assert_eq!(user.timezone.as_deref(), Some("Asia/Tokyo"));
+        assert_eq!(user.tz(), chrono_tz::Asia::Tokyo);
+    }
+}
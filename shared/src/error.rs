@@ -3,6 +3,12 @@
 //! This module defines all possible errors that can occur in the application.
 //! Using `thiserror`, we get automatic implementations of standard error traits.
 
+use std::fmt;
+use std::time::Duration;
+
+use http::StatusCode;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
 use thiserror::Error;
 
 /// All possible errors in the application.
@@ -19,18 +25,62 @@ pub enum AppError {
     #[error("Migration error: {0}")]
     Migration(#[from] sqlx::migrate::MigrateError),
 
-    /// Task not found in the database
+    /// Any entity not found in the database, e.g.
+    /// `NotFound { entity: "Project", id: 5 }`.
+    ///
+    /// New repositories use this rather than adding a variant per type.
+    /// Its [`code`](AppError::code) is `comment_not_found`,
+    /// `project_not_found`, or `attachment_not_found` for those entities,
+    /// as the variants it replaced had, and `not_found` otherwise.
+    #[error("{entity} not found with id: {id}")]
+    NotFound {
+        /// Capitalized type name, as it should read in the message
+        entity: &'static str,
+        id: i64,
+    },
+
+    /// Task not found in the database.
+    ///
+    /// Deprecated in favor of `NotFound { entity: "Task", .. }`; kept
+    /// until existing callers and clients matching `task_not_found` move.
     #[error("Task not found with id: {0}")]
     TaskNotFound(i64),
 
-    /// User not found in the database
+    /// User not found in the database.
+    ///
+    /// Deprecated in favor of `NotFound { entity: "User", .. }`.
     #[error("User not found with id: {0}")]
     UserNotFound(i64),
 
+    /// Comment not found in the database.
+    ///
+    /// No longer returned; repositories use `NotFound { entity: "Comment", .. }`.
+    #[deprecated(note = "use `AppError::NotFound { entity: \"Comment\", .. }`")]
+    #[error("Comment not found with id: {0}")]
+    CommentNotFound(i64),
+
+    /// Project not found in the database.
+    ///
+    /// No longer returned; repositories use `NotFound { entity: "Project", .. }`.
+    #[deprecated(note = "use `AppError::NotFound { entity: \"Project\", .. }`")]
+    #[error("Project not found with id: {0}")]
+    ProjectNotFound(i64),
+
+    /// Attachment not found in the database.
+    ///
+    /// No longer returned; repositories use `NotFound { entity: "Attachment", .. }`.
+    #[deprecated(note = "use `AppError::NotFound { entity: \"Attachment\", .. }`")]
+    #[error("Attachment not found with id: {0}")]
+    AttachmentNotFound(i64),
+
     /// Username already exists (during registration)
     #[error("Username already exists: {0}")]
     UsernameExists(String),
 
+    /// A unique value (e.g. an email) is already taken
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     /// Invalid credentials during login
     #[error("Invalid username or password")]
     InvalidCredentials,
@@ -39,10 +89,32 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// Several fields failed validation at once
+    #[error("Validation error: {0}")]
+    ValidationMany(ValidationErrors),
+
     /// Unauthorised access attempt
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
+    /// Authenticated, but not allowed to touch this resource (e.g. someone
+    /// else's task)
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// Too many requests; the client should wait this many seconds
+    #[error("Too many requests, retry after {0}s")]
+    RateLimited(u64),
+
+    /// A database call ran longer than the query timeout
+    #[error("Database query timed out after {}ms", .0.as_millis())]
+    Timeout(Duration),
+
+    /// Writes are blocked while the service is in read-only mode for
+    /// maintenance (see `db::read_only`)
+    #[error("Service is in read-only mode")]
+    ReadOnly,
+
     /// Generic internal server error
     #[error("Internal server error: {0}")]
     Internal(String),
@@ -55,19 +127,138 @@ pub enum AppError {
 /// We can write: AppResult<Task>
 pub type AppResult<T> = Result<T, AppError>;
 
+/// Every validation failure found in one input, as `(field, message)` pairs.
+///
+/// Collected so a form with several bad fields can report them all at
+/// once. Serializes as an object keyed by field, e.g.
+/// `{"title": "Title cannot be empty"}`; a field with more than one
+/// failure gets its messages joined with "; ".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<(&'static str, String)>);
+
+impl ValidationErrors {
+    /// An empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a failure for `field`.
+    pub fn add(&mut self, field: &'static str, message: impl Into<String>) {
+        self.0.push((field, message.into()));
+    }
+
+    /// True if nothing has failed.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The failures in the order they were found.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.0
+            .iter()
+            .map(|(field, message)| (*field, message.as_str()))
+    }
+
+    /// `Ok(())` if nothing failed, otherwise `Err(self)`.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.iter().map(|(_, message)| message).collect();
+        f.write_str(&messages.join("; "))
+    }
+}
+
+impl Serialize for ValidationErrors {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Keep first-seen field order, merging repeats of a field
+        let mut fields: Vec<(&str, Vec<&str>)> = Vec::new();
+        for (field, message) in self.iter() {
+            match fields.iter_mut().find(|(seen, _)| *seen == field) {
+                Some((_, messages)) => messages.push(message),
+                None => fields.push((field, vec![message])),
+            }
+        }
+
+        let mut map = serializer.serialize_map(Some(fields.len()))?;
+        for (field, messages) in fields {
+            map.serialize_entry(field, &messages.join("; "))?;
+        }
+        map.end()
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        AppError::ValidationMany(errors)
+    }
+}
+
+/// JSON body returned to API clients for every error.
+///
+/// `code` is stable and machine-readable, so frontends can switch on it
+/// instead of matching the human-readable `message`. `fields` is only
+/// present for `AppError::ValidationMany`, mapping each bad field to what's
+/// wrong with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorResponse {
+    #[cfg_attr(feature = "openapi", schema(value_type = String, example = "task_not_found"))]
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub fields: Option<ValidationErrors>,
+}
+
+impl ErrorResponse {
+    /// Build a response body from a code and message.
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            fields: None,
+        }
+    }
+}
+
+// Still matches the deprecated not-found variants until they're removed
+#[allow(deprecated)]
 impl AppError {
     /// Check if this error is a not-found error.
     ///
     /// Useful for determining HTTP status codes (404 vs 500)
     pub fn is_not_found(&self) -> bool {
-        matches!(self, AppError::TaskNotFound(_) | AppError::UserNotFound(_))
+        matches!(
+            self,
+            AppError::NotFound { .. }
+                | AppError::TaskNotFound(_)
+                | AppError::UserNotFound(_)
+                | AppError::CommentNotFound(_)
+                | AppError::ProjectNotFound(_)
+                | AppError::AttachmentNotFound(_)
+        )
+    }
+
+    /// Check if this error is a uniqueness conflict.
+    ///
+    /// Conflicts typically return 409 Conflict.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, AppError::Conflict(_) | AppError::UsernameExists(_))
     }
 
     /// Check if this error is a validation error.
     ///
     /// Validation errors typically return 400 Bad Request.
     pub fn is_validation(&self) -> bool {
-        matches!(self, AppError::Validation(_))
+        matches!(self, AppError::Validation(_) | AppError::ValidationMany(_))
     }
 
     /// Check if this error is an authentication error.
@@ -79,4 +270,366 @@ impl AppError {
             AppError::InvalidCredentials | AppError::Unauthorized(_)
         )
     }
+
+    /// Check if this error is the pool running out of connections, i.e.
+    /// `acquire_timeout` passing with every connection in use.
+    ///
+    /// These map to 503 with a `Retry-After`, since the server is only
+    /// overloaded for the moment.
+    pub fn is_pool_timeout(&self) -> bool {
+        matches!(self, AppError::Database(sqlx::Error::PoolTimedOut))
+    }
+
+    /// Seconds a client should wait before retrying, for errors that say
+    /// "not now" rather than "no": rate limiting and an exhausted pool.
+    ///
+    /// Sent to HTTP clients as the `Retry-After` header.
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            AppError::RateLimited(secs) => Some(*secs),
+            _ if self.is_pool_timeout() => Some(crate::constants::POOL_BUSY_RETRY_AFTER_SECS),
+            _ => None,
+        }
+    }
+
+    /// Check if retrying the failed operation might succeed.
+    ///
+    /// True only for transient database conditions: SQLite reporting the
+    /// database as busy or locked, or the pool timing out waiting for a
+    /// connection. Validation, not-found, and auth errors are never
+    /// retryable - retrying a validation error is always pointless, since
+    /// the same input will fail the same way. Neither is a query that ran
+    /// past the query timeout: it keeps running in the background (see
+    /// `db::with_timeout`), so a retry would only pile another slow
+    /// statement onto the pool.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AppError::Database(err) => is_transient_sqlx_error(err),
+            _ => false,
+        }
+    }
+
+    /// Stable machine-readable code for this error variant.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(sqlx::Error::PoolTimedOut) => "unavailable",
+            AppError::Database(_) => "database_error",
+            AppError::Migration(_) => "migration_error",
+            AppError::NotFound { entity, .. } => match *entity {
+                "Comment" => "comment_not_found",
+                "Project" => "project_not_found",
+                "Attachment" => "attachment_not_found",
+                _ => "not_found",
+            },
+            AppError::TaskNotFound(_) => "task_not_found",
+            AppError::UserNotFound(_) => "user_not_found",
+            AppError::CommentNotFound(_) => "comment_not_found",
+            AppError::ProjectNotFound(_) => "project_not_found",
+            AppError::AttachmentNotFound(_) => "attachment_not_found",
+            AppError::UsernameExists(_) => "username_exists",
+            AppError::Conflict(_) => "conflict",
+            AppError::InvalidCredentials => "invalid_credentials",
+            AppError::Validation(_) | AppError::ValidationMany(_) => "validation",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::RateLimited(_) => "rate_limited",
+            AppError::Timeout(_) => "timeout",
+            AppError::ReadOnly => "read_only",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    /// HTTP status code for this error.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::NotFound { .. }
+            | AppError::TaskNotFound(_)
+            | AppError::UserNotFound(_)
+            | AppError::CommentNotFound(_)
+            | AppError::ProjectNotFound(_)
+            | AppError::AttachmentNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Validation(_) | AppError::ValidationMany(_) => StatusCode::BAD_REQUEST,
+            AppError::InvalidCredentials | AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::UsernameExists(_) | AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            AppError::ReadOnly | AppError::Database(sqlx::Error::PoolTimedOut) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            AppError::Database(_) | AppError::Migration(_) | AppError::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    /// Convert this error into an HTTP status and JSON body.
+    ///
+    /// Server-side errors get a generic message so database details never
+    /// reach clients; log the original error before calling this. Read-only
+    /// mode and an exhausted pool say so instead, since clients need to
+    /// know to come back later.
+    pub fn to_response(&self) -> (StatusCode, ErrorResponse) {
+        let status = self.status_code();
+        let message = if matches!(self, AppError::ReadOnly) {
+            self.to_string()
+        } else if self.is_pool_timeout() {
+            "Service is busy, please retry shortly".to_string()
+        } else if status.is_server_error() {
+            "Internal server error".to_string()
+        } else {
+            self.to_string()
+        };
+
+        let mut body = ErrorResponse::new(self.code(), message);
+        if let AppError::ValidationMany(errors) = self {
+            body.fields = Some(errors.clone());
+        }
+
+        (status, body)
+    }
+}
+
+/// Convert a sqlx error from an insert or update, mapping unique-constraint
+/// violations to `AppError::Conflict`.
+///
+/// SQLite reports these as SQLITE_CONSTRAINT_UNIQUE (2067) or
+/// SQLITE_CONSTRAINT_PRIMARYKEY (1555) with a message like
+/// `UNIQUE constraint failed: users.email`; the conflict message names the
+/// column without exposing the table.
+pub(crate) fn map_unique_violation(err: sqlx::Error) -> AppError {
+    if let sqlx::Error::Database(db_err) = &err {
+        if matches!(db_err.code().as_deref(), Some("2067") | Some("1555")) {
+            let column = db_err
+                .message()
+                .rsplit('.')
+                .next()
+                .unwrap_or("value")
+                .to_string();
+            return AppError::Conflict(format!("{} already exists", column));
+        }
+    }
+
+    AppError::Database(err)
+}
+
+/// SQLITE_BUSY and SQLITE_LOCKED primary result codes.
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+
+/// Whether a sqlx error reflects a temporary condition worth retrying.
+fn is_transient_sqlx_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db_err) => {
+            // SQLite reports extended codes (e.g. 517 = BUSY_SNAPSHOT);
+            // the low byte is the primary code
+            let primary = db_err
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .map(|code| code & 0xff);
+            matches!(primary, Some(SQLITE_BUSY) | Some(SQLITE_LOCKED))
+                || db_err.message().contains("database is locked")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fake_db_error as db_error;
+
+    #[test]
+    fn test_is_retryable_transient_errors() {
+        assert!(AppError::Database(sqlx::Error::PoolTimedOut).is_retryable());
+        assert!(db_error("5", "database is locked").is_retryable());
+        assert!(db_error("517", "database is busy").is_retryable());
+        assert!(db_error("6", "database table is locked").is_retryable());
+    }
+
+    #[test]
+    fn test_is_retryable_permanent_errors() {
+        assert!(!db_error("2067", "UNIQUE constraint failed").is_retryable());
+        assert!(!AppError::Database(sqlx::Error::RowNotFound).is_retryable());
+        assert!(!AppError::Validation("bad".into()).is_retryable());
+        assert!(!AppError::TaskNotFound(1).is_retryable());
+        assert!(!AppError::Unauthorized("no".into()).is_retryable());
+        assert!(!AppError::InvalidCredentials.is_retryable());
+        assert!(!AppError::Timeout(Duration::from_secs(1)).is_retryable());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_to_response_codes_and_statuses() {
+        let cases = [
+            (
+                AppError::Database(sqlx::Error::RowNotFound),
+                "database_error",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                AppError::Database(sqlx::Error::PoolTimedOut),
+                "unavailable",
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            (
+                AppError::Migration(sqlx::migrate::MigrateError::VersionMissing(1)),
+                "migration_error",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                AppError::NotFound {
+                    entity: "Tag",
+                    id: 1,
+                },
+                "not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                AppError::NotFound {
+                    entity: "Comment",
+                    id: 1,
+                },
+                "comment_not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                AppError::TaskNotFound(1),
+                "task_not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                AppError::UserNotFound(1),
+                "user_not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                AppError::CommentNotFound(1),
+                "comment_not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                AppError::ProjectNotFound(1),
+                "project_not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                AppError::AttachmentNotFound(1),
+                "attachment_not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                AppError::UsernameExists("alice".into()),
+                "username_exists",
+                StatusCode::CONFLICT,
+            ),
+            (
+                AppError::Conflict("email already exists".into()),
+                "conflict",
+                StatusCode::CONFLICT,
+            ),
+            (
+                AppError::InvalidCredentials,
+                "invalid_credentials",
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                AppError::Validation("bad".into()),
+                "validation",
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                AppError::Unauthorized("no".into()),
+                "unauthorized",
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                AppError::Forbidden("not yours".into()),
+                "forbidden",
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                AppError::RateLimited(5),
+                "rate_limited",
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            (
+                AppError::Timeout(Duration::from_secs(1)),
+                "timeout",
+                StatusCode::GATEWAY_TIMEOUT,
+            ),
+            (
+                AppError::ReadOnly,
+                "read_only",
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            (
+                AppError::Internal("boom".into()),
+                "internal",
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+
+        for (err, code, status) in cases {
+            let (got_status, body) = err.to_response();
+            assert_eq!(got_status, status, "{:?}", err);
+            assert_eq!(body.code, code, "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn test_validation_many_serializes_by_field() {
+        let mut errors = ValidationErrors::new();
+        errors.add("title", "Title cannot be empty");
+        errors.add("description", "Too long");
+        errors.add("title", "Title is odd");
+
+        let (status, body) = AppError::from(errors).to_response();
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            serde_json::json!({
+                "code": "validation",
+                "message": "Validation error: Title cannot be empty; Too long; Title is odd",
+                "fields": {
+                    "title": "Title cannot be empty; Title is odd",
+                    "description": "Too long"
+                }
+            })
+        );
+        // Other errors leave `fields` out entirely
+        let (_, body) = AppError::Validation("bad".into()).to_response();
+        assert!(serde_json::to_value(&body).unwrap().get("fields").is_none());
+    }
+
+    #[test]
+    fn test_to_response_hides_internal_details() {
+        let (_, body) = AppError::Internal("secret db path".into()).to_response();
+        assert_eq!(body.message, "Internal server error");
+
+        let (_, body) = AppError::TaskNotFound(42).to_response();
+        assert_eq!(body.message, "Task not found with id: 42");
+
+        let (_, body) = AppError::ReadOnly.to_response();
+        assert_eq!(body.message, "Service is in read-only mode");
+
+        let (_, body) = AppError::Database(sqlx::Error::PoolTimedOut).to_response();
+        assert_eq!(body.message, "Service is busy, please retry shortly");
+    }
+
+    #[test]
+    fn test_retry_after() {
+        assert_eq!(AppError::RateLimited(7).retry_after(), Some(7));
+        assert_eq!(
+            AppError::Database(sqlx::Error::PoolTimedOut).retry_after(),
+            Some(crate::constants::POOL_BUSY_RETRY_AFTER_SECS)
+        );
+        assert_eq!(
+            AppError::Database(sqlx::Error::RowNotFound).retry_after(),
+            None
+        );
+        assert_eq!(AppError::ReadOnly.retry_after(), None);
+    }
 }
@@ -0,0 +1,162 @@
+//! Exporting tasks to other formats.
+//!
+//! Currently just iCalendar (RFC 5545), so calendar apps can subscribe to a
+//! user's deadlines.
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{Task, TaskStatus};
+
+/// Maximum line length in octets before folding (RFC 5545 section 3.1).
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Render tasks as an iCalendar document with one `VTODO` per task.
+///
+/// Tasks without a due date are skipped, since they have nothing to show on
+/// a calendar. Lines end in CRLF and are folded at 75 octets as the spec
+/// requires.
+pub fn to_ical(tasks: &[Task]) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//rust-task-manager//Tasks//EN");
+
+    for task in tasks {
+        let Some(due) = task.due_date else {
+            continue;
+        };
+
+        push_line(&mut out, "BEGIN:VTODO");
+        push_line(&mut out, &format!("UID:task-{}@rust-task-manager", task.id));
+        push_line(
+            &mut out,
+            &format!("DTSTAMP:{}", format_utc(task.updated_at)),
+        );
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&task.title)));
+        if !task.description.is_empty() {
+            push_line(
+                &mut out,
+                &format!("DESCRIPTION:{}", escape_text(&task.description)),
+            );
+        }
+        push_line(&mut out, &format!("DUE:{}", format_utc(due)));
+        push_line(&mut out, &format!("STATUS:{}", ical_status(&task.status)));
+        push_line(&mut out, "END:VTODO");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// iCalendar STATUS value for a task status.
+fn ical_status(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "NEEDS-ACTION",
+        TaskStatus::InProgress => "IN-PROCESS",
+        TaskStatus::Done => "COMPLETED",
+    }
+}
+
+/// Format a timestamp in iCalendar's UTC form, e.g. `20240615T120000Z`.
+fn format_utc(value: DateTime<Utc>) -> String {
+    value.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value: backslashes, semicolons, commas, and newlines.
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append a content line, folding it at 75 octets and ending it with CRLF.
+///
+/// Continuation lines start with a single space, which counts toward their
+/// length. Folds never split a multi-byte UTF-8 character.
+fn push_line(out: &mut String, line: &str) {
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            line_octets = 1;
+        }
+        out.push(c);
+        line_octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskPriority;
+    use chrono::TimeZone;
+
+    fn task(id: i64, title: &str, due_date: Option<DateTime<Utc>>) -> Task {
+        let stamp = Utc.with_ymd_and_hms(2024, 6, 1, 9, 0, 0).unwrap();
+        Task {
+            id,
+            title: title.to_string(),
+            description: "Bring snacks; and slides, please".to_string(),
+            status: TaskStatus::InProgress,
+            priority: TaskPriority::High,
+            due_date,
+            user_id: 1,
+            recurrence: None,
+            estimated_minutes: None,
+            actual_minutes: None,
+            project_id: None,
+            parent_id: None,
+            position: 0,
+            version: 1,
+            created_at: stamp,
+            updated_at: stamp,
+        }
+    }
+
+    #[test]
+    fn test_to_ical_emits_vtodo_for_due_tasks() {
+        let due = Utc.with_ymd_and_hms(2024, 6, 15, 12, 30, 0).unwrap();
+        let ical = to_ical(&[task(1, "Team meeting", Some(due)), task(2, "Someday", None)]);
+
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ical.matches("BEGIN:VTODO").count(), 1);
+
+        let lines: Vec<&str> = ical.split("\r\n").collect();
+        let start = lines.iter().position(|l| *l == "BEGIN:VTODO").unwrap();
+        let end = lines.iter().position(|l| *l == "END:VTODO").unwrap();
+        let vtodo = &lines[start..=end];
+
+        assert!(vtodo.contains(&"UID:task-1@rust-task-manager"));
+        assert!(vtodo.contains(&"SUMMARY:Team meeting"));
+        assert!(vtodo.contains(&"DESCRIPTION:Bring snacks\\; and slides\\, please"));
+        assert!(vtodo.contains(&"DUE:20240615T123000Z"));
+        assert!(vtodo.contains(&"STATUS:IN-PROCESS"));
+        assert!(!ical.contains("Someday"));
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let due = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let title = "é".repeat(100);
+        let ical = to_ical(&[task(1, &title, Some(due))]);
+
+        for line in ical.split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS, "line too long: {:?}", line);
+        }
+
+        // Unfolding (dropping CRLF + space) restores the original line
+        let unfolded = ical.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("SUMMARY:{}", title)));
+    }
+}
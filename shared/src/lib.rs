@@ -5,21 +5,28 @@
 //!
 //! # Modules
 //!
+//! - `auth`: Token issuing and verification
+//! - `config`: Environment-driven runtime configuration
 //! - `models`: Data models (Task, User, enums)
 //! - `db`: Database connection and repository layer
 //! - `error`: Application error types
+//! - `export`: Rendering tasks for other tools (iCalendar)
+//! - `logging`: Pretty or JSON log output for both services
+//! - `notify`: Pluggable notifications, e.g. due-soon reminders
+//! - `proto`: Generated gRPC types and model conversions
+//! - `search`: Highlighted excerpts for search results
 //!
 //! # Example
 //!
 //! ```no_run
-//! use shared::db::{create_pool, run_migrations, TaskRepository};
+//! use shared::db::{create_pool, run_migrations, DatabaseUrl, TaskRepository};
 //! use shared::models::{CreateTask, TaskStatus, TaskPriority};
 //! use shared::error::AppResult;
 //!
 //! #[tokio::main]
 //! async fn main() -> AppResult<()> {
 //!     // Create database connection pool
-//!     let pool = create_pool("sqlite:tasks.db").await?;
+//!     let pool = create_pool(&DatabaseUrl::parse("sqlite:tasks.db")?).await?;
 //!     
 //!     // Run migrations
 //!     run_migrations(&pool).await?;
@@ -32,6 +39,11 @@
 //!         priority: TaskPriority::High,
 //!         due_date: None,
 //!         user_id: 1,
+//!         recurrence: None,
+//!         estimated_minutes: None,
+//!         actual_minutes: None,
+//!         project_id: None,
+//!         parent_id: None,
 //!     };
 //!     
 //!     let task = TaskRepository::create(&pool, task_data).await?;
@@ -42,20 +54,34 @@
 //! ```
 
 // Declare modules
+pub mod auth;
+pub mod config;
 pub mod db;
 pub mod error;
+pub mod export;
+pub mod logging;
 pub mod models;
+pub mod notify;
+pub mod proto;
+pub mod sanitize;
+pub mod search;
 
 // Re-export commonly used types for convenience
 pub use chrono::{DateTime, Utc};
 pub use uuid::Uuid;
 
 // Re-export key types from submodules
-pub use db::{create_pool, run_migrations, DbPool, TaskRepository};
-pub use error::{AppError, AppResult};
+pub use config::Config;
+pub use db::{
+    create_pool, create_pool_with_config, run_migrations, AttachmentRepository, CommentRepository,
+    DatabaseUrl, DbPool, PoolConfig, ProjectRepository, SqlxTaskRepository, TaskFilter,
+    TaskRepository, TaskSort, TaskStore, UserRepository,
+};
+pub use error::{AppError, AppResult, ErrorResponse, ValidationErrors};
 pub use models::{
-    CreateTask, CreateUser, Task, TaskPriority, TaskStatus, UpdateTask, UpdateUser, User,
-    UserResponse,
+    Attachment, AuditAction, AuditEntry, Comment, CreateAttachment, CreateProject, CreateTask,
+    CreateUser, LoginRequest, LoginResponse, Project, RecurrenceRule, Task, TaskPriority,
+    TaskStats, TaskStatus, TaskSummary, UpdateProject, UpdateTask, UpdateUser, User, UserResponse,
 };
 
 /// Application version information.
@@ -81,6 +107,15 @@ pub mod constants {
     /// Maximum description length for tasks.
     pub const MAX_DESCRIPTION_LENGTH: usize = 2000;
 
+    /// Maximum attachment filename length.
+    pub const MAX_FILENAME_LENGTH: usize = 255;
+
+    /// Maximum project name length.
+    pub const MAX_PROJECT_NAME_LENGTH: usize = 100;
+
+    /// Maximum tag length, after normalization.
+    pub const MAX_TAG_LENGTH: usize = 50;
+
     /// Maximum username length.
     pub const MAX_USERNAME_LENGTH: usize = 50;
 
@@ -89,6 +124,58 @@ pub mod constants {
 
     /// Minimum password length.
     pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+    /// Token secret used when `JWT_SECRET` is unset and
+    /// `ALLOW_DEV_JWT_SECRET=true`. Public, so only fit for local development.
+    pub const DEV_JWT_SECRET: &str = "dev-secret-change-me";
+
+    /// How long issued access tokens stay valid, in hours.
+    pub const TOKEN_TTL_HOURS: i64 = 24;
+
+    /// Wrong passwords allowed before an account is locked.
+    pub const MAX_FAILED_LOGINS: u32 = 5;
+
+    /// How long a locked account refuses logins, in minutes.
+    pub const LOCKOUT_MINUTES: u32 = 15;
+
+    /// How long an email verification token stays valid, in hours.
+    pub const EMAIL_VERIFICATION_TTL_HOURS: i64 = 48;
+
+    /// Default cap on request bodies for the web API, in bytes. Fits a
+    /// task with the longest title and description many times over.
+    pub const MAX_BODY_BYTES: u64 = 64 * 1024;
+
+    /// `Retry-After` sent when every database connection is busy, in
+    /// seconds. Queries are short, so connections free up quickly.
+    pub const POOL_BUSY_RETRY_AFTER_SECS: u64 = 1;
+
+    /// Longest shutdown waits for checked-out database connections to be
+    /// returned before giving up on closing the pool, in seconds.
+    pub const POOL_CLOSE_TIMEOUT_SECS: u64 = 10;
+
+    /// Default time limit for one repository call, in seconds.
+    pub const QUERY_TIMEOUT_SECS: u64 = 30;
+
+    /// Default time limit for one webhook delivery attempt, in seconds.
+    pub const WEBHOOK_TIMEOUT_SECS: u64 = 5;
+
+    /// Default per-client request budget for the web API, per minute.
+    pub const RATE_LIMIT_PER_MINUTE: u32 = 120;
+
+    /// Maximum number of rows returned by a single page of results.
+    pub const MAX_PAGE_SIZE: i64 = 100;
+
+    /// Furthest back `TaskRepository::recent_activity` looks, in days.
+    pub const MAX_ACTIVITY_DAYS: i64 = 365;
+
+    /// Most tasks accepted by one batch create request.
+    pub const MAX_BATCH_SIZE: usize = 100;
+
+    /// Page size used when a listing doesn't ask for one.
+    pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+    /// Characters of context kept on each side of a search match.
+    pub const SNIPPET_RADIUS: usize = 40;
 }
 
 #[cfg(test)]
@@ -0,0 +1,149 @@
+//! Log output setup shared by the web and gRPC services.
+//!
+//! Both services log through `tracing`; this decides how those events are
+//! written to stdout. Human-readable lines suit a terminal, while log
+//! aggregators want one JSON object per line.
+
+use std::str::FromStr;
+
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
+
+/// How log lines are written (`LOG_FORMAT`).
+///
+/// Debug builds default to `Pretty`, release builds to `Json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One human-readable line per event
+    Pretty,
+    /// One JSON object per line, for log aggregation
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            Self::Pretty
+        } else {
+            Self::Json
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "unknown log format {:?} (expected pretty or json)",
+                other
+            )),
+        }
+    }
+}
+
+/// Install the global `tracing` subscriber, writing to stdout.
+///
+/// Levels are filtered by `RUST_LOG` (e.g. `RUST_LOG=debug` to see the
+/// repository spans), falling back to `default_filter` when it's unset or
+/// invalid.
+///
+/// # Panics
+/// If a global subscriber is already installed; call this once, at startup.
+pub fn init_logging(format: LogFormat, default_filter: &str) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+    subscriber(format, filter, std::io::stdout).init();
+}
+
+/// Build a subscriber that writes `format` lines to `writer`.
+fn subscriber<W>(
+    format: LogFormat,
+    filter: EnvFilter,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match format {
+        LogFormat::Pretty => Box::new(
+            registry.with(
+                tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_level(true)
+                    .with_writer(writer),
+            ),
+        ),
+        LogFormat::Json => Box::new(
+            registry.with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_writer(writer),
+            ),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tracing::{debug, info, info_span};
+
+    /// Collects everything written to it, for inspecting log output
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_format_writes_parseable_lines() {
+        let out = Captured::default();
+        let writer = out.clone();
+        let subscriber = subscriber(LogFormat::Json, EnvFilter::new("info"), move || {
+            writer.clone()
+        });
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = info_span!("request", user_id = 7).entered();
+            info!(task_id = 42, "Created task");
+            debug!("Filtered out by the info level");
+        });
+
+        let out = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1, "{}", out);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["fields"]["message"], "Created task");
+        assert_eq!(lines[0]["fields"]["task_id"], 42);
+        assert_eq!(lines[0]["span"]["user_id"], 7);
+    }
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!("json".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert_eq!("Pretty".parse::<LogFormat>(), Ok(LogFormat::Pretty));
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
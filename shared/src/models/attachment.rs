@@ -0,0 +1,86 @@
+//! Attachment model: metadata for a file attached to a task.
+//!
+//! Only the metadata lives in the database; the file's bytes are kept in
+//! object storage under `storage_key`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::constants::MAX_FILENAME_LENGTH;
+use crate::error::{AppError, AppResult};
+
+/// A file attached to a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Attachment {
+    /// Unique identifier for the attachment (database primary key)
+    pub id: i64,
+
+    /// ID of the task the file is attached to
+    pub task_id: i64,
+
+    /// Original file name, with no directory part
+    pub filename: String,
+
+    /// MIME type, e.g. `image/png`
+    pub content_type: String,
+
+    /// File size in bytes
+    pub size_bytes: i64,
+
+    /// Key of the file's bytes in object storage
+    pub storage_key: String,
+
+    /// Timestamp when the file was attached
+    pub created_at: DateTime<Utc>,
+}
+
+/// Data structure for attaching a file to a task.
+///
+/// The storage key is derived from these when the attachment is added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAttachment {
+    pub task_id: i64,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+}
+
+impl CreateAttachment {
+    /// Check the filename, content type, and size.
+    ///
+    /// The filename ends up in the storage key, so anything that could
+    /// climb out of the task's directory (`/`, `\`, `..`) is rejected.
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the filename is blank, too long, or
+    ///   has a path separator, the content type is blank, or the size is
+    ///   negative
+    pub fn validate(&self) -> AppResult<()> {
+        let filename = self.filename.trim();
+        if filename.is_empty() {
+            return Err(AppError::Validation("Filename cannot be empty".to_string()));
+        }
+        if self.filename.chars().count() > MAX_FILENAME_LENGTH {
+            return Err(AppError::Validation(format!(
+                "Filename cannot exceed {} characters",
+                MAX_FILENAME_LENGTH
+            )));
+        }
+        if self.filename.contains(['/', '\\', '\0']) || filename == "." || filename == ".." {
+            return Err(AppError::Validation(format!(
+                "Filename cannot contain path separators: {:?}",
+                self.filename
+            )));
+        }
+        if self.content_type.trim().is_empty() {
+            return Err(AppError::Validation(
+                "Content type cannot be empty".to_string(),
+            ));
+        }
+        if self.size_bytes < 0 {
+            return Err(AppError::Validation("Size cannot be negative".to_string()));
+        }
+        Ok(())
+    }
+}
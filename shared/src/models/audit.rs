@@ -0,0 +1,61 @@
+//! Audit trail model for task mutations.
+//!
+//! Every create, update, and delete of a task records an `AuditEntry` in
+//! the same transaction as the change itself, attributed to the user who
+//! made it. That covers `TaskRepository::create`, `create_if_absent`,
+//! `import_json`, `update`, and `delete`.
+//!
+//! The other `TaskRepository` mutators are not audited: `reassign`,
+//! `duplicate`, `bulk_insert`, `mark_done`, `mark_in_progress`, `log_time`,
+//! `complete_and_reschedule`, `complete_with_subtasks`, `set_priorities`,
+//! and `reorder`. They are bookkeeping or bulk operations whose effect is
+//! visible on the task itself; route a change through `update` when it
+//! needs to show up in the trail.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+/// The kind of change an audit entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// The task was created
+    Create,
+    /// One or more fields changed
+    Update,
+    /// The task was deleted
+    Delete,
+}
+
+/// One change to a task.
+///
+/// `old_value` and `new_value` are JSON objects keyed by field name that
+/// hold only the fields that changed, so an update of the title stores
+/// `{"title": "Old"}` and `{"title": "New"}`. A create has no old value
+/// and a delete has no new value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct AuditEntry {
+    /// Unique identifier for the entry (database primary key)
+    pub id: i64,
+
+    /// ID of the task that changed (it may no longer exist)
+    pub task_id: i64,
+
+    /// ID of the user who made the change (not necessarily the task's owner)
+    pub user_id: i64,
+
+    /// What kind of change this was
+    pub action: AuditAction,
+
+    /// Changed fields before the change
+    pub old_value: Option<Value>,
+
+    /// Changed fields after the change
+    pub new_value: Option<Value>,
+
+    /// Timestamp when the change was made
+    pub at: DateTime<Utc>,
+}
@@ -0,0 +1,26 @@
+//! Comment model for discussion threads on tasks.
+//!
+//! Each comment belongs to one task and records who wrote it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A comment posted on a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Comment {
+    /// Unique identifier for the comment (database primary key)
+    pub id: i64,
+
+    /// ID of the task being discussed
+    pub task_id: i64,
+
+    /// ID of the user who wrote the comment
+    pub user_id: i64,
+
+    /// Comment text (never blank)
+    pub body: String,
+
+    /// Timestamp when the comment was posted
+    pub created_at: DateTime<Utc>,
+}
@@ -3,16 +3,34 @@
 //! This module contains the core domain models:
 //! - `Task`: represents a task with status, priority, and metadata
 //! - `User`: represents a user account
+//! - `Comment`: a message on a task's discussion thread
+//! - `Attachment`: metadata for a file attached to a task
+//! - `Project`: a named group of a user's tasks
+//! - Tags: free-form labels on tasks (see `normalize_tag`)
+//! - `AuditEntry`: one recorded change to a task
 //!
 //! These models map to database tables and are used throughout
 //! the application for type-safe data handling.
 
 // Declare submodules (tells Rust these files exist)
+pub mod attachment;
+pub mod audit;
+pub mod comment;
+pub mod project;
+pub mod tag;
 pub mod task;
 pub mod user;
 
 // Re-export types for easier imports
 // Instead of: use shared::models::task::Task;
 // Users can do: use shared::models::Task
-pub use task::{CreateTask, Task, TaskPriority, TaskStatus, UpdateTask};
-pub use user::{CreateUser, UpdateUser, User, UserResponse};
+pub use attachment::{Attachment, CreateAttachment};
+pub use audit::{AuditAction, AuditEntry};
+pub use comment::Comment;
+pub use project::{CreateProject, Project, UpdateProject};
+pub use tag::normalize_tag;
+pub use task::{
+    CreateTask, RecurrenceRule, Task, TaskPriority, TaskStats, TaskStatus, TaskSummary, UpdateTask,
+    UpdateTaskBuilder,
+};
+pub use user::{CreateUser, LoginRequest, LoginResponse, UpdateUser, User, UserResponse};
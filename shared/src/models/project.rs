@@ -0,0 +1,148 @@
+//! Project model for grouping a user's tasks.
+//!
+//! A task belongs to at most one project. Deleting a project leaves its
+//! tasks in place with no project.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::constants::MAX_PROJECT_NAME_LENGTH;
+use crate::error::{AppError, AppResult};
+
+/// Color given to projects created without one.
+pub const DEFAULT_PROJECT_COLOR: &str = "#808080";
+
+/// A named group of tasks owned by one user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Project {
+    /// Unique identifier for the project (database primary key)
+    pub id: i64,
+
+    /// ID of the user who owns the project
+    pub user_id: i64,
+
+    /// Display name (never blank)
+    pub name: String,
+
+    /// Display color as `#rrggbb`
+    pub color: String,
+
+    /// Timestamp when the project was created
+    pub created_at: DateTime<Utc>,
+
+    /// Timestamp when the project was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Data structure for creating a new project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateProject {
+    pub name: String,
+    /// Display color as `#rrggbb`; defaults to grey.
+    #[serde(default = "default_color")]
+    pub color: String,
+    /// Owner of the project. Optional in JSON because the web API takes it
+    /// from the authenticated user instead.
+    #[serde(default)]
+    pub user_id: i64,
+}
+
+fn default_color() -> String {
+    DEFAULT_PROJECT_COLOR.to_string()
+}
+
+impl CreateProject {
+    /// Check the name and color.
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the name is blank or too long, or the
+    ///   color isn't `#rrggbb`
+    pub fn validate(&self) -> AppResult<()> {
+        validate_name(&self.name)?;
+        validate_color(&self.color)
+    }
+}
+
+/// Data structure for updating an existing project.
+///
+/// All fields are optional - only provided fields will be updated.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpdateProject {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl UpdateProject {
+    /// Check whichever fields are set, with the same rules as [`CreateProject`].
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If a provided field is invalid
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(name) = &self.name {
+            validate_name(name)?;
+        }
+        if let Some(color) = &self.color {
+            validate_color(color)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_name(name: &str) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::Validation(
+            "Project name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_PROJECT_NAME_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Project name cannot exceed {} characters",
+            MAX_PROJECT_NAME_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+fn validate_color(color: &str) -> AppResult<()> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Color must look like #rrggbb, got {:?}",
+            color
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_project_validation() {
+        let project: CreateProject = serde_json::from_str(r#"{"name": "Home"}"#).unwrap();
+        assert_eq!(project.color, DEFAULT_PROJECT_COLOR);
+        assert!(project.validate().is_ok());
+
+        for (name, color) in [("  ", "#112233"), ("Home", "red"), ("Home", "#12345g")] {
+            let project = CreateProject {
+                name: name.to_string(),
+                color: color.to_string(),
+                user_id: 1,
+            };
+            assert!(project.validate().unwrap_err().is_validation());
+        }
+
+        let rename = UpdateProject {
+            name: Some(String::new()),
+            color: None,
+        };
+        assert!(rename.validate().is_err());
+        assert!(UpdateProject::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,49 @@
+//! Tags: free-form labels on tasks.
+//!
+//! Tags are stored by name only, normalized with [`normalize_tag`] so that
+//! spelling variants like `"Needs Review"` and `" needs  review "` end up
+//! as the same tag.
+
+use crate::constants::MAX_TAG_LENGTH;
+use crate::error::{AppError, AppResult};
+
+/// Normalize a tag name: trim, collapse runs of whitespace to a single
+/// space, and lowercase.
+///
+/// # Errors
+/// * `AppError::Validation` - If the tag is blank or longer than
+///   `MAX_TAG_LENGTH` once normalized
+pub fn normalize_tag(tag: &str) -> AppResult<String> {
+    let normalized = tag
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    if normalized.is_empty() {
+        return Err(AppError::Validation("Tag cannot be empty".to_string()));
+    }
+    if normalized.chars().count() > MAX_TAG_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Tag cannot exceed {} characters",
+            MAX_TAG_LENGTH
+        )));
+    }
+
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("  Needs   Review ").unwrap(), "needs review");
+        assert_eq!(normalize_tag("bug").unwrap(), "bug");
+        assert!(normalize_tag(" \t").unwrap_err().is_validation());
+        assert!(normalize_tag(&"x".repeat(MAX_TAG_LENGTH + 1))
+            .unwrap_err()
+            .is_validation());
+    }
+}
@@ -3,18 +3,30 @@
 //! A task has a title, description, status, priority, optional due date,
 //! and belongs to a user.
 //!
-use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Months, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::sqlite::{Sqlite, SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef};
+use sqlx::{Decode, Encode, FromRow, Type};
+
+use crate::constants::{MAX_DESCRIPTION_LENGTH, MAX_TITLE_LENGTH};
+use crate::error::{AppError, AppResult, ValidationErrors};
 
 /// Represents the current status of a task.
 ///
 /// Task progress through states: Todo -> InProgress -> Done
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[sqlx(type_name = "TEXT", rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
     /// Task is not yet started
+    #[default]
     Todo,
     /// Task is currently being worked
     InProgress,
@@ -26,14 +38,16 @@ pub enum TaskStatus {
 ///
 /// Higher priority tasks should be worked on first
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
 )]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[sqlx(type_name = "TEXT", rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
 pub enum TaskPriority {
     /// Low priority - can be done later
     Low,
     /// Normal priority - default level
+    #[default]
     Medium,
     /// High priority - should be done soon
     High,
@@ -41,11 +55,215 @@ pub enum TaskPriority {
     Urgent,
 }
 
+impl TaskStatus {
+    /// The snake_case name used by serde, sqlx, and query params.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Done => "done",
+        }
+    }
+}
+
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TaskStatus {
+    type Err = AppError;
+
+    /// Parse the snake_case form, e.g. `"in_progress"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "todo" => Ok(TaskStatus::Todo),
+            "in_progress" => Ok(TaskStatus::InProgress),
+            "done" => Ok(TaskStatus::Done),
+            other => Err(AppError::Validation(format!(
+                "Unknown task status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl TaskPriority {
+    /// Every priority, lowest first - handy for UI dropdowns.
+    pub fn all() -> [TaskPriority; 4] {
+        [
+            TaskPriority::Low,
+            TaskPriority::Medium,
+            TaskPriority::High,
+            TaskPriority::Urgent,
+        ]
+    }
+
+    /// Whether this is the highest priority.
+    pub fn is_urgent(&self) -> bool {
+        matches!(self, TaskPriority::Urgent)
+    }
+
+    /// The next priority up, or `None` if already `Urgent`.
+    pub fn next_higher(&self) -> Option<TaskPriority> {
+        match self {
+            TaskPriority::Low => Some(TaskPriority::Medium),
+            TaskPriority::Medium => Some(TaskPriority::High),
+            TaskPriority::High => Some(TaskPriority::Urgent),
+            TaskPriority::Urgent => None,
+        }
+    }
+
+    /// The snake_case name used by serde, sqlx, and query params.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+            TaskPriority::Urgent => "urgent",
+        }
+    }
+}
+
+impl fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TaskPriority {
+    type Err = AppError;
+
+    /// Parse the snake_case form, e.g. `"urgent"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(TaskPriority::Low),
+            "medium" => Ok(TaskPriority::Medium),
+            "high" => Ok(TaskPriority::High),
+            "urgent" => Ok(TaskPriority::Urgent),
+            other => Err(AppError::Validation(format!(
+                "Unknown task priority: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// How often a recurring task repeats.
+///
+/// Stored (and serialized) as text: `"daily"`, `"weekly"`, `"monthly"`, or
+/// `"every_<n>_days"`, e.g. `"every_3_days"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum RecurrenceRule {
+    Daily,
+    Weekly,
+    /// Same day next month, clamped to the month's last day
+    Monthly,
+    /// Every `n` days; `n` is always at least 1
+    EveryNDays(u32),
+}
+
+impl RecurrenceRule {
+    /// The next occurrence after `from`.
+    pub fn next_after(&self, from: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            RecurrenceRule::Daily => from + Duration::days(1),
+            RecurrenceRule::Weekly => from + Duration::days(7),
+            RecurrenceRule::Monthly => from
+                .checked_add_months(Months::new(1))
+                .unwrap_or(from + Duration::days(30)),
+            RecurrenceRule::EveryNDays(n) => from + Duration::days(i64::from(*n)),
+        }
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecurrenceRule::Daily => f.write_str("daily"),
+            RecurrenceRule::Weekly => f.write_str("weekly"),
+            RecurrenceRule::Monthly => f.write_str("monthly"),
+            RecurrenceRule::EveryNDays(n) => write!(f, "every_{}_days", n),
+        }
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AppError::Validation(format!("Unknown recurrence rule: {}", s));
+
+        match s {
+            "daily" => Ok(RecurrenceRule::Daily),
+            "weekly" => Ok(RecurrenceRule::Weekly),
+            "monthly" => Ok(RecurrenceRule::Monthly),
+            other => {
+                let n: u32 = other
+                    .strip_prefix("every_")
+                    .and_then(|rest| rest.strip_suffix("_days"))
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(invalid)?;
+                if n == 0 {
+                    return Err(invalid());
+                }
+                Ok(RecurrenceRule::EveryNDays(n))
+            }
+        }
+    }
+}
+
+impl From<RecurrenceRule> for String {
+    fn from(rule: RecurrenceRule) -> Self {
+        rule.to_string()
+    }
+}
+
+impl TryFrom<String> for RecurrenceRule {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+// Store RecurrenceRule as TEXT using its Display/FromStr form.
+// (The derive used for TaskStatus only handles unit variants.)
+impl Type<Sqlite> for RecurrenceRule {
+    fn type_info() -> SqliteTypeInfo {
+        <String as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <String as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for RecurrenceRule {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> Result<IsNull, BoxDynError> {
+        <String as Encode<'q, Sqlite>>::encode(self.to_string(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for RecurrenceRule {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let text = <&str as Decode<'r, Sqlite>>::decode(value)?;
+        Ok(text.parse()?)
+    }
+}
+
 /// Represents a task in the system
 ///
 /// Tasks are the core entity of the application. Each task belongs to a user
 /// and has various properties like status, priority, and due date.
+///
+/// Queries are almost always scoped to one user, so the table's indexes
+/// lead with `user_id`: `(user_id, status)`, `(user_id, created_at)`, and
+/// `(user_id, position)`.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Task {
     /// Unique identifier for the task (database primary key)
     pub id: i64,
@@ -69,6 +287,30 @@ pub struct Task {
     /// ID of the user who owns this task
     pub user_id: i64,
 
+    /// How the task repeats, if at all
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, example = "weekly"))]
+    pub recurrence: Option<RecurrenceRule>,
+
+    /// Expected effort in minutes, if estimated
+    pub estimated_minutes: Option<i32>,
+
+    /// Effort logged so far in minutes; `None` until time is first logged
+    pub actual_minutes: Option<i32>,
+
+    /// Project the task is grouped under, if any
+    pub project_id: Option<i64>,
+
+    /// Task this is a subtask of, if any; never the task itself
+    pub parent_id: Option<i64>,
+
+    /// Manual sort order set by `TaskRepository::reorder`, lowest first;
+    /// 0 until the user's list is first reordered
+    pub position: i64,
+
+    /// Starts at 1 and goes up by one on every write; send it back in
+    /// `UpdateTask::version` to detect concurrent edits
+    pub version: i64,
+
     /// Timestamp when the task was created
     pub created_at: DateTime<Utc>,
 
@@ -76,39 +318,710 @@ pub struct Task {
     pub updated_at: DateTime<Utc>,
 }
 
+impl Task {
+    /// Whether the task is unfinished and its due date has passed.
+    ///
+    /// Tasks without a due date are never overdue.
+    pub fn is_overdue(&self) -> bool {
+        self.is_overdue_at(Utc::now())
+    }
+
+    /// Like [`Task::is_overdue`], but measured against `now`.
+    ///
+    /// A task due exactly at `now` is not yet overdue.
+    pub fn is_overdue_at(&self, now: DateTime<Utc>) -> bool {
+        match self.due_date {
+            Some(due) => due < now && self.status != TaskStatus::Done,
+            None => false,
+        }
+    }
+
+    /// Whole days until the due date; negative once it has passed.
+    ///
+    /// Returns `None` if the task has no due date.
+    pub fn days_until_due(&self) -> Option<i64> {
+        self.days_until_due_at(Utc::now())
+    }
+
+    /// Like [`Task::days_until_due`], but measured against `now`.
+    ///
+    /// Rounds down, so a task due in 23 hours is `0` days away and one
+    /// that was due an hour ago is `-1`.
+    pub fn days_until_due_at(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.due_date
+            .map(|due| (due - now).num_seconds().div_euclid(86_400))
+    }
+
+    /// Logged minus estimated minutes: positive means over the estimate.
+    ///
+    /// Returns `None` unless the task has both an estimate and logged time.
+    pub fn time_variance(&self) -> Option<i32> {
+        Some(self.actual_minutes? - self.estimated_minutes?)
+    }
+}
+
 /// Data structure for creating a new task.
 ///
 /// This omits fields that are auto-generated (id, timestamps).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CreateTask {
     pub title: String,
     pub description: String,
     pub status: TaskStatus,
     pub priority: TaskPriority,
+    /// RFC 3339 text like `"2024-06-15T12:00:00Z"`, or Unix epoch seconds
+    #[serde(default, deserialize_with = "deserialize_due_date")]
     pub due_date: Option<DateTime<Utc>>,
+    /// Owner of the task. Optional in JSON because the web API takes it
+    /// from the authenticated user instead.
+    #[serde(default)]
     pub user_id: i64,
+    /// Optional repeat schedule; omit for a one-off task.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, example = "weekly"))]
+    pub recurrence: Option<RecurrenceRule>,
+    /// Optional effort estimate in minutes.
+    #[serde(default)]
+    pub estimated_minutes: Option<i32>,
+    /// Optional effort already spent in minutes; usually left out and
+    /// added to later with `TaskRepository::log_time`.
+    #[serde(default)]
+    pub actual_minutes: Option<i32>,
+    /// Optional project to put the task in.
+    #[serde(default)]
+    pub project_id: Option<i64>,
+    /// Optional task to make this a subtask of; must have the same owner.
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+}
+
+impl CreateTask {
+    /// Check the task's fields against the limits in [`crate::constants`].
+    ///
+    /// Stops at the first problem; use `validate_all` to report every one.
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If the title is blank or too long, or the
+    ///   description is too long
+    pub fn validate(&self) -> AppResult<()> {
+        self.validate_all().map_err(|errors| {
+            let (_, message) = errors.iter().next().unwrap_or(("", "Invalid task"));
+            AppError::Validation(message.to_string())
+        })
+    }
+
+    /// Check every field, collecting all failures instead of the first.
+    ///
+    /// Past due dates pass here, so restoring an export or copying an
+    /// overdue task still works; `validate_new` adds that rule for tasks a
+    /// user is creating.
+    ///
+    /// Convert the error with `AppError::from` (or `?`) to get an
+    /// `AppError::ValidationMany` whose response lists each bad field.
+    pub fn validate_all(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if self.title.trim().is_empty() {
+            errors.add("title", "Title cannot be empty");
+        } else if self.title.chars().count() > MAX_TITLE_LENGTH {
+            errors.add(
+                "title",
+                format!("Title cannot exceed {} characters", MAX_TITLE_LENGTH),
+            );
+        }
+        if self.description.chars().count() > MAX_DESCRIPTION_LENGTH {
+            errors.add(
+                "description",
+                format!(
+                    "Description cannot exceed {} characters",
+                    MAX_DESCRIPTION_LENGTH
+                ),
+            );
+        }
+        if self.estimated_minutes.is_some_and(|minutes| minutes < 0) {
+            errors.add("estimated_minutes", "Estimate cannot be negative");
+        }
+        if self.actual_minutes.is_some_and(|minutes| minutes < 0) {
+            errors.add("actual_minutes", "Logged time cannot be negative");
+        }
+
+        errors.into_result()
+    }
+
+    /// `validate_all`, plus the rule that a new task can't be due in the
+    /// past. The create endpoints call this; updates can still back-date a
+    /// task, see [`UpdateTask::due_date`].
+    pub fn validate_new(&self) -> Result<(), ValidationErrors> {
+        let mut errors = match self.validate_all() {
+            Ok(()) => ValidationErrors::new(),
+            Err(errors) => errors,
+        };
+        if self.due_date.is_some_and(|due| due < Utc::now()) {
+            errors.add("due_date", "Due date cannot be in the past");
+        }
+        errors.into_result()
+    }
 }
 
 /// Data structure for updating an existing task.
 ///
 /// All fields are optional - only provided fields will be updated.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UpdateTask {
     pub title: Option<String>,
     pub description: Option<String>,
     pub status: Option<TaskStatus>,
     pub priority: Option<TaskPriority>,
+    /// `None` leaves the due date alone, `Some(None)` clears it, and
+    /// `Some(Some(date))` sets it. In JSON, an absent field means "leave"
+    /// and an explicit `null` means "clear"; a date can be RFC 3339 text or
+    /// Unix epoch seconds, as in `CreateTask`.
+    ///
+    /// Unlike in `CreateTask`, a date in the past is allowed, e.g. to
+    /// record when something that's already overdue was really due.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_due_date_update"
+    )]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>, format = DateTime))]
+    pub due_date: Option<Option<DateTime<Utc>>>,
+    /// New effort estimate in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_minutes: Option<i32>,
+    /// Replacement for the logged minutes (e.g. to correct a mistake);
+    /// use `TaskRepository::log_time` to add to them instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual_minutes: Option<i32>,
+    /// Same tri-state as `due_date`: `Some(None)` (JSON `null`) takes the
+    /// task out of its project.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<i64>))]
+    pub project_id: Option<Option<i64>>,
+    /// Same tri-state again: `Some(None)` (JSON `null`) makes the task a
+    /// top-level one. The new parent must have the same owner and can't be
+    /// the task itself or one of its subtasks.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_some"
+    )]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<i64>))]
+    pub parent_id: Option<Option<i64>>,
+    /// The task's `version` as the client last read it. When set, the
+    /// update fails with `AppError::Conflict` if someone else has written
+    /// the task since; when `None`, the update applies regardless.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
+}
+
+/// Deserialize a present field (even `null`) as `Some`, so `#[serde(default)]`
+/// can tell a missing field apart from an explicit `null`.
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Deserialize an optional due date from RFC 3339 text or an integer of
+/// Unix epoch seconds, since frontends send either.
+fn deserialize_due_date<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<DueDate>::deserialize(deserializer)?.map(|due| due.0))
+}
+
+/// `deserialize_due_date` for `UpdateTask`'s tri-state field.
+fn deserialize_due_date_update<'de, D>(
+    deserializer: D,
+) -> Result<Option<Option<DateTime<Utc>>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_due_date(deserializer).map(Some)
+}
+
+/// A due date in either accepted JSON form.
+struct DueDate(DateTime<Utc>);
+
+impl<'de> Deserialize<'de> for DueDate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct DueDateVisitor;
+
+        impl serde::de::Visitor<'_> for DueDateVisitor {
+            type Value = DueDate;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an RFC 3339 timestamp or Unix epoch seconds")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<DueDate, E> {
+                value.parse().map(DueDate).map_err(|_| {
+                    E::custom(format!(
+                        "invalid due date {:?}: expected an RFC 3339 timestamp \
+                         like \"2024-06-15T12:00:00Z\" or Unix epoch seconds",
+                        value
+                    ))
+                })
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, secs: i64) -> Result<DueDate, E> {
+                Utc.timestamp_opt(secs, 0)
+                    .single()
+                    .map(DueDate)
+                    .ok_or_else(|| E::custom(format!("due date {} is out of range", secs)))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, secs: u64) -> Result<DueDate, E> {
+                let secs = i64::try_from(secs)
+                    .map_err(|_| E::custom(format!("due date {} is out of range", secs)))?;
+                self.visit_i64(secs)
+            }
+        }
+
+        deserializer.deserialize_any(DueDateVisitor)
+    }
+}
+
+impl UpdateTask {
+    /// Start building an update that changes nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shared::{TaskStatus, UpdateTask};
+    ///
+    /// let update = UpdateTask::builder().status(TaskStatus::Done).build();
+    /// assert_eq!(update.status, Some(TaskStatus::Done));
+    /// assert!(update.title.is_none());
+    /// ```
+    pub fn builder() -> UpdateTaskBuilder {
+        UpdateTaskBuilder::default()
+    }
+}
+
+/// Builder for [`UpdateTask`]; only the fields you set are changed.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateTaskBuilder {
+    update: UpdateTask,
+}
+
+impl UpdateTaskBuilder {
+    /// Set a new title.
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.update.title = Some(title.into());
+        self
+    }
+
+    /// Set a new description.
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.update.description = Some(description.into());
+        self
+    }
+
+    /// Set a new status.
+    #[must_use]
+    pub fn status(mut self, status: TaskStatus) -> Self {
+        self.update.status = Some(status);
+        self
+    }
+
+    /// Set a new priority.
+    #[must_use]
+    pub fn priority(mut self, priority: TaskPriority) -> Self {
+        self.update.priority = Some(priority);
+        self
+    }
+
+    /// Set a new due date.
+    #[must_use]
+    pub fn due_date(mut self, due_date: DateTime<Utc>) -> Self {
+        self.update.due_date = Some(Some(due_date));
+        self
+    }
+
+    /// Remove the task's due date.
+    #[must_use]
+    pub fn clear_due_date(mut self) -> Self {
+        self.update.due_date = Some(None);
+        self
+    }
+
+    /// Set a new effort estimate in minutes.
+    #[must_use]
+    pub fn estimated_minutes(mut self, minutes: i32) -> Self {
+        self.update.estimated_minutes = Some(minutes);
+        self
+    }
+
+    /// Overwrite the logged minutes.
+    #[must_use]
+    pub fn actual_minutes(mut self, minutes: i32) -> Self {
+        self.update.actual_minutes = Some(minutes);
+        self
+    }
+
+    /// Move the task into a project.
+    #[must_use]
+    pub fn project_id(mut self, project_id: i64) -> Self {
+        self.update.project_id = Some(Some(project_id));
+        self
+    }
+
+    /// Take the task out of its project.
+    #[must_use]
+    pub fn clear_project(mut self) -> Self {
+        self.update.project_id = Some(None);
+        self
+    }
+
+    /// Make the task a subtask of `parent_id`.
+    #[must_use]
+    pub fn parent_id(mut self, parent_id: i64) -> Self {
+        self.update.parent_id = Some(Some(parent_id));
+        self
+    }
+
+    /// Make the task a top-level task again.
+    #[must_use]
+    pub fn clear_parent(mut self) -> Self {
+        self.update.parent_id = Some(None);
+        self
+    }
+
+    /// Only apply the update if the task is still at `version`.
+    #[must_use]
+    pub fn version(mut self, version: i64) -> Self {
+        self.update.version = Some(version);
+        self
+    }
+
+    /// Finish building the update.
+    #[must_use]
+    pub fn build(self) -> UpdateTask {
+        self.update
+    }
+}
+
+/// The columns a task list needs, without the description and bookkeeping.
+///
+/// Descriptions can run to `MAX_DESCRIPTION_LENGTH` characters each, so
+/// list views load these with `TaskRepository::list_summaries` and only
+/// fetch the full [`Task`] for a detail view.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TaskSummary {
+    /// Unique identifier for the task
+    pub id: i64,
+
+    /// Task title
+    pub title: String,
+
+    /// Current status of the task
+    pub status: TaskStatus,
+
+    /// Priority level
+    pub priority: TaskPriority,
+
+    /// Optional due date for the task
     pub due_date: Option<DateTime<Utc>>,
 }
 
-impl Default for TaskStatus {
-    fn default() -> Self {
-        TaskStatus::Todo
+impl From<Task> for TaskSummary {
+    fn from(task: Task) -> Self {
+        Self {
+            id: task.id,
+            title: task.title,
+            status: task.status,
+            priority: task.priority,
+            due_date: task.due_date,
+        }
     }
 }
 
-impl Default for TaskPriority {
-    fn default() -> Self {
-        TaskPriority::Medium
+/// Aggregated task counts for a user's dashboard.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskStats {
+    /// Total number of tasks
+    pub total: i64,
+
+    /// Tasks with status `Todo`
+    pub todo: i64,
+
+    /// Tasks with status `InProgress`
+    pub in_progress: i64,
+
+    /// Tasks with status `Done`
+    pub done: i64,
+
+    /// Unfinished tasks whose due date has passed
+    pub overdue: i64,
+
+    /// Task count per priority (every priority is present, even if zero)
+    pub by_priority: BTreeMap<TaskPriority, i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::new_test_task;
+    use chrono::{Duration, TimeZone};
+
+    fn task_due(due_date: Option<DateTime<Utc>>, status: TaskStatus) -> Task {
+        let created = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        Task {
+            id: 1,
+            title: "Test".to_string(),
+            description: String::new(),
+            status,
+            priority: TaskPriority::Medium,
+            due_date,
+            user_id: 1,
+            recurrence: None,
+            estimated_minutes: None,
+            actual_minutes: None,
+            project_id: None,
+            parent_id: None,
+            position: 0,
+            version: 1,
+            created_at: created,
+            updated_at: created,
+        }
+    }
+
+    #[test]
+    fn test_is_overdue_at() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let past = now - Duration::seconds(1);
+
+        assert!(task_due(Some(past), TaskStatus::Todo).is_overdue_at(now));
+        assert!(!task_due(Some(now), TaskStatus::Todo).is_overdue_at(now));
+        assert!(!task_due(Some(past), TaskStatus::Done).is_overdue_at(now));
+        assert!(!task_due(None, TaskStatus::Todo).is_overdue_at(now));
+    }
+
+    #[test]
+    fn test_days_until_due_at() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let days = |due| task_due(due, TaskStatus::Todo).days_until_due_at(now);
+
+        assert_eq!(days(None), None);
+        assert_eq!(days(Some(now)), Some(0));
+        assert_eq!(days(Some(now + Duration::days(3))), Some(3));
+        assert_eq!(days(Some(now + Duration::hours(23))), Some(0));
+        assert_eq!(days(Some(now - Duration::hours(1))), Some(-1));
+        assert_eq!(days(Some(now - Duration::days(2))), Some(-2));
+    }
+
+    #[test]
+    fn test_create_task_validate() {
+        let task = |title: &str, description: &str| CreateTask {
+            description: description.to_string(),
+            ..new_test_task(1, title)
+        };
+
+        assert!(task("Ship it", "").validate().is_ok());
+        assert!(task("   ", "").validate().unwrap_err().is_validation());
+        assert!(task(&"x".repeat(MAX_TITLE_LENGTH + 1), "")
+            .validate()
+            .is_err());
+        assert!(task("ok", &"x".repeat(MAX_DESCRIPTION_LENGTH + 1))
+            .validate()
+            .is_err());
+
+        let due = |offset: Duration| CreateTask {
+            due_date: Some(Utc::now() + offset),
+            ..task("Ship it", "")
+        };
+        assert!(due(Duration::days(1)).validate_new().is_ok());
+        // Only a new task is held to a future due date
+        assert!(due(-Duration::days(1)).validate().is_ok());
+        let errors = due(-Duration::days(1)).validate_new().unwrap_err();
+        assert_eq!(
+            errors.iter().collect::<Vec<_>>(),
+            vec![("due_date", "Due date cannot be in the past")]
+        );
+    }
+
+    #[test]
+    fn test_create_task_validate_all_reports_every_field() {
+        let task = CreateTask {
+            description: "x".repeat(MAX_DESCRIPTION_LENGTH + 1),
+            ..new_test_task(1, "  ")
+        };
+
+        let errors = task.validate_all().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|(field, _)| field).collect();
+        assert_eq!(fields, vec!["title", "description"]);
+
+        // validate() still stops at the first
+        assert!(matches!(
+            task.validate(),
+            Err(AppError::Validation(message)) if message == "Title cannot be empty"
+        ));
+    }
+
+    #[test]
+    fn test_update_builder_sets_only_given_fields() {
+        let due = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let update = UpdateTask::builder()
+            .title("Renamed")
+            .priority(TaskPriority::Urgent)
+            .due_date(due)
+            .build();
+
+        assert_eq!(update.title.as_deref(), Some("Renamed"));
+        assert_eq!(update.priority, Some(TaskPriority::Urgent));
+        assert_eq!(update.due_date, Some(Some(due)));
+        assert!(update.description.is_none());
+        assert!(update.status.is_none());
+
+        assert_eq!(UpdateTask::builder().build(), UpdateTask::default());
+    }
+
+    #[test]
+    fn test_update_due_date_json_tri_state() {
+        let leave: UpdateTask = serde_json::from_str(r#"{"title": "x"}"#).unwrap();
+        assert_eq!(leave.due_date, None);
+
+        let clear: UpdateTask = serde_json::from_str(r#"{"due_date": null}"#).unwrap();
+        assert_eq!(clear.due_date, Some(None));
+
+        let set: UpdateTask =
+            serde_json::from_str(r#"{"due_date": "2024-06-15T12:00:00Z"}"#).unwrap();
+        assert_eq!(
+            set.due_date,
+            Some(Some(Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_due_date_accepts_rfc3339_or_epoch_seconds() {
+        let instant = Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap();
+        let create = |due: &str| {
+            serde_json::from_str::<CreateTask>(&format!(
+                r#"{{"title": "x", "description": "", "status": "todo", "priority": "low", "due_date": {}}}"#,
+                due
+            ))
+        };
+
+        for due in [
+            r#""2024-06-15T12:00:00Z""#,
+            r#""2024-06-15T14:00:00+02:00""#,
+            "1718452800",
+        ] {
+            assert_eq!(create(due).unwrap().due_date, Some(instant), "{}", due);
+        }
+        assert_eq!(create("null").unwrap().due_date, None);
+
+        let update: UpdateTask = serde_json::from_str(r#"{"due_date": 1718452800}"#).unwrap();
+        assert_eq!(update.due_date, Some(Some(instant)));
+
+        for due in [r#""next tuesday""#, r#""2024-13-45""#, "true"] {
+            assert!(create(due).is_err(), "{}", due);
+        }
+        let err = create(r#""next tuesday""#).unwrap_err();
+        assert!(err.to_string().contains("invalid due date"), "{}", err);
+    }
+
+    #[test]
+    fn test_recurrence_rule_round_trip() {
+        for s in ["daily", "weekly", "monthly", "every_3_days"] {
+            assert_eq!(s.parse::<RecurrenceRule>().unwrap().to_string(), s);
+        }
+        for s in ["yearly", "every_0_days", "every_x_days"] {
+            assert!(s.parse::<RecurrenceRule>().is_err(), "{}", s);
+        }
+
+        let json = serde_json::to_string(&RecurrenceRule::EveryNDays(3)).unwrap();
+        assert_eq!(json, "\"every_3_days\"");
+    }
+
+    #[test]
+    fn test_recurrence_next_after() {
+        let jan31 = Utc.with_ymd_and_hms(2024, 1, 31, 9, 0, 0).unwrap();
+
+        assert_eq!(
+            RecurrenceRule::Weekly.next_after(jan31),
+            Utc.with_ymd_and_hms(2024, 2, 7, 9, 0, 0).unwrap()
+        );
+        // Monthly clamps to the end of a shorter month
+        assert_eq!(
+            RecurrenceRule::Monthly.next_after(jan31),
+            Utc.with_ymd_and_hms(2024, 2, 29, 9, 0, 0).unwrap()
+        );
+        assert_eq!(
+            RecurrenceRule::EveryNDays(3).next_after(jan31),
+            Utc.with_ymd_and_hms(2024, 2, 3, 9, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_task_status_round_trip() {
+        for s in ["todo", "in_progress", "done"] {
+            assert_eq!(s.parse::<TaskStatus>().unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_task_priority_round_trip() {
+        for s in ["low", "medium", "high", "urgent"] {
+            assert_eq!(s.parse::<TaskPriority>().unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_priority_next_higher() {
+        use TaskPriority::*;
+        assert_eq!(Low.next_higher(), Some(Medium));
+        assert_eq!(Medium.next_higher(), Some(High));
+        assert_eq!(High.next_higher(), Some(Urgent));
+        assert_eq!(Urgent.next_higher(), None);
+
+        assert!(Urgent.is_urgent());
+        assert!(!High.is_urgent());
+    }
+
+    #[test]
+    fn test_priority_all_is_ascending() {
+        let all = TaskPriority::all();
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+        // Walking next_higher from the bottom visits the same sequence
+        let walked: Vec<_> =
+            std::iter::successors(Some(TaskPriority::Low), TaskPriority::next_higher).collect();
+        assert_eq!(walked, all);
+    }
+
+    #[test]
+    fn test_display_matches_serde() {
+        let json = serde_json::to_string(&TaskStatus::InProgress).unwrap();
+        assert_eq!(json, format!("\"{}\"", TaskStatus::InProgress));
+    }
+
+    #[test]
+    fn test_unknown_values_are_validation_errors() {
+        assert!("bogus".parse::<TaskStatus>().unwrap_err().is_validation());
+        assert!("InProgress"
+            .parse::<TaskStatus>()
+            .unwrap_err()
+            .is_validation());
+        assert!("critical"
+            .parse::<TaskPriority>()
+            .unwrap_err()
+            .is_validation());
     }
 }
@@ -3,9 +3,12 @@
 //! Users own tasks and authenticate to access the application
 
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use crate::error::{AppError, AppResult};
+
 /// Represents a user account in the system.
 ///
 /// Users can create and manage their own tasks. Passwords are stored
@@ -19,13 +22,28 @@ pub struct User {
     pub username: String,
 
     /// Hashed password (never store plain text passwords!)
-    /// This field should not be serialized in API responses
-    /// #[serde(skip_serializing)]
+    /// Skipped by serde in both directions so it can never leak into JSON;
+    /// it is only ever loaded from the database via `FromRow`.
+    #[serde(skip)]
     pub password_hash: String,
 
     /// Optional email address (unique if provided)
     pub email: Option<String>,
 
+    /// Whether the user has proven they own `email`; see
+    /// `UserRepository::verify_email`
+    pub email_verified: bool,
+
+    /// Wrong passwords since the last successful login or lockout
+    pub failed_attempts: i32,
+
+    /// Logins are refused until this time; `None` when not locked
+    pub locked_until: Option<DateTime<Utc>>,
+
+    /// IANA timezone name like `Europe/Paris`, used to work out the
+    /// user's local day; `None` means UTC
+    pub timezone: Option<String>,
+
     /// Timestamp when the user account was created
     pub created_at: DateTime<Utc>,
 
@@ -37,6 +55,7 @@ pub struct User {
 ///
 /// Used during registration - contains plain text password that will be hashed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CreateUser {
     /// Desired username (will be validated for uniqueness)
     pub username: String,
@@ -50,10 +69,27 @@ pub struct CreateUser {
     pub email: Option<String>,
 }
 
+/// Credentials posted to the login endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginRequest {
+    pub username: String,
+    /// Plain text password - never log this value!
+    pub password: String,
+}
+
+/// Successful login: a bearer token plus the user's public details.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginResponse {
+    pub token: String,
+    pub user: UserResponse,
+}
+
 /// Data structure for updating user account information.
 ///
-/// All fields are optional - only provided fields will be updated.
-/// Password changes are handled separately for security.
+/// Replaces every field, so send the current values of those that
+/// shouldn't change. Password changes are handled separately for security.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateUser {
     /// New username (must still be unique)
@@ -61,12 +97,41 @@ pub struct UpdateUser {
 
     /// New email address
     pub email: Option<String>,
+
+    /// IANA timezone name like `Europe/Paris`, or `None` for UTC
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl UpdateUser {
+    /// Check the timezone is a known IANA name.
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `timezone` isn't a known zone
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(timezone) = &self.timezone {
+            parse_timezone(timezone)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse an IANA timezone name like `America/New_York`.
+///
+/// # Errors
+/// * `AppError::Validation` - If `name` isn't a known zone
+pub fn parse_timezone(name: &str) -> AppResult<Tz> {
+    name.parse()
+        .map_err(|_| AppError::Validation(format!("Unknown timezone: {:?}", name)))
 }
 
 /// Sanitized user data safe for API responses.
 ///
 /// This type excludes sensitive information like password hashes.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// It also derives `FromRow`, so listings can select just these columns
+/// and never load the hash at all.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UserResponse {
     pub id: i64,
     pub username: String,
@@ -89,6 +154,17 @@ impl From<User> for UserResponse {
 }
 
 impl User {
+    /// The user's timezone, or UTC if they haven't set one.
+    ///
+    /// A stored name that no longer parses also falls back to UTC rather
+    /// than failing every date calculation for the user.
+    pub fn tz(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(Tz::UTC)
+    }
+
     /// Create a sanitized response from this user.
     ///
     /// Convenience method that strips sensitive information.
@@ -101,3 +177,30 @@ impl User {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_serialization_omits_password_hash() {
+        let user = User {
+            id: 1,
+            username: "alice".to_string(),
+            password_hash: "$argon2id$v=19$secret".to_string(),
+            email: Some("alice@example.com".to_string()),
+            email_verified: false,
+            failed_attempts: 0,
+            locked_until: None,
+            timezone: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let json = serde_json::to_value(&user).unwrap();
+
+        assert!(json.get("password_hash").is_none());
+        assert!(!json.to_string().contains("argon2"));
+        assert_eq!(json["username"], "alice");
+    }
+}
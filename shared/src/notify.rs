@@ -0,0 +1,506 @@
+//! Pluggable notifications about tasks.
+//!
+//! A [`Notifier`] is told about tasks that need a user's attention:
+//! [`LogNotifier`] just logs them, [`WebhookNotifier`] POSTs them to a
+//! configured URL, and other backends (email) implement the same trait.
+//! [`notify_due_tasks`] is one reminder pass for a user, and
+//! [`spawn_reminders`] runs a pass for every user in the background:
+//!
+//! ```no_run
+//! use shared::db::{create_pool, DatabaseUrl};
+//! use shared::notify::{spawn_reminders, LogNotifier, REMINDER_INTERVAL, REMINDER_WINDOW_HOURS};
+//!
+//! #[tokio::main]
+//! async fn main() -> shared::AppResult<()> {
+//!     let pool = create_pool(&DatabaseUrl::parse("sqlite:tasks.db")?).await?;
+//!     spawn_reminders(pool, LogNotifier, REMINDER_INTERVAL, REMINDER_WINDOW_HOURS);
+//!     # Ok(())
+//! }
+//! ```
+
+use std::future::Future;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::WebhookConfig;
+use crate::constants::MAX_PAGE_SIZE;
+use crate::db::{DbPool, TaskRepository, UserRepository};
+use crate::error::{AppError, AppResult};
+use crate::models::Task;
+
+/// Header carrying the payload's HMAC-SHA256, as `sha256=<hex>`.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Most delivery attempts per notification, including the first.
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first redelivery; it doubles for each one after that.
+pub const WEBHOOK_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// How often [`spawn_reminders`] runs a pass when the services start it.
+pub const REMINDER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How far ahead a reminder pass looks for due tasks, in hours.
+pub const REMINDER_WINDOW_HOURS: i64 = 24;
+
+/// A way of telling a task's owner about it.
+pub trait Notifier: Send + Sync {
+    /// Deliver a notification about `task` to its owner.
+    fn notify(&self, task: &Task) -> impl Future<Output = AppResult<()>> + Send;
+}
+
+/// Notifier that writes each notification to the log.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    async fn notify(&self, task: &Task) -> AppResult<()> {
+        info!(
+            task_id = task.id,
+            user_id = task.user_id,
+            due_date = ?task.due_date,
+            "Task due soon: {}",
+            task.title
+        );
+        Ok(())
+    }
+}
+
+/// JSON body POSTed by [`WebhookNotifier`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload<'a> {
+    /// The task's ID
+    pub task_id: i64,
+
+    /// The task's title
+    pub title: &'a str,
+
+    /// When the task is due, as RFC 3339
+    pub due_date: Option<DateTime<Utc>>,
+
+    /// The task's owner
+    pub user_id: i64,
+}
+
+/// Notifier that POSTs each notification as JSON to a webhook URL.
+///
+/// Failed deliveries (connection errors, timeouts, 5xx and 429 responses)
+/// are logged and retried up to [`WEBHOOK_MAX_ATTEMPTS`] times. Any other
+/// 4xx means the receiver rejected the payload itself, so sending it again
+/// wouldn't help and it fails at once. With a secret
+/// configured, each body is signed in the [`SIGNATURE_HEADER`] header so
+/// the receiver can check it came from us.
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    secret: Option<String>,
+}
+
+impl WebhookNotifier {
+    /// Build a notifier from the `WEBHOOK_*` settings.
+    ///
+    /// # Errors
+    /// * `AppError::Internal` - If the HTTP client can't be created
+    pub fn new(config: &WebhookConfig) -> AppResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| AppError::Internal(format!("Failed to build webhook client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            url: config.url.clone(),
+            secret: config.secret.clone(),
+        })
+    }
+
+    /// Send `body` once, failing on any non-2xx response.
+    async fn deliver(&self, body: &[u8]) -> Result<(), reqwest::Error> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, body));
+        }
+
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, task: &Task) -> AppResult<()> {
+        let body = serde_json::to_vec(&WebhookPayload {
+            task_id: task.id,
+            title: &task.title,
+            due_date: task.due_date,
+            user_id: task.user_id,
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to encode webhook payload: {}", e)))?;
+
+        let mut delay = WEBHOOK_RETRY_DELAY;
+        let mut attempt = 1;
+        loop {
+            match self.deliver(&body).await {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt < WEBHOOK_MAX_ATTEMPTS && is_transient(&err) => {
+                    warn!(
+                        task_id = task.id,
+                        "Webhook attempt {}/{} failed ({}), retrying in {:?}",
+                        attempt,
+                        WEBHOOK_MAX_ATTEMPTS,
+                        err,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(err) => {
+                    warn!(task_id = task.id, "Webhook delivery failed: {}", err);
+                    return Err(AppError::Internal(format!(
+                        "Webhook delivery failed after {} attempts: {}",
+                        attempt, err
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// Whether a failed delivery might succeed if sent again: anything but a
+/// 4xx, except 429 Too Many Requests.
+fn is_transient(err: &reqwest::Error) -> bool {
+    match err.status() {
+        Some(status) => {
+            !status.is_client_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        }
+        None => true,
+    }
+}
+
+/// `sha256=<hex>` HMAC of `body` under `secret`.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Notify `notifier` about each of a user's tasks due in the next `hours`.
+///
+/// Every call notifies about every matching task, so a task stays in the
+/// reminders until it's done or its due date passes. A notification that
+/// fails is logged, and the rest are still sent.
+///
+/// # Returns
+/// * `AppResult<usize>` - How many notifications were sent successfully
+///
+/// # Errors
+/// * `AppError::Validation` - If `hours` is negative
+/// * `AppError::Database` - If the query fails
+pub async fn notify_due_tasks<N: Notifier>(
+    pool: &DbPool,
+    user_id: i64,
+    hours: i64,
+    notifier: &N,
+) -> AppResult<usize> {
+    let tasks = TaskRepository::find_due_within(pool, user_id, hours).await?;
+    let mut sent = 0;
+    for task in &tasks {
+        match notifier.notify(task).await {
+            Ok(()) => sent += 1,
+            Err(err) => warn!(task_id = task.id, "Reminder not sent: {}", err),
+        }
+    }
+    Ok(sent)
+}
+
+/// [`notify_due_tasks`] for every user, a page of users at a time.
+///
+/// A user whose pass fails is logged and skipped.
+///
+/// # Returns
+/// * `AppResult<usize>` - How many notifications were sent successfully
+///
+/// # Errors
+/// * `AppError::Validation` - If `hours` is negative
+/// * `AppError::Database` - If listing users fails
+pub async fn notify_all_due_tasks<N: Notifier>(
+    pool: &DbPool,
+    hours: i64,
+    notifier: &N,
+) -> AppResult<usize> {
+    if hours < 0 {
+        return Err(AppError::Validation(
+            "hours must not be negative".to_string(),
+        ));
+    }
+
+    let mut sent = 0;
+    let mut offset = 0;
+    loop {
+        let users = UserRepository::list(pool, MAX_PAGE_SIZE, offset).await?;
+        for user in &users {
+            match notify_due_tasks(pool, user.id, hours, notifier).await {
+                Ok(count) => sent += count,
+                Err(err) => warn!(user_id = user.id, "Reminder pass failed: {}", err),
+            }
+        }
+        if (users.len() as i64) < MAX_PAGE_SIZE {
+            return Ok(sent);
+        }
+        offset += MAX_PAGE_SIZE;
+    }
+}
+
+/// Run [`notify_all_due_tasks`] every `interval` on the Tokio runtime,
+/// looking `hours` ahead. A failed pass is logged and the next one runs
+/// as usual.
+///
+/// Runs until the returned handle is aborted or the runtime shuts down.
+pub fn spawn_reminders<N: Notifier + 'static>(
+    pool: DbPool,
+    notifier: N,
+    interval: Duration,
+    hours: i64,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        loop {
+            ticks.tick().await;
+            match notify_all_due_tasks(&pool, hours, &notifier).await {
+                Ok(sent) => info!("Reminder pass sent {} notifications", sent),
+                Err(err) => warn!("Reminder pass failed: {}", err),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{create_test_pool, create_test_user, new_test_task};
+    use crate::models::CreateTask;
+    use chrono::Duration;
+    use std::sync::{Arc, Mutex};
+    use warp::Filter;
+
+    /// Records the IDs it's told about, except that it fails for tasks
+    /// titled "Broken".
+    #[derive(Default)]
+    struct RecordingNotifier(Mutex<Vec<i64>>);
+
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, task: &Task) -> AppResult<()> {
+            if task.title == "Broken" {
+                return Err(AppError::Internal("unreachable".to_string()));
+            }
+            self.0.lock().unwrap().push(task.id);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_notifications_dont_stop_the_pass() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let soon = Utc::now() + Duration::hours(1);
+        TaskRepository::create(
+            &pool,
+            CreateTask {
+                due_date: Some(soon),
+                ..new_test_task(alice, "Broken")
+            },
+        )
+        .await
+        .unwrap();
+        let rent = TaskRepository::create(
+            &pool,
+            CreateTask {
+                due_date: Some(soon),
+                ..new_test_task(alice, "Pay rent")
+            },
+        )
+        .await
+        .unwrap();
+        let dentist = TaskRepository::create(
+            &pool,
+            CreateTask {
+                due_date: Some(soon),
+                ..new_test_task(bob, "Dentist")
+            },
+        )
+        .await
+        .unwrap();
+
+        let notifier = RecordingNotifier::default();
+        let sent = notify_all_due_tasks(&pool, 24, &notifier).await.unwrap();
+
+        assert_eq!(sent, 2);
+        assert_eq!(*notifier.0.lock().unwrap(), vec![rent.id, dentist.id]);
+    }
+
+    #[tokio::test]
+    async fn test_notify_due_tasks_reaches_notifier() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let mut ids = Vec::new();
+        for hours in [2, 48] {
+            let task = TaskRepository::create(
+                &pool,
+                CreateTask {
+                    due_date: Some(Utc::now() + Duration::hours(hours)),
+                    ..new_test_task(user_id, &format!("Due in {}h", hours))
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+
+        let notifier = RecordingNotifier::default();
+        let sent = notify_due_tasks(&pool, user_id, 24, &notifier)
+            .await
+            .unwrap();
+
+        assert_eq!(sent, 1);
+        assert_eq!(*notifier.0.lock().unwrap(), vec![ids[0]]);
+        assert_eq!(
+            notify_due_tasks(&pool, user_id, 24, &LogNotifier)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    /// A request as seen by [`mock_server`]
+    #[derive(Debug, Clone)]
+    struct Received {
+        signature: Option<String>,
+        body: Vec<u8>,
+    }
+
+    /// Warp server on an ephemeral port whose `POST /hook` answers with
+    /// each status in `statuses` in turn (then 200), recording what it
+    /// was sent.
+    async fn mock_server(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let statuses = Arc::new(Mutex::new(statuses.into_iter()));
+
+        let log = received.clone();
+        let hook = warp::path!("hook")
+            .and(warp::post())
+            .and(warp::header::optional::<String>(SIGNATURE_HEADER))
+            .and(warp::body::bytes())
+            .map(
+                move |signature: Option<String>, body: warp::hyper::body::Bytes| {
+                    log.lock().unwrap().push(Received {
+                        signature,
+                        body: body.to_vec(),
+                    });
+                    let status = statuses.lock().unwrap().next().unwrap_or(200);
+                    warp::reply::with_status(
+                        warp::reply(),
+                        warp::http::StatusCode::from_u16(status).unwrap(),
+                    )
+                },
+            );
+        let (addr, server) = warp::serve(hook).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        (format!("http://{}/hook", addr), received)
+    }
+
+    fn due_task() -> Task {
+        let stamp = Utc::now();
+        Task {
+            id: 42,
+            title: "Pay rent".to_string(),
+            description: String::new(),
+            status: Default::default(),
+            priority: Default::default(),
+            due_date: Some("2026-03-01T09:00:00Z".parse().unwrap()),
+            user_id: 7,
+            recurrence: None,
+            estimated_minutes: None,
+            actual_minutes: None,
+            project_id: None,
+            parent_id: None,
+            position: 0,
+            version: 1,
+            created_at: stamp,
+            updated_at: stamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_posts_signed_json_and_retries() {
+        let (url, received) = mock_server(vec![500, 200]).await;
+        let notifier = WebhookNotifier::new(&WebhookConfig {
+            url,
+            timeout_secs: 5,
+            secret: Some("shh".to_string()),
+        })
+        .unwrap();
+
+        notifier.notify(&due_task()).await.unwrap();
+
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 2);
+        let last = &received[1];
+        let body: serde_json::Value = serde_json::from_slice(&last.body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "task_id": 42,
+                "title": "Pay rent",
+                "due_date": "2026-03-01T09:00:00Z",
+                "user_id": 7,
+            })
+        );
+        assert_eq!(last.signature, Some(sign("shh", &last.body)));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_gives_up_after_max_attempts() {
+        let (url, received) = mock_server(vec![503; WEBHOOK_MAX_ATTEMPTS as usize]).await;
+        let notifier = WebhookNotifier::new(&WebhookConfig {
+            url,
+            timeout_secs: 5,
+            secret: None,
+        })
+        .unwrap();
+
+        let err = notifier.notify(&due_task()).await.unwrap_err();
+
+        assert!(matches!(err, AppError::Internal(_)));
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), WEBHOOK_MAX_ATTEMPTS as usize);
+        assert_eq!(received[0].signature, None);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_does_not_retry_client_errors() {
+        let (url, received) = mock_server(vec![400]).await;
+        let notifier = WebhookNotifier::new(&WebhookConfig {
+            url,
+            timeout_secs: 5,
+            secret: None,
+        })
+        .unwrap();
+
+        assert!(notifier.notify(&due_task()).await.is_err());
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,128 @@
+//! Generated gRPC types and conversions to the domain models.
+//!
+//! The message, client, and server types are generated from
+//! `proto/task.proto` by `build.rs`. This module adds conversions between
+//! those wire types and the models in [`crate::models`], so each service
+//! doesn't have to write its own mapping code.
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{self, CreateTask, UpdateTask};
+
+tonic::include_proto!("task");
+
+impl From<models::TaskStatus> for TaskStatus {
+    fn from(status: models::TaskStatus) -> Self {
+        match status {
+            models::TaskStatus::Todo => Self::Todo,
+            models::TaskStatus::InProgress => Self::InProgress,
+            models::TaskStatus::Done => Self::Done,
+        }
+    }
+}
+
+impl From<models::TaskPriority> for TaskPriority {
+    fn from(priority: models::TaskPriority) -> Self {
+        match priority {
+            models::TaskPriority::Low => Self::Low,
+            models::TaskPriority::Medium => Self::Medium,
+            models::TaskPriority::High => Self::High,
+            models::TaskPriority::Urgent => Self::Urgent,
+        }
+    }
+}
+
+impl From<models::Task> for Task {
+    fn from(task: models::Task) -> Self {
+        Task {
+            id: task.id,
+            title: task.title,
+            description: task.description,
+            status: TaskStatus::from(task.status) as i32,
+            priority: TaskPriority::from(task.priority) as i32,
+            due_date: task.due_date.map(|d| d.to_rfc3339()),
+            user_id: task.user_id,
+            created_at: task.created_at.to_rfc3339(),
+            updated_at: task.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+impl TryFrom<CreateTaskRequest> for CreateTask {
+    type Error = AppError;
+
+    fn try_from(req: CreateTaskRequest) -> AppResult<Self> {
+        Ok(CreateTask {
+            title: req.title,
+            description: req.description,
+            status: parse_status(req.status)?.unwrap_or_default(),
+            priority: parse_priority(req.priority)?.unwrap_or_default(),
+            due_date: req.due_date.as_deref().map(parse_datetime).transpose()?,
+            user_id: req.user_id,
+            // Recurrence and time tracking aren't exposed over gRPC yet
+            recurrence: None,
+            estimated_minutes: None,
+            actual_minutes: None,
+            project_id: None,
+            parent_id: None,
+        })
+    }
+}
+
+impl TryFrom<UpdateTaskRequest> for UpdateTask {
+    type Error = AppError;
+
+    fn try_from(req: UpdateTaskRequest) -> AppResult<Self> {
+        Ok(UpdateTask {
+            title: req.title,
+            description: req.description,
+            status: req.status.map(parse_status).transpose()?.flatten(),
+            priority: req.priority.map(parse_priority).transpose()?.flatten(),
+            // An empty string clears the due date
+            due_date: match req.due_date.as_deref() {
+                None => None,
+                Some("") => Some(None),
+                Some(value) => Some(Some(parse_datetime(value)?)),
+            },
+            // Time tracking isn't exposed over gRPC yet
+            ..Default::default()
+        })
+    }
+}
+
+/// Parse a wire status. `UNSPECIFIED` maps to `None`.
+fn parse_status(value: i32) -> AppResult<Option<models::TaskStatus>> {
+    match TaskStatus::try_from(value) {
+        Ok(TaskStatus::Unspecified) => Ok(None),
+        Ok(TaskStatus::Todo) => Ok(Some(models::TaskStatus::Todo)),
+        Ok(TaskStatus::InProgress) => Ok(Some(models::TaskStatus::InProgress)),
+        Ok(TaskStatus::Done) => Ok(Some(models::TaskStatus::Done)),
+        Err(_) => Err(AppError::Validation(format!(
+            "Unknown task status: {}",
+            value
+        ))),
+    }
+}
+
+/// Parse a wire priority. `UNSPECIFIED` maps to `None`.
+fn parse_priority(value: i32) -> AppResult<Option<models::TaskPriority>> {
+    match TaskPriority::try_from(value) {
+        Ok(TaskPriority::Unspecified) => Ok(None),
+        Ok(TaskPriority::Low) => Ok(Some(models::TaskPriority::Low)),
+        Ok(TaskPriority::Medium) => Ok(Some(models::TaskPriority::Medium)),
+        Ok(TaskPriority::High) => Ok(Some(models::TaskPriority::High)),
+        Ok(TaskPriority::Urgent) => Ok(Some(models::TaskPriority::Urgent)),
+        Err(_) => Err(AppError::Validation(format!(
+            "Unknown task priority: {}",
+            value
+        ))),
+    }
+}
+
+/// Parse an RFC 3339 timestamp from the wire.
+fn parse_datetime(value: &str) -> AppResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|d| d.with_timezone(&Utc))
+        .map_err(|_| AppError::Validation(format!("Invalid RFC 3339 timestamp: {}", value)))
+}
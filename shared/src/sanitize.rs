@@ -0,0 +1,141 @@
+//! Turning user-supplied task text into HTML-safe text.
+//!
+//! Task titles and descriptions are never sanitized on the way in: they
+//! are stored exactly as typed, so JSON, gRPC, and webhook clients get
+//! them back unchanged, and escaping happens at render time. The HTML UI
+//! gets it from askama's autoescaping; code that builds HTML by hand
+//! (like [`crate::search::snippet`]) calls these helpers itself:
+//!
+//! - [`SanitizePolicy::Escape`] keeps everything, escaping the special
+//!   characters: `<b>hi</b>` becomes `&lt;b&gt;hi&lt;/b&gt;`
+//! - [`SanitizePolicy::Strip`] drops anything that looks like a tag and
+//!   escapes the rest: `<b>hi</b>` becomes `hi`
+//!
+//! Either way a lone `<` as in "x < y" is kept, as `&lt;`.
+
+use std::str::FromStr;
+
+/// How HTML in task text is handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SanitizePolicy {
+    /// Keep markup as visible text, escaped
+    #[default]
+    Escape,
+    /// Remove tags, escaping whatever text is left
+    Strip,
+}
+
+impl FromStr for SanitizePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "escape" => Ok(Self::Escape),
+            "strip" => Ok(Self::Strip),
+            other => Err(format!(
+                "unknown sanitize policy {:?} (expected escape or strip)",
+                other
+            )),
+        }
+    }
+}
+
+/// Make `text` safe to insert into HTML under `policy`.
+pub fn sanitize(text: &str, policy: SanitizePolicy) -> String {
+    match policy {
+        SanitizePolicy::Escape => escape_html(text),
+        SanitizePolicy::Strip => escape_html(&strip_tags(text)),
+    }
+}
+
+/// Escape the characters that are special in HTML text and attributes.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Remove everything that looks like a tag: `<` followed by a letter,
+/// `/`, `!`, or `?`, through the next `>`. Other `<`s are kept, and so is
+/// an unclosed tag's text.
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let after = &rest[start + 1..];
+        let is_tag = after
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'));
+        match after.find('>') {
+            Some(end) if is_tag => {
+                out.push_str(&rest[..start]);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push_str(&rest[..=start]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &str = r#"<script>alert("hi")</script>"#;
+
+    #[test]
+    fn test_escape_neutralizes_script() {
+        assert_eq!(
+            sanitize(PAYLOAD, SanitizePolicy::Escape),
+            "&lt;script&gt;alert(&quot;hi&quot;)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn test_strip_removes_tags_but_keeps_comparisons() {
+        assert_eq!(
+            sanitize(PAYLOAD, SanitizePolicy::Strip),
+            "alert(&quot;hi&quot;)"
+        );
+        assert_eq!(
+            sanitize("Check <b>x < y</b> & <3", SanitizePolicy::Strip),
+            "Check x &lt; y &amp; &lt;3"
+        );
+    }
+
+    #[test]
+    fn test_plain_text_is_unchanged_and_entities_are_literal() {
+        for policy in [SanitizePolicy::Escape, SanitizePolicy::Strip] {
+            assert_eq!(
+                sanitize("Buy milk, 2 cartons", policy),
+                "Buy milk, 2 cartons"
+            );
+            // Someone who types an entity sees that entity, not its character
+            assert_eq!(sanitize("&amp;", policy), "&amp;amp;");
+        }
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("STRIP".parse::<SanitizePolicy>(), Ok(SanitizePolicy::Strip));
+        assert_eq!(
+            "escape".parse::<SanitizePolicy>(),
+            Ok(SanitizePolicy::Escape)
+        );
+        assert!("remove".parse::<SanitizePolicy>().is_err());
+    }
+}
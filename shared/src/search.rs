@@ -0,0 +1,106 @@
+//! Search result presentation.
+//!
+//! Matching itself is done by the repository (`TaskFilter::search`); this
+//! module turns a match into a short, highlighted excerpt for display.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Task;
+use crate::sanitize::escape_html;
+
+/// A task that matched a search, with an excerpt of its description.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SearchHit {
+    /// The matching task
+    pub task: Task,
+
+    /// HTML excerpt of the description around the first match, with the
+    /// match wrapped in `<mark>`; empty if only the title matched
+    pub snippet: String,
+}
+
+/// Excerpt of `text` around the first match of `query`, as HTML.
+///
+/// Matching is ASCII case-insensitive, like the repository's search. The
+/// excerpt keeps up to `radius` characters on each side of the match,
+/// with `…` where text was cut off. Everything is HTML-escaped, and the
+/// match itself is wrapped in `<mark>`. Returns an empty string if `query`
+/// is empty or doesn't occur in `text`.
+pub fn snippet(text: &str, query: &str, radius: usize) -> String {
+    if query.is_empty() {
+        return String::new();
+    }
+    // ASCII lowercasing keeps every byte offset, so positions found in
+    // the lowered copy are valid in `text`
+    let Some(start) = text.to_ascii_lowercase().find(&query.to_ascii_lowercase()) else {
+        return String::new();
+    };
+    let end = start + query.len();
+
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .take(radius)
+        .last()
+        .map_or(start, |(i, _)| i);
+    let to = text[end..]
+        .char_indices()
+        .nth(radius)
+        .map_or(text.len(), |(i, _)| end + i);
+
+    let mut out = String::new();
+    if from > 0 {
+        out.push('…');
+    }
+    out.push_str(&escape_html(&text[from..start]));
+    out.push_str("<mark>");
+    out.push_str(&escape_html(&text[start..end]));
+    out.push_str("</mark>");
+    out.push_str(&escape_html(&text[end..to]));
+    if to < text.len() {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_at_start() {
+        assert_eq!(
+            snippet("Deploy the new build tonight", "deploy", 8),
+            "<mark>Deploy</mark> the new…"
+        );
+    }
+
+    #[test]
+    fn test_snippet_in_middle() {
+        assert_eq!(
+            snippet("Ask <b>Sam</b> about the budget before Friday", "BUDGET", 6),
+            "…t the <mark>budget</mark> befor…"
+        );
+        // Surrounding markup is escaped rather than passed through
+        assert_eq!(
+            snippet("Ask <b>Sam</b> about it", "sam", 3),
+            "…&lt;b&gt;<mark>Sam</mark>&lt;/b…"
+        );
+    }
+
+    #[test]
+    fn test_snippet_at_end() {
+        assert_eq!(
+            snippet("Remember to water the plants", "plants", 10),
+            "…water the <mark>plants</mark>"
+        );
+    }
+
+    #[test]
+    fn test_no_match_is_empty() {
+        assert_eq!(snippet("Nothing to see here", "budget", 10), "");
+        assert_eq!(snippet("", "budget", 10), "");
+        assert_eq!(snippet("Anything", "", 10), "");
+    }
+}
@@ -0,0 +1,72 @@
+//! Read-only mode flips a process-wide flag, so it's tested in its own
+//! binary where no other test can be writing at the same time.
+
+use shared::db::{create_test_task, is_read_only, set_read_only};
+use shared::{
+    create_pool, run_migrations, AppError, CreateUser, DatabaseUrl, DbPool, TaskRepository,
+    UserRepository, Uuid,
+};
+
+async fn temp_pool() -> DbPool {
+    let path = std::env::temp_dir().join(format!("read-only-{}.db", Uuid::new_v4()));
+    let pool = create_pool(&DatabaseUrl::parse(&format!("sqlite:{}", path.display())).unwrap())
+        .await
+        .unwrap();
+    run_migrations(&pool).await.unwrap();
+    pool
+}
+
+#[tokio::test]
+async fn test_read_only_blocks_writes_but_not_reads() {
+    let pool = temp_pool().await;
+    let user = UserRepository::create(
+        &pool,
+        CreateUser {
+            username: "alice".to_string(),
+            password: "correct-horse-battery".to_string(),
+            email: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Off: both work
+    assert!(!is_read_only());
+    let task = create_test_task(&pool, user.id, "Before").await.unwrap();
+    assert_eq!(
+        TaskRepository::find_by_id(&pool, task.id)
+            .await
+            .unwrap()
+            .title,
+        "Before"
+    );
+
+    // On: writes fail, reads still succeed
+    set_read_only(true);
+    let err = create_test_task(&pool, user.id, "During")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppError::ReadOnly));
+    assert_eq!(err.status_code().as_u16(), 503);
+    assert!(matches!(
+        TaskRepository::mark_done(&pool, task.id).await,
+        Err(AppError::ReadOnly)
+    ));
+    assert_eq!(
+        TaskRepository::find_by_user(&pool, user.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Off again: writes resume
+    set_read_only(false);
+    create_test_task(&pool, user.id, "After").await.unwrap();
+    assert_eq!(
+        TaskRepository::count_by_user(&pool, user.id).await.unwrap(),
+        2
+    );
+
+    pool.close().await;
+}
@@ -0,0 +1,174 @@
+// web-service/src/auth.rs
+// Bearer-token authentication filters, and the task ownership guard
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use shared::auth::{verify_bearer, verify_token};
+use shared::{AppError, AppResult, Task, TaskStore};
+use warp::{Filter, Rejection};
+
+use crate::error::reject;
+
+/// Require a valid `Authorization: Bearer <token>` header.
+///
+/// Extracts the authenticated user's ID for downstream handlers, or rejects
+/// with `AppError::Unauthorized` (401) when the header is missing or the
+/// token doesn't verify.
+pub fn with_auth(secret: Arc<str>) -> impl Filter<Extract = (i64,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization").and_then(move |header: Option<String>| {
+        let secret = secret.clone();
+        async move { verify_bearer(header.as_deref(), &secret).map_err(reject) }
+    })
+}
+
+/// Require a valid token in the `token` cookie.
+///
+/// Used by the HTML UI, where the browser sends the cookie automatically.
+/// Rejects with `AppError::Unauthorized` (401) like `with_auth`.
+pub fn with_session_auth(
+    secret: Arc<str>,
+) -> impl Filter<Extract = (i64,), Error = Rejection> + Clone {
+    warp::cookie::optional::<String>("token").and_then(move |token: Option<String>| {
+        let secret = secret.clone();
+        async move {
+            let token =
+                token.ok_or_else(|| reject(AppError::Unauthorized("Not logged in".to_string())))?;
+            verify_token(&token, &secret)
+                .map(|claims| claims.sub)
+                .map_err(reject)
+        }
+    })
+}
+
+/// Load a task the user owns.
+///
+/// The one ownership rule for every surface (REST, UI, WebSocket, and
+/// gRPC through its own copy): a task that doesn't exist is
+/// `AppError::TaskNotFound` (404) and someone else's task is
+/// `AppError::Forbidden` (403). Takes a single lookup either way.
+///
+/// # Errors
+/// * `AppError::TaskNotFound` - If no task has the given ID
+/// * `AppError::Forbidden` - If the task belongs to another user
+pub(crate) async fn load_owned_task<S: TaskStore>(
+    store: &S,
+    id: i64,
+    user_id: i64,
+) -> AppResult<Task> {
+    let task = store.find_by_id(id).await?;
+
+    if task.user_id != user_id {
+        return Err(AppError::Forbidden(format!(
+            "Task {} belongs to another user",
+            id
+        )));
+    }
+
+    Ok(task)
+}
+
+/// Require that the authenticated user owns the task in the path.
+///
+/// Wraps a filter extracting `(task_id, user_id)` - a task path followed
+/// by one of the auth filters - and passes both IDs on once
+/// [`load_owned_task`] succeeds, so handlers behind it can skip the check.
+pub fn require_ownership<F, S>(
+    ids: F,
+    store: S,
+) -> impl Filter<Extract = (i64, i64), Error = Rejection> + Clone
+where
+    F: Filter<Extract = (i64, i64), Error = Rejection> + Clone + Send + Sync + 'static,
+    S: TaskStore + Clone + 'static,
+{
+    ids.and(warp::any().map(move || store.clone()))
+        .and_then(|id: i64, user_id: i64, store: S| async move {
+            load_owned_task(&store, id, user_id).await.map_err(reject)?;
+            Ok::<_, Rejection>((id, user_id))
+        })
+        .untuple_one()
+}
+
+/// Query string carrying a token, e.g. `/ws?token=...`.
+#[derive(Debug, Default, Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Require a valid token in the `token` query parameter.
+///
+/// For WebSocket upgrades, where browsers can't set an `Authorization`
+/// header. Rejects with `AppError::Unauthorized` (401) like `with_auth`.
+pub fn with_query_auth(
+    secret: Arc<str>,
+) -> impl Filter<Extract = (i64,), Error = Rejection> + Clone {
+    // A missing or unparseable query string just means there's no token
+    warp::query::<TokenQuery>()
+        .or(warp::any().map(TokenQuery::default))
+        .unify()
+        .and_then(move |query: TokenQuery| {
+            let secret = secret.clone();
+            async move {
+                let token = query
+                    .token
+                    .ok_or_else(|| reject(AppError::Unauthorized("Missing token".to_string())))?;
+                verify_token(&token, &secret)
+                    .map(|claims| claims.sub)
+                    .map_err(reject)
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::handle_rejection;
+    use chrono::Duration;
+    use shared::auth::issue_token;
+    use warp::http::StatusCode;
+
+    const SECRET: &str = "test-secret";
+
+    fn whoami(
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = std::convert::Infallible> + Clone {
+        warp::path!("whoami")
+            .and(with_auth(Arc::from(SECRET)))
+            .map(|user_id: i64| user_id.to_string())
+            .recover(handle_rejection)
+    }
+
+    #[tokio::test]
+    async fn test_valid_token_passes_user_id() {
+        let token = issue_token(7, SECRET, Duration::hours(1)).unwrap();
+
+        let res = warp::test::request()
+            .path("/whoami")
+            .header("authorization", format!("Bearer {}", token))
+            .reply(&whoami())
+            .await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body(), "7");
+    }
+
+    #[tokio::test]
+    async fn test_expired_or_invalid_token_rejected() {
+        let expired = issue_token(7, SECRET, Duration::minutes(-5)).unwrap();
+
+        for header in [
+            format!("Bearer {}", expired),
+            "Bearer garbage".to_string(),
+            format!("Token {}", expired),
+        ] {
+            let res = warp::test::request()
+                .path("/whoami")
+                .header("authorization", header)
+                .reply(&whoami())
+                .await;
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let res = warp::test::request().path("/whoami").reply(&whoami()).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,227 @@
+// web-service/src/error.rs
+// Bridges shared::AppError into warp's rejection system
+
+use std::convert::Infallible;
+
+use shared::{AppError, ErrorResponse};
+use tracing::error;
+use warp::http::header::RETRY_AFTER;
+use warp::http::StatusCode;
+use warp::{Rejection, Reply};
+
+use crate::negotiate::NotAcceptable;
+
+/// Wrapper that lets an `AppError` travel through warp as a rejection.
+///
+/// `AppError` lives in the shared crate, so the orphan rule stops us from
+/// implementing warp's `Reject` trait on it directly - this newtype does it.
+#[derive(Debug)]
+pub struct ApiError(pub AppError);
+
+impl warp::reject::Reject for ApiError {}
+
+/// Convert an `AppError` into a warp rejection.
+///
+/// Handlers use this with `map_err` so `?`-style errors from the repository
+/// end up in `handle_rejection`.
+pub fn reject(err: AppError) -> Rejection {
+    warp::reject::custom(ApiError(err))
+}
+
+/// Convert any rejection into a JSON error response.
+///
+/// Used with `.recover()` on the route tree. Application errors use
+/// `AppError::to_response`; warp's own rejections get matching codes.
+/// Internal errors are logged here, since the response hides their details.
+pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
+    let mut retry_after = None;
+    let (status, body) = if err.is_not_found() {
+        (
+            StatusCode::NOT_FOUND,
+            ErrorResponse::new("not_found", "Not found"),
+        )
+    } else if let Some(ApiError(app_err)) = err.find::<ApiError>() {
+        let (status, body) = app_err.to_response();
+        retry_after = app_err.retry_after();
+        // Blocked writes are expected while in read-only mode
+        if status.is_server_error() && !matches!(app_err, AppError::ReadOnly) {
+            error!("Request failed: {}", app_err);
+        }
+        (status, body)
+    } else if let Some(e) = err.find::<warp::filters::body::BodyDeserializeError>() {
+        (
+            StatusCode::BAD_REQUEST,
+            ErrorResponse::new("bad_request", e.to_string()),
+        )
+    } else if err.find::<warp::reject::PayloadTooLarge>().is_some() {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorResponse::new("payload_too_large", "Request body is too large"),
+        )
+    } else if err.find::<warp::reject::LengthRequired>().is_some() {
+        (
+            StatusCode::LENGTH_REQUIRED,
+            ErrorResponse::new("length_required", "Content-Length header is required"),
+        )
+    } else if let Some(e) = err.find::<warp::reject::InvalidQuery>() {
+        (
+            StatusCode::BAD_REQUEST,
+            ErrorResponse::new("bad_request", e.to_string()),
+        )
+    } else if err.find::<NotAcceptable>().is_some() {
+        (
+            StatusCode::NOT_ACCEPTABLE,
+            ErrorResponse::new(
+                "not_acceptable",
+                "Only application/json and text/html are available",
+            ),
+        )
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        (
+            StatusCode::METHOD_NOT_ALLOWED,
+            ErrorResponse::new("method_not_allowed", "Method not allowed"),
+        )
+    } else {
+        error!("Unhandled rejection: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorResponse::new("internal", "Internal server error"),
+        )
+    };
+
+    let mut response = warp::reply::with_status(warp::reply::json(&body), status).into_response();
+    if let Some(secs) = retry_after {
+        response.headers_mut().insert(RETRY_AFTER, secs.into());
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::constants::POOL_BUSY_RETRY_AFTER_SECS;
+    use shared::{create_pool_with_config, DatabaseUrl, DbPool, PoolConfig, TaskRepository};
+    use std::time::Duration;
+    use warp::Filter;
+
+    #[tokio::test]
+    async fn test_task_not_found_returns_404_json() {
+        let route = warp::path!("api" / "tasks" / i64)
+            .and_then(|id| async move { Err::<String, _>(reject(AppError::TaskNotFound(id))) })
+            .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .path("/api/tasks/42")
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "task_not_found");
+        assert_eq!(body["message"], "Task not found with id: 42");
+    }
+
+    #[tokio::test]
+    async fn test_generic_not_found_returns_404_json() {
+        let route = warp::path!("api" / "projects" / i64)
+            .and_then(|id| async move {
+                Err::<String, _>(reject(AppError::NotFound {
+                    entity: "Project",
+                    id,
+                }))
+            })
+            .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .path("/api/projects/5")
+            .reply(&route)
+            .await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "project_not_found");
+        assert_eq!(body["message"], "Project not found with id: 5");
+    }
+
+    #[tokio::test]
+    async fn test_conflict_returns_409() {
+        let route = warp::any()
+            .and_then(|| async {
+                Err::<String, _>(reject(AppError::Conflict("email already exists".into())))
+            })
+            .recover(handle_rejection);
+
+        let res = warp::test::request().reply(&route).await;
+
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "conflict");
+    }
+
+    #[tokio::test]
+    async fn test_read_only_returns_503() {
+        let route = warp::any()
+            .and_then(|| async { Err::<String, _>(reject(AppError::ReadOnly)) })
+            .recover(handle_rejection);
+
+        let res = warp::test::request().reply(&route).await;
+
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "read_only");
+        assert_eq!(body["message"], "Service is in read-only mode");
+    }
+
+    #[tokio::test]
+    async fn test_pool_timeout_returns_503_with_retry_after() {
+        let pool = create_pool_with_config(
+            &DatabaseUrl::parse("sqlite::memory:").unwrap(),
+            PoolConfig {
+                max_connections: 1,
+                min_connections: 0,
+                acquire_timeout: Duration::from_millis(50),
+                ..PoolConfig::default()
+            },
+        )
+        .await
+        .unwrap();
+        // Hold the only connection so the next query can't get one
+        let _held = pool.acquire().await.unwrap();
+
+        let route = warp::any()
+            .and(warp::any().map(move || pool.clone()))
+            .and_then(|pool: DbPool| async move {
+                TaskRepository::count_by_user(&pool, 1)
+                    .await
+                    .map(|count| count.to_string())
+                    .map_err(reject)
+            })
+            .recover(handle_rejection);
+
+        let res = warp::test::request().reply(&route).await;
+
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            res.headers()[RETRY_AFTER],
+            POOL_BUSY_RETRY_AFTER_SECS.to_string()
+        );
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "unavailable");
+    }
+
+    #[tokio::test]
+    async fn test_internal_error_is_hidden() {
+        let route = warp::any()
+            .and_then(|| async {
+                Err::<String, _>(reject(AppError::Internal("db on fire".into())))
+            })
+            .recover(handle_rejection);
+
+        let res = warp::test::request().reply(&route).await;
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "internal");
+        assert_eq!(body["message"], "Internal server error");
+    }
+}
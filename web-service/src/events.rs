@@ -0,0 +1,113 @@
+// web-service/src/events.rs
+// In-process broadcast of task changes, streamed to browsers over SSE
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::Serialize;
+use shared::Task;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use warp::sse::Event;
+use warp::Reply;
+
+/// How many events a slow subscriber may fall behind before it starts
+/// missing some.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Interval between keep-alive comments, so proxies don't close idle streams.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// What happened to a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskEventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// A change to one of a user's tasks.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskEvent {
+    pub kind: TaskEventKind,
+    pub user_id: i64,
+    pub task_id: i64,
+    /// The task after the change; `None` for deletions
+    pub task: Option<Task>,
+}
+
+impl TaskEvent {
+    /// Event for a task that was just created or updated.
+    pub fn changed(kind: TaskEventKind, task: &Task) -> Self {
+        Self {
+            kind,
+            user_id: task.user_id,
+            task_id: task.id,
+            task: Some(task.clone()),
+        }
+    }
+
+    /// Event for a task that was just deleted.
+    pub fn deleted(user_id: i64, task_id: i64) -> Self {
+        Self {
+            kind: TaskEventKind::Deleted,
+            user_id,
+            task_id,
+            task: None,
+        }
+    }
+}
+
+/// Publisher side of the task event channel.
+///
+/// Cloning is cheap and every clone publishes to the same subscribers.
+#[derive(Debug, Clone)]
+pub struct TaskEvents {
+    sender: broadcast::Sender<TaskEvent>,
+}
+
+impl Default for TaskEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// Send an event to everyone listening. Having no listeners is fine.
+    pub fn publish(&self, event: TaskEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Start receiving events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<TaskEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// Build the SSE reply that streams one user's task events.
+///
+/// Events for other users are filtered out, and events a slow client
+/// missed (lagged) are skipped rather than ending the stream.
+pub fn sse_stream(events: &TaskEvents, user_id: i64) -> impl Reply {
+    let stream = BroadcastStream::new(events.subscribe()).filter_map(move |result| async move {
+        let event = result.ok().filter(|e| e.user_id == user_id)?;
+        let sse = Event::default()
+            .event("task")
+            .json_data(&event)
+            .unwrap_or_else(|_| Event::default().comment("unserializable event"));
+        Some(Ok::<_, Infallible>(sse))
+    });
+
+    warp::sse::reply(
+        warp::sse::keep_alive()
+            .interval(HEARTBEAT_INTERVAL)
+            .stream(stream),
+    )
+}
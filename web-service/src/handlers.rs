@@ -0,0 +1,467 @@
+// web-service/src/handlers.rs
+// Request handlers for the health check and JSON task API
+//
+// Each handler receives already-extracted values from its route filter
+// and returns either a reply or a rejection built from an AppError.
+// The single-task handlers are generic over TaskStore, so tests can run
+// them against shared::db::MockTaskStore instead of a database.
+
+use std::convert::Infallible;
+
+use std::sync::Arc;
+
+use chrono::Duration;
+use metrics_exporter_prometheus::PrometheusHandle;
+use serde::Serialize;
+use shared::auth::issue_token;
+use shared::config::LockoutConfig;
+use shared::constants::{MAX_BATCH_SIZE, SNIPPET_RADIUS, TOKEN_TTL_HOURS};
+use shared::db::check_health_detailed;
+use shared::search::{snippet, SearchHit};
+use shared::{
+    AppError, CreateTask, CreateUser, DbPool, ErrorResponse, LoginRequest, LoginResponse, Task,
+    TaskRepository, TaskStore, TaskSummary, UpdateTask, UserRepository,
+};
+use tracing::instrument;
+use utoipa::ToSchema;
+use warp::http::StatusCode;
+use warp::{Rejection, Reply};
+
+use crate::error::reject;
+use crate::events::{sse_stream, TaskEvent, TaskEventKind, TaskEvents};
+use crate::negotiate::Format;
+use crate::query::{SearchQuery, TaskQuery};
+use crate::telemetry;
+use crate::ui::{render, TasksTemplate};
+
+/// GET /health - report service status and database pool stats.
+///
+/// Responds 503 when the database check fails so load balancers can
+/// take the instance out of rotation. A database whose schema is behind
+/// the code still answers queries, so that's reported as `degraded` with
+/// a 200.
+#[instrument(skip_all)]
+pub async fn health(pool: DbPool) -> Result<impl Reply, Infallible> {
+    let db = check_health_detailed(&pool).await;
+    let (status, label) = match (db.healthy, db.schema_current) {
+        (false, _) => (StatusCode::SERVICE_UNAVAILABLE, "unhealthy"),
+        (true, false) => (StatusCode::OK, "degraded"),
+        (true, true) => (StatusCode::OK, "healthy"),
+    };
+
+    let body = warp::reply::json(&serde_json::json!({
+        "status": label,
+        "service": "web-service",
+        "version": env!("CARGO_PKG_VERSION"),
+        "database": db,
+    }));
+
+    Ok(warp::reply::with_status(body, status))
+}
+
+/// GET /metrics - the metrics registry in Prometheus text format.
+#[instrument(skip_all)]
+pub async fn metrics(pool: DbPool, handle: PrometheusHandle) -> Result<impl Reply, Infallible> {
+    telemetry::record_pool(&pool);
+
+    Ok(warp::reply::with_header(
+        handle.render(),
+        "content-type",
+        "text/plain; version=0.0.4",
+    ))
+}
+
+/// POST /api/register - create an account, responding with 201 Created.
+#[utoipa::path(
+    post,
+    path = "/api/register",
+    tag = "auth",
+    request_body = CreateUser,
+    responses(
+        (status = 201, description = "Account created", body = UserResponse),
+        (status = 400, description = "Username or password too short or long", body = ErrorResponse),
+        (status = 409, description = "Username or email already taken", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(username = %user.username))]
+pub async fn register(user: CreateUser, pool: DbPool) -> Result<impl Reply, Rejection> {
+    let user = UserRepository::create(&pool, user).await.map_err(reject)?;
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&user.to_response()),
+        StatusCode::CREATED,
+    ))
+}
+
+/// POST /api/login - exchange a username and password for a bearer token.
+///
+/// An unknown username and a wrong password both produce the same
+/// `InvalidCredentials` error, so the response doesn't reveal which usernames
+/// exist. After too many wrong passwords the account is locked for a while
+/// (see `LockoutConfig`). The token is also set as an HttpOnly `token`
+/// cookie for the HTML UI.
+#[utoipa::path(
+    post,
+    path = "/api/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in; the token is also set as a cookie", body = LoginResponse),
+        (status = 401, description = "Wrong username or password, or the account is locked", body = ErrorResponse),
+    )
+)]
+#[instrument(skip_all, fields(username = %credentials.username))]
+pub async fn login(
+    credentials: LoginRequest,
+    pool: DbPool,
+    jwt_secret: Arc<str>,
+    lockout: LockoutConfig,
+) -> Result<impl Reply, Rejection> {
+    let user = UserRepository::authenticate(
+        &pool,
+        &credentials.username,
+        &credentials.password,
+        &lockout,
+    )
+    .await
+    .map_err(reject)?;
+
+    let ttl = Duration::hours(TOKEN_TTL_HOURS);
+    let token = issue_token(user.id, &jwt_secret, ttl).map_err(reject)?;
+    let cookie = format!(
+        "token={}; HttpOnly; SameSite=Lax; Path=/; Max-Age={}",
+        token,
+        ttl.num_seconds()
+    );
+
+    let body = warp::reply::json(&LoginResponse {
+        token,
+        user: user.to_response(),
+    });
+
+    Ok(warp::reply::with_header(body, "set-cookie", cookie))
+}
+
+/// GET /api/tasks - list the authenticated user's tasks.
+///
+/// Filtering, sorting, and paging come from the query string; see
+/// [`TaskQuery`] for the params and their defaults. The same list is
+/// returned as JSON or as the HTML task page, whichever `format` the
+/// `Accept` header asked for.
+#[utoipa::path(
+    get,
+    path = "/api/tasks",
+    tag = "tasks",
+    params(TaskQuery),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The user's tasks", content(
+            ("application/json" = Vec<Task>),
+            ("text/html" = String),
+        )),
+        (status = 400, description = "Unknown filter, sort, or paging value", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 406, description = "Accept allows neither JSON nor HTML", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(query, pool))]
+pub async fn list_tasks(
+    user_id: i64,
+    format: Format,
+    query: TaskQuery,
+    pool: DbPool,
+) -> Result<impl Reply, Rejection> {
+    let filter = query.into_filter(user_id).map_err(reject)?;
+    let tasks = TaskRepository::find_filtered(&pool, filter)
+        .await
+        .map_err(reject)?;
+
+    let reply = match format {
+        Format::Json => warp::reply::json(&tasks).into_response(),
+        Format::Html => render(&TasksTemplate {
+            tasks: tasks.into_iter().map(TaskSummary::from).collect(),
+        })?
+        .into_response(),
+    };
+    // Caches must key on Accept, since it picks the body
+    Ok(warp::reply::with_header(reply, "vary", "accept"))
+}
+
+/// GET /api/tasks/search?q= - the user's tasks mentioning `q`.
+///
+/// Each hit carries an HTML snippet of the description around the first
+/// match, with the match in `<mark>`; the snippet is empty when only the
+/// title matched.
+#[utoipa::path(
+    get,
+    path = "/api/tasks/search",
+    tag = "tasks",
+    params(SearchQuery),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Matching tasks, newest first", body = Vec<SearchHit>),
+        (status = 400, description = "Blank search text or bad limit", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(query, pool))]
+pub async fn search_tasks(
+    user_id: i64,
+    query: SearchQuery,
+    pool: DbPool,
+) -> Result<impl Reply, Rejection> {
+    let filter = query.into_filter(user_id).map_err(reject)?;
+    let term = filter.search.clone().unwrap_or_default();
+    let tasks = TaskRepository::find_filtered(&pool, filter)
+        .await
+        .map_err(reject)?;
+
+    let hits: Vec<SearchHit> = tasks
+        .into_iter()
+        .map(|task| SearchHit {
+            snippet: snippet(&task.description, &term, SNIPPET_RADIUS),
+            task,
+        })
+        .collect();
+    Ok(warp::reply::json(&hits))
+}
+
+/// GET /api/tasks/:id - fetch one of the user's tasks.
+///
+/// The response carries an `ETag`; polling clients send it back in
+/// `If-None-Match` and get an empty 304 while the task is unchanged.
+#[utoipa::path(
+    get,
+    path = "/api/tasks/{id}",
+    tag = "tasks",
+    params(
+        ("id" = i64, Path, description = "Task ID"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response"),
+    ),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The task", body = Task,
+            headers(("etag" = String, description = "Changes whenever the task does"))),
+        (status = 304, description = "The task still matches `If-None-Match`"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "The task belongs to someone else", body = ErrorResponse),
+        (status = 404, description = "No such task", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(store))]
+pub async fn get_task<S: TaskStore>(
+    id: i64,
+    user_id: i64,
+    if_none_match: Option<String>,
+    store: S,
+) -> Result<impl Reply, Rejection> {
+    let task = store.find_by_id(id).await.map_err(reject)?;
+    let etag = task_etag(&task);
+
+    // Compared before serializing, so an unchanged task costs no body at all
+    if if_none_match.is_some_and(|header| etag_matches(&header, &etag)) {
+        return Ok(
+            warp::reply::with_header(StatusCode::NOT_MODIFIED, "etag", etag).into_response(),
+        );
+    }
+
+    Ok(warp::reply::with_header(warp::reply::json(&task), "etag", etag).into_response())
+}
+
+/// Strong ETag for a task's JSON representation.
+///
+/// Every edit bumps `version`; reordering only changes `position`, so that
+/// goes in too.
+fn task_etag(task: &Task) -> String {
+    format!("\"{}-{}-{}\"", task.id, task.version, task.position)
+}
+
+/// Whether an `If-None-Match` header value matches `etag`.
+///
+/// The header may list several tags, or be `*` for any; weak tags
+/// (`W/"..."`) compare by their value.
+fn etag_matches(header: &str, etag: &str) -> bool {
+    header.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// POST /api/tasks - create a task owned by the user, responding with 201 Created.
+///
+/// Invalid input is rejected with 400 and a `fields` object naming every
+/// bad field, not just the first.
+#[utoipa::path(
+    post,
+    path = "/api/tasks",
+    tag = "tasks",
+    request_body = CreateTask,
+    security(("bearer" = [])),
+    responses(
+        (status = 201, description = "Task created", body = Task),
+        (status = 400, description = "Invalid task; `fields` names every bad field", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(task, store, events))]
+pub async fn create_task<S: TaskStore>(
+    user_id: i64,
+    mut task: CreateTask,
+    store: S,
+    events: TaskEvents,
+) -> Result<impl Reply, Rejection> {
+    // Never trust an owner supplied in the body
+    task.user_id = user_id;
+    task.validate_new()
+        .map_err(|errors| reject(errors.into()))?;
+    let task = store.create(task).await.map_err(reject)?;
+    events.publish(TaskEvent::changed(TaskEventKind::Created, &task));
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&task),
+        StatusCode::CREATED,
+    ))
+}
+
+/// Outcome of one item in a batch create, serialized like a Rust `Result`:
+/// `{"Ok": task}` or `{"Err": error}`.
+#[derive(Debug, Serialize, ToSchema)]
+pub enum BatchOutcome {
+    Ok(Task),
+    Err(ErrorResponse),
+}
+
+/// One entry in the batch create response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchItemResult {
+    /// Position of the item in the request array, from 0
+    pub index: usize,
+    pub result: BatchOutcome,
+}
+
+/// POST /api/tasks/batch - create several tasks, each on its own.
+///
+/// Unlike `TaskRepository::import_json`, this is best-effort: every item
+/// is validated and inserted separately, so bad items are reported
+/// without stopping the good ones. The response has one entry per item,
+/// in request order.
+#[utoipa::path(
+    post,
+    path = "/api/tasks/batch",
+    tag = "tasks",
+    request_body = Vec<CreateTask>,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Per-item results, in request order", body = Vec<BatchItemResult>),
+        (status = 400, description = "Not a JSON array, or more than MAX_BATCH_SIZE items", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(tasks, store, events), fields(count = tasks.len()))]
+pub async fn create_tasks_batch<S: TaskStore>(
+    user_id: i64,
+    tasks: Vec<CreateTask>,
+    store: S,
+    events: TaskEvents,
+) -> Result<impl Reply, Rejection> {
+    if tasks.len() > MAX_BATCH_SIZE {
+        return Err(reject(AppError::Validation(format!(
+            "A batch can hold at most {} tasks",
+            MAX_BATCH_SIZE
+        ))));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for (index, mut task) in tasks.into_iter().enumerate() {
+        // Never trust an owner supplied in the body
+        task.user_id = user_id;
+        let created = match task.validate_new() {
+            Ok(()) => store.create(task).await,
+            Err(errors) => Err(errors.into()),
+        };
+
+        let result = match created {
+            Ok(task) => {
+                events.publish(TaskEvent::changed(TaskEventKind::Created, &task));
+                BatchOutcome::Ok(task)
+            }
+            Err(err) => BatchOutcome::Err(err.to_response().1),
+        };
+        results.push(BatchItemResult { index, result });
+    }
+
+    Ok(warp::reply::json(&results))
+}
+
+/// PUT /api/tasks/:id - apply a partial update to one of the user's tasks.
+#[utoipa::path(
+    put,
+    path = "/api/tasks/{id}",
+    tag = "tasks",
+    params(("id" = i64, Path, description = "Task ID")),
+    request_body = UpdateTask,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The updated task", body = Task),
+        (status = 400, description = "Invalid field value", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "The task belongs to someone else", body = ErrorResponse),
+        (status = 404, description = "No such task", body = ErrorResponse),
+        (status = 409, description = "The task changed since `version` was read", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(update, store, events))]
+pub async fn update_task<S: TaskStore>(
+    id: i64,
+    user_id: i64,
+    update: UpdateTask,
+    store: S,
+    events: TaskEvents,
+) -> Result<impl Reply, Rejection> {
+    let task = store.update(id, user_id, update).await.map_err(reject)?;
+    events.publish(TaskEvent::changed(TaskEventKind::Updated, &task));
+
+    Ok(warp::reply::json(&task))
+}
+
+/// DELETE /api/tasks/:id - delete one of the user's tasks, responding with 204 No Content.
+#[utoipa::path(
+    delete,
+    path = "/api/tasks/{id}",
+    tag = "tasks",
+    params(("id" = i64, Path, description = "Task ID")),
+    security(("bearer" = [])),
+    responses(
+        (status = 204, description = "Task deleted"),
+        (status = 401, description = "Missing or invalid token", body = ErrorResponse),
+        (status = 403, description = "The task belongs to someone else", body = ErrorResponse),
+        (status = 404, description = "No such task", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+    )
+)]
+#[instrument(skip(store, events))]
+pub async fn delete_task<S: TaskStore>(
+    id: i64,
+    user_id: i64,
+    store: S,
+    events: TaskEvents,
+) -> Result<impl Reply, Rejection> {
+    store.delete(id, user_id).await.map_err(reject)?;
+    events.publish(TaskEvent::deleted(user_id, id));
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/tasks/events - stream the user's task changes as SSE.
+///
+/// Scoped to the authenticated user, so there's no `user_id` parameter
+/// to trust or check.
+#[instrument(skip(events))]
+pub async fn task_events(user_id: i64, events: TaskEvents) -> Result<impl Reply, Infallible> {
+    Ok(sse_stream(&events, user_id))
+}
@@ -1,104 +1,222 @@
 // web-service/src/main.rs
 // Entry point for the HTTP web service that serves HTMX UI
 
-use tracing::{info, Level};
-use tracing_subscriber;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use shared::db::{pool_monitor, warmup, ShutdownCause};
+use shared::logging::init_logging;
+use shared::{create_pool, run_migrations, Config};
+use tracing::{info, warn};
 use warp::Filter;
 
+mod auth;
+mod error;
+mod events;
+mod handlers;
+mod middleware;
+mod negotiate;
+mod openapi;
+mod query;
+mod rate_limit;
+mod routes;
+mod telemetry;
+mod tls;
+mod ui;
+mod ws;
+
+/// Log filter used when RUST_LOG isn't set
+const DEFAULT_LOG_FILTER: &str = "info,warp::filters::trace=off";
+
 // The #[tokio::main] macro sets up the async runtime
 // Same as gRPC service, but now we are handling HTTP instead
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_target(false)
-        .with_level(true)
-        .with_max_level(Level::INFO)
-        .init();
+async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    // Read settings from the environment (DATABASE_URL, WEB_PORT, JWT_SECRET)
+    // Anything unset falls back to the defaults in shared::constants
+    let config = Config::from_env()?;
+
+    // Initialize logging as pretty or JSON lines (LOG_FORMAT), filtered by
+    // RUST_LOG (e.g. RUST_LOG=debug to see the repository spans under
+    // each request)
+    // By default warp's trace filter's own "processing request" lines are
+    // silenced, because middleware::request_logging covers each request
+    // in a single line
+    init_logging(config.log_format, DEFAULT_LOG_FILTER);
 
     info!("🌐 Web Service starting...");
     info!("📍 Version: {}", env!("CARGO_PKG_VERSION"));
 
+    // Secret used to verify bearer tokens; refuse to start without one
+    // unless ALLOW_DEV_JWT_SECRET opts into the public development secret
+    let jwt_secret: Arc<str> = config.signing_secret()?.into();
+    if config.jwt_secret.is_none() {
+        warn!("⚠️  JWT_SECRET not set, using the insecure development secret");
+    }
+
     // Define the server address
     // 0.0.0.0 means listen on all network interfaces
     // [u8; 4] is an array of 4 bytes - Rust's way of representing IPv4
     let address: [u8; 4] = [0, 0, 0, 0];
-    let port: u16 = 3000;
+    let port: u16 = config.web_port;
+
+    // HTTPS when both TLS_CERT and TLS_KEY are set, plain HTTP otherwise
+    let tls = config.tls_cert.as_deref().zip(config.tls_key.as_deref());
+    let scheme = if tls.is_some() { "https" } else { "http" };
+
+    info!(
+        "🎯 Server will listen on {}://{}:{}",
+        scheme, "0.0.0.0", port
+    );
+
+    // One limit for every repository call in the process
+    shared::db::set_query_timeout(std::time::Duration::from_secs(config.query_timeout_secs));
+
+    // Connect to the database and make sure the schema is current
+    let pool = create_pool(&config.database_url).await?;
+    run_migrations(&pool).await?;
+    // Open the whole pool now rather than during the first requests
+    warmup(&pool, pool.options().get_max_connections()).await?;
+    info!("🗄️  Database ready at {}", config.database_url);
 
-    info!("🎯 Server will listen on http://{}:{}", "0.0.0.0", port);
+    // Give development databases a demo user and tasks to look at; only
+    // built with `--features dev`, so a release can never seed demo data
+    #[cfg(feature = "dev")]
+    shared::db::seed(&pool).await?;
 
-    // Create a simple health check route
-    // This demonstrates Warp's filter-based routing
-    // Filters are composable building blocks
-    let health_route = warp::path("health")
-        // Match GET requests
+    // READ_ONLY=true starts with writes blocked; SIGUSR1 toggles it
+    shared::db::set_read_only(config.read_only);
+    #[cfg(unix)]
+    shared::db::spawn_read_only_toggle()?;
+    if config.read_only {
+        warn!("🔒 Read-only mode: writes are blocked until SIGUSR1");
+    }
+
+    // Install the Prometheus recorder before anything records a metric
+    let prometheus = telemetry::prometheus();
+
+    // Warn in the logs when every database connection stays busy
+    shared::db::spawn_pool_monitor(
+        pool.clone(),
+        pool_monitor::DEFAULT_CHECK_INTERVAL,
+        pool_monitor::DEFAULT_WARN_AFTER,
+    );
+
+    // POST due-task reminders to WEBHOOK_URL when it's set. Only this
+    // service sends them: the gRPC service shares the database, so running
+    // them there too would send every reminder twice
+    if let Some(webhook) = &config.webhook {
+        shared::notify::spawn_reminders(
+            pool.clone(),
+            shared::notify::WebhookNotifier::new(webhook)?,
+            shared::notify::REMINDER_INTERVAL,
+            shared::notify::REMINDER_WINDOW_HOURS,
+        );
+        info!("🔔 Due-task reminders go to the configured webhook");
+    }
+
+    // Token buckets per user (or per IP when unauthenticated), shared by
+    // the API, the UI, and the WebSocket
+    let limiter = rate_limit::RateLimiter::per_minute(config.rate_limit_per_minute);
+
+    // Task changes fan out to every open SSE stream
+    let events = events::TaskEvents::new();
+
+    // The root just sends browsers to the task UI
+    let root_route = warp::path::end()
         .and(warp::get())
-        // When matched, respond with JSON
-        .map(|| {
-            // warp::reply::json creates a JSON response
-            // This struct will be serialized automatically
-            warp::reply::json(&serde_json::json!({
-                "status": "healthy",
-                "service": "web-service",
-                "version": env!("CARGO_PKG_VERSION")
-            }))
-        });
-
-    // Create a root route that shows a welcome message
-    let root_route = warp::path::end().and(warp::get()).map(|| {
-        // Return HTML directly
-        // In phase 3, we'll use Askama templates instead
-        warp::reply::html(
-            r#"
-                <!DOCTYPE html>
-                <html>
-                <head>
-                    <title>Task Manager</title>
-                    <meta charset="utf-8">
-                </head>
-                <body>
-                    <h1>🚀 Rust Task Manager</h1>
-                    <p>Web service is running!</p>
-                    <p>⚠️ UI not yet implemented (Phase 3)</p>
-                    <h2>Next Steps:</h2>
-                    <ul>
-                        <li>Create Askama templates</li>
-                        <li>Set up gRPC client to communicate with backend</li>
-                        <li>Build HTMX-powered task management UI</li>
-                        <li>Implement authentication (Phase 5)</li>
-                    </ul>
-                    <p><a href="/health">Health Check</a></p>
-                </body>
-                </html>
-                "#,
-        )
-    });
+        .map(|| warp::redirect::see_other(warp::http::Uri::from_static("/tasks")));
 
     // Combine routes using .or()
     // Warp tries each route in order until one matches
     // Python equivalent: @app.route() decorators
     // Rust advantage: routes are type-checked at compile time
     let routes = root_route
-        .or(health_route)
-        // Add CORS headers for development (will refine in Phase 3)
-        .with(warp::cors().allow_any_origin());
+        .or(routes::health(pool.clone()))
+        .or(routes::metrics(pool.clone(), prometheus))
+        .or(routes::api_docs())
+        .or(routes::auth_routes(
+            pool.clone(),
+            jwt_secret.clone(),
+            limiter.clone(),
+            config.lockout,
+            config.max_body_bytes,
+        ))
+        .or(ui::ui_routes(
+            pool.clone(),
+            jwt_secret.clone(),
+            limiter.clone(),
+            events.clone(),
+            config.max_body_bytes,
+        ))
+        .or(routes::websocket(
+            pool.clone(),
+            jwt_secret.clone(),
+            events.clone(),
+            limiter.clone(),
+        ))
+        .or(routes::task_routes(
+            pool.clone(),
+            jwt_secret,
+            limiter,
+            events,
+            config.max_body_bytes,
+        ))
+        // Turn rejections (including AppError) into JSON error responses
+        .recover(error::handle_rejection)
+        // Cross-origin access follows the CORS_* settings
+        .with(middleware::cors(&config.cors))
+        // Log every request; the span outside gives each line a request ID
+        .with(middleware::request_logging())
+        .with(middleware::request_span());
 
     info!("✅ Routes configured:");
-    info!("   GET  /        - Welcome page");
-    info!("   GET  /health  - Health check endpoint");
+    info!("   GET    /                - Redirect to /tasks");
+    info!("   GET    /tasks           - Task UI (HTMX)");
+    info!("   GET    /health          - Health check endpoint");
+    info!("   GET    /metrics         - Prometheus metrics");
+    info!("   GET    /api/openapi.json - OpenAPI spec");
+    info!("   GET    /api/docs        - Swagger UI");
+    info!("   POST   /api/register    - Create an account");
+    info!("   POST   /api/login       - Get a bearer token");
+    info!("   GET    /api/tasks       - List your tasks (Bearer token required)");
+    info!("                               ?status=&priority=&sort=&limit=&offset=");
+    info!("   POST   /api/tasks       - Create a task");
+    info!("   GET    /api/tasks/search?q= - Search with highlighted snippets");
+    info!("   GET    /api/tasks/events - Live task changes (SSE)");
+    info!("   GET    /ws?token=       - Two-way task sync (WebSocket)");
+    info!("   GET    /api/tasks/:id   - Fetch a task");
+    info!("   PUT    /api/tasks/:id   - Update a task");
+    info!("   DELETE /api/tasks/:id   - Delete a task");
     info!("");
-    info!("🚀 Server starting on http://localhost:{}", port);
+    info!("🚀 Server starting on {}://localhost:{}", scheme, port);
     info!("   Press Ctrl+C to stop");
 
     // Create the server
-    // warp::serve() takes our routes and creates a server
-    // .run() starts the server and runs forever (until shutdown)
-    let server = warp::serve(routes).run((address, port));
-
-    // In production, we'd add graceful shutdown here
-    // For now, just run until Ctrl+C
+    // tls::bind() wraps warp::serve() and picks HTTPS or HTTP
+    // The server stops accepting connections on Ctrl+C/SIGTERM and lets
+    // in-flight requests finish
+    // warp logs server errors and finishes on its own, so remember whether
+    // the signal arrived to tell a requested stop from a crash
+    let signalled = Arc::new(AtomicBool::new(false));
+    let shutdown = {
+        let signalled = signalled.clone();
+        async move {
+            shared::db::shutdown_signal().await;
+            signalled.store(true, Ordering::SeqCst);
+        }
+    };
+    let (_, server) = tls::bind(routes, (address, port).into(), tls, shutdown)?;
     server.await;
 
-    info!("👋 Web service stopped");
-    Ok(())
+    let cause = if signalled.load(Ordering::SeqCst) {
+        ShutdownCause::Signal
+    } else {
+        ShutdownCause::Fatal("HTTP server stopped without a shutdown signal".to_string())
+    };
+
+    // Log why we stopped and close the pool; a fatal cause exits non-zero
+    // so orchestrators see a crash
+    Ok(shared::db::finish_shutdown(&pool, "Web service", cause).await)
 }
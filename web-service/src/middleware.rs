@@ -0,0 +1,246 @@
+// web-service/src/middleware.rs
+// Cross-cutting wrappers applied to the whole route tree with .with(),
+// plus response compression, which wraps a filter directly
+
+use std::io::Write;
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use shared::config::CorsConfig;
+use shared::Uuid;
+use tracing::{error, info, info_span, Span};
+use warp::http::header::{HeaderValue, CONTENT_ENCODING, CONTENT_LENGTH, VARY};
+use warp::http::StatusCode;
+use warp::hyper::body::{self, Body, HttpBody};
+use warp::log::{Info, Log};
+use warp::reply::Response;
+use warp::trace::{self, Trace};
+use warp::{Filter, Rejection, Reply};
+
+use crate::negotiate::weighted;
+use crate::telemetry;
+
+/// Log one line per request with its method, path, status, and latency.
+///
+/// The same numbers feed the HTTP request metrics served at `/metrics`.
+///
+/// Apply this *inside* `request_span` so the line carries the request ID.
+pub fn request_logging() -> Log<impl Fn(Info<'_>) + Copy> {
+    warp::log::custom(|info: Info<'_>| {
+        info!(
+            method = %info.method(),
+            path = info.path(),
+            status = info.status().as_u16(),
+            latency_ms = info.elapsed().as_secs_f64() * 1000.0,
+            "request completed"
+        );
+        telemetry::record_request(&info);
+    })
+}
+
+/// Run each request inside a tracing span tagged with a fresh request ID.
+///
+/// Every log event emitted while handling the request - including the
+/// `request_logging` line - is attached to this span.
+pub fn request_span() -> Trace<impl Fn(trace::Info<'_>) -> Span + Clone> {
+    warp::trace(|_info: trace::Info<'_>| info_span!("request", request_id = %Uuid::new_v4()))
+}
+
+/// Build the CORS wrapper from the configured policy.
+///
+/// Requests from origins outside the policy are rejected with 403, both
+/// preflights and the requests themselves, and never get an
+/// `Access-Control-Allow-Origin` header. Same-origin requests (no
+/// `Origin` header) pass through untouched.
+pub fn cors(config: &CorsConfig) -> warp::cors::Builder {
+    let cors = warp::cors()
+        .allow_methods(config.allowed_methods.iter().map(String::as_str))
+        .allow_headers(["authorization", "content-type"])
+        .allow_credentials(config.allow_credentials);
+
+    if config.allows_any_origin() {
+        cors.allow_any_origin()
+    } else {
+        cors.allow_origins(config.allowed_origins.iter().map(String::as_str))
+    }
+}
+
+/// Responses with bodies smaller than this are sent uncompressed; the
+/// saving wouldn't be worth the CPU time or the gzip header overhead.
+pub const COMPRESSION_MIN_BYTES: u64 = 1024;
+
+/// A content coding we can compress responses with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    /// Choose a coding for an `Accept-Encoding` header value.
+    ///
+    /// The highest `q` wins, with the earlier entry winning ties; `*`
+    /// means gzip. Returns `None` if neither coding is acceptable.
+    fn from_accept_encoding(accept: &str) -> Option<Encoding> {
+        let mut best: Option<(f32, Encoding)> = None;
+
+        for (coding, q) in weighted(accept) {
+            let encoding = match coding.as_str() {
+                "gzip" | "x-gzip" | "*" => Encoding::Gzip,
+                "deflate" => Encoding::Deflate,
+                _ => continue,
+            };
+            if q <= 0.0 {
+                continue;
+            }
+            match best {
+                Some((best_q, _)) if best_q >= q => {}
+                _ => best = Some((q, encoding)),
+            }
+        }
+
+        best.map(|(_, encoding)| encoding)
+    }
+
+    fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        })
+    }
+
+    fn encode(self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            // HTTP's "deflate" is the zlib format, not a raw deflate stream
+            Encoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Compress `filter`'s responses with gzip or deflate when the client's
+/// `Accept-Encoding` allows it.
+///
+/// Only bodies of a known size of at least [`COMPRESSION_MIN_BYTES`] are
+/// compressed. Streamed bodies (like the SSE event stream) have no known
+/// size, so they pass through untouched instead of being buffered.
+pub fn compressed<F, R>(filter: F) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone
+where
+    F: Filter<Extract = (R,), Error = Rejection> + Clone + Send + Sync + 'static,
+    R: Reply,
+{
+    warp::header::optional::<String>("accept-encoding")
+        .and(filter)
+        .then(|accept: Option<String>, reply: R| {
+            let encoding = accept.as_deref().and_then(Encoding::from_accept_encoding);
+            compress(encoding, reply.into_response())
+        })
+}
+
+async fn compress(encoding: Option<Encoding>, response: Response) -> Response {
+    let Some(encoding) = encoding else {
+        return response;
+    };
+    let large_enough = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|len| len >= COMPRESSION_MIN_BYTES);
+    if !large_enough || response.headers().contains_key(CONTENT_ENCODING) {
+        return response;
+    }
+
+    let (mut parts, content) = response.into_parts();
+    let bytes = match body::to_bytes(content).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            error!("Failed to read response body for compression: {}", err);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let compressed = match encoding.encode(&bytes) {
+        Ok(compressed) => compressed,
+        Err(err) => {
+            error!("Failed to compress response: {}", err);
+            return Response::from_parts(parts, Body::from(bytes));
+        }
+    };
+
+    parts.headers.remove(CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(CONTENT_ENCODING, encoding.header_value());
+    parts
+        .headers
+        .append(VARY, HeaderValue::from_static("accept-encoding"));
+    Response::from_parts(parts, Body::from(compressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::http::StatusCode;
+
+    #[tokio::test]
+    async fn test_cors_only_allows_configured_origins() {
+        let config = CorsConfig {
+            allowed_origins: vec!["https://app.example.com".to_string()],
+            allowed_methods: vec!["GET".to_string()],
+            allow_credentials: true,
+        };
+        let route = warp::path!("api" / "tasks")
+            .map(|| "tasks")
+            .with(cors(&config));
+
+        let res = warp::test::request()
+            .path("/api/tasks")
+            .header("origin", "https://app.example.com")
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()["access-control-allow-origin"],
+            "https://app.example.com"
+        );
+        assert_eq!(res.headers()["access-control-allow-credentials"], "true");
+
+        for method in ["GET", "OPTIONS"] {
+            let res = warp::test::request()
+                .method(method)
+                .path("/api/tasks")
+                .header("origin", "https://evil.example.com")
+                .header("access-control-request-method", "GET")
+                .reply(&route)
+                .await;
+            assert_eq!(res.status(), StatusCode::FORBIDDEN, "{}", method);
+            assert!(res.headers().get("access-control-allow-origin").is_none());
+        }
+
+        // No Origin header means same-origin, which CORS doesn't restrict
+        let res = warp::test::request().path("/api/tasks").reply(&route).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_accept_encoding_negotiation() {
+        assert_eq!(
+            Encoding::from_accept_encoding("gzip, deflate, br"),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            Encoding::from_accept_encoding("gzip;q=0.5, deflate"),
+            Some(Encoding::Deflate)
+        );
+        assert_eq!(Encoding::from_accept_encoding("*"), Some(Encoding::Gzip));
+        assert_eq!(Encoding::from_accept_encoding("br, identity"), None);
+        assert_eq!(Encoding::from_accept_encoding("gzip;q=0"), None);
+    }
+}
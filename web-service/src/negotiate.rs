@@ -0,0 +1,112 @@
+// web-service/src/negotiate.rs
+// Content negotiation: pick JSON or HTML from the request's Accept header
+
+use warp::{Filter, Rejection};
+
+/// A representation a negotiated route can respond with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Html,
+}
+
+/// Rejection for an `Accept` header that allows neither format (406).
+#[derive(Debug)]
+pub struct NotAcceptable;
+
+impl warp::reject::Reject for NotAcceptable {}
+
+/// Split an `Accept`-style header (`Accept`, `Accept-Encoding`, ...) into
+/// its lowercased values and their `q` weights, in header order.
+///
+/// A missing or unparseable `q` counts as 1.0. Entries with `q=0` are
+/// kept; callers skip them as "not acceptable".
+pub fn weighted(header: &str) -> impl Iterator<Item = (String, f32)> + '_ {
+    header.split(',').map(|item| {
+        let mut parts = item.split(';');
+        let value = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+        let q = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        (value, q)
+    })
+}
+
+impl Format {
+    /// Choose a format for an `Accept` header value.
+    ///
+    /// The acceptable media range with the highest `q` wins; on a tie, an
+    /// exact type beats a wildcard, then the earlier entry wins. `*/*` and
+    /// `application/*` mean JSON, `text/*` means HTML. Returns `None` if
+    /// nothing listed is acceptable.
+    pub fn from_accept(accept: &str) -> Option<Format> {
+        let mut best: Option<((f32, bool), Format)> = None;
+
+        for (media, q) in weighted(accept) {
+            let format = match media.as_str() {
+                "application/json" | "application/*" | "*/*" => Format::Json,
+                "text/html" | "text/*" => Format::Html,
+                _ => continue,
+            };
+            let rank = (q, !media.ends_with("/*"));
+
+            if q <= 0.0 {
+                continue;
+            }
+            match best {
+                Some((best_rank, _)) if best_rank >= rank => {}
+                _ => best = Some((rank, format)),
+            }
+        }
+
+        best.map(|(_, format)| format)
+    }
+}
+
+/// Extract the response `Format` from the `Accept` header.
+///
+/// A request without the header gets JSON, since API clients often leave
+/// it out. Rejects with `NotAcceptable` if the header rules out both.
+pub fn accept() -> impl Filter<Extract = (Format,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("accept").and_then(|accept: Option<String>| async move {
+        match accept {
+            None => Ok(Format::Json),
+            Some(accept) => {
+                Format::from_accept(&accept).ok_or_else(|| warp::reject::custom(NotAcceptable))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_accept_prefers_quality_then_specificity() {
+        let browser = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+        assert_eq!(Format::from_accept(browser), Some(Format::Html));
+        assert_eq!(
+            Format::from_accept("text/html;q=0.5, application/json"),
+            Some(Format::Json)
+        );
+        assert_eq!(Format::from_accept("*/*, text/html"), Some(Format::Html));
+        assert_eq!(Format::from_accept("*/*"), Some(Format::Json));
+        assert_eq!(Format::from_accept("image/png"), None);
+        assert_eq!(Format::from_accept("application/json;q=0"), None);
+    }
+
+    #[test]
+    fn test_weighted_defaults_q_and_normalizes_case() {
+        let items: Vec<_> = weighted("Text/HTML, gzip;q=0.5, br;q=oops").collect();
+        assert_eq!(
+            items,
+            vec![
+                ("text/html".to_string(), 1.0),
+                ("gzip".to_string(), 0.5),
+                ("br".to_string(), 1.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+// web-service/src/openapi.rs
+// OpenAPI spec for the JSON API, plus Swagger UI to browse it
+//
+// Paths come from the #[utoipa::path] annotations in handlers.rs; keep
+// their `responses` in step with AppError::status_code.
+
+use std::sync::Arc;
+
+use shared::search::SearchHit;
+use shared::{
+    CreateTask, CreateUser, ErrorResponse, LoginRequest, LoginResponse, Task, TaskPriority,
+    TaskStatus, UpdateTask, UserResponse,
+};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::Config;
+use warp::http::{StatusCode, Uri};
+use warp::path::{FullPath, Tail};
+use warp::{Rejection, Reply};
+
+use crate::handlers;
+
+/// Where the spec is served; Swagger UI loads it from here.
+pub const SPEC_PATH: &str = "/api/openapi.json";
+
+/// The generated spec. Call `ApiDoc::openapi()` to build it.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Task Manager API"),
+    paths(
+        handlers::register,
+        handlers::login,
+        handlers::list_tasks,
+        handlers::search_tasks,
+        handlers::get_task,
+        handlers::create_task,
+        handlers::create_tasks_batch,
+        handlers::update_task,
+        handlers::delete_task,
+    ),
+    components(schemas(
+        Task,
+        TaskStatus,
+        TaskPriority,
+        CreateTask,
+        UpdateTask,
+        CreateUser,
+        LoginRequest,
+        LoginResponse,
+        UserResponse,
+        ErrorResponse,
+        SearchHit,
+        handlers::BatchItemResult,
+        handlers::BatchOutcome,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Accounts and tokens"),
+        (name = "tasks", description = "The authenticated user's tasks"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the `bearer` scheme the task paths' `security` refers to.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+        }
+    }
+}
+
+/// GET /api/openapi.json - the spec as JSON.
+pub async fn spec() -> Result<impl Reply, Rejection> {
+    Ok(warp::reply::json(&ApiDoc::openapi()))
+}
+
+/// GET /api/docs/* - Swagger UI's page and assets.
+///
+/// `/api/docs` redirects to `/api/docs/` so the page's relative asset
+/// URLs resolve under it.
+pub async fn swagger_ui(
+    full_path: FullPath,
+    tail: Tail,
+    config: Arc<Config<'static>>,
+) -> Result<Box<dyn Reply>, Rejection> {
+    if full_path.as_str() == "/api/docs" {
+        return Ok(Box::new(warp::redirect::found(Uri::from_static(
+            "/api/docs/",
+        ))));
+    }
+
+    match utoipa_swagger_ui::serve(tail.as_str(), config) {
+        Ok(Some(file)) => Ok(Box::new(warp::reply::with_header(
+            file.bytes.into_owned(),
+            "content-type",
+            file.content_type,
+        ))),
+        Ok(None) => Err(warp::reject::not_found()),
+        Err(err) => Ok(Box::new(warp::reply::with_status(
+            err.to_string(),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_spec_documents_create_task() {
+        let spec: Value = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        let post = &spec["paths"]["/api/tasks"]["post"];
+        assert!(post.is_object(), "POST /api/tasks missing from the spec");
+        for status in ["201", "400"] {
+            assert!(
+                post["responses"][status].is_object(),
+                "missing {} response",
+                status
+            );
+        }
+        assert_eq!(
+            post["responses"]["201"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/Task"
+        );
+        assert!(spec["components"]["securitySchemes"]["bearer"].is_object());
+    }
+}
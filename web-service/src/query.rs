@@ -0,0 +1,163 @@
+// web-service/src/query.rs
+// Query-string parameters for the JSON task API
+
+use serde::Deserialize;
+use shared::constants::DEFAULT_PAGE_SIZE;
+use shared::{AppError, AppResult, TaskFilter, TaskPriority};
+use utoipa::IntoParams;
+
+/// Query params accepted by GET /api/tasks, e.g.
+/// `?status=done&priority=high,urgent&limit=20&offset=0&sort=due_date_asc`.
+///
+/// `priority` takes a comma-separated list and matches any of them.
+///
+/// Enum values arrive as plain strings and are parsed with their `FromStr`
+/// impls, so a bad value becomes an `AppError::Validation` (400) that
+/// names the offending value. Non-numeric `limit`/`offset` are rejected
+/// by `warp::query()` itself, which also maps to 400.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TaskQuery {
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    pub sort: Option<String>,
+    pub search: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl TaskQuery {
+    /// Build the repository filter for `user_id`'s tasks.
+    ///
+    /// Absent params fall back to the defaults: every status and
+    /// priority, newest first, `DEFAULT_PAGE_SIZE` rows from the start.
+    /// The limit itself is range-checked by the repository.
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `status`, `priority`, or `sort` is unknown
+    pub fn into_filter(self, user_id: i64) -> AppResult<TaskFilter> {
+        let mut filter = TaskFilter::new(user_id).page(
+            self.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+            self.offset.unwrap_or(0),
+        );
+
+        if let Some(status) = self.status {
+            filter = filter.status(status.parse()?);
+        }
+        if let Some(priority) = self.priority {
+            filter = filter.priorities(parse_priorities(&priority)?);
+        }
+        if let Some(sort) = self.sort {
+            filter = filter.sort(sort.parse()?);
+        }
+        if let Some(search) = self.search.filter(|s| !s.is_empty()) {
+            filter = filter.search(search);
+        }
+
+        Ok(filter)
+    }
+}
+
+/// Parse a comma-separated priority list like `high,urgent`.
+///
+/// Blank entries are skipped, so an empty value means no priority filter.
+///
+/// # Errors
+/// * `AppError::Validation` - If any entry isn't a known priority
+fn parse_priorities(value: &str) -> AppResult<Vec<TaskPriority>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Query params accepted by GET /api/tasks/search, e.g. `?q=budget&limit=10`.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    /// Text to look for in titles and descriptions
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+impl SearchQuery {
+    /// Build the repository filter for `user_id`'s tasks mentioning `q`.
+    ///
+    /// Surrounding whitespace in `q` is ignored. Returns up to `limit`
+    /// tasks (default `DEFAULT_PAGE_SIZE`), newest first.
+    ///
+    /// # Errors
+    /// * `AppError::Validation` - If `q` is blank
+    pub fn into_filter(self, user_id: i64) -> AppResult<TaskFilter> {
+        let q = self.q.trim();
+        if q.is_empty() {
+            return Err(AppError::Validation(
+                "Search text cannot be empty".to_string(),
+            ));
+        }
+
+        Ok(TaskFilter::new(user_id)
+            .search(q)
+            .page(self.limit.unwrap_or(DEFAULT_PAGE_SIZE), 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{TaskPriority, TaskSort, TaskStatus};
+
+    #[test]
+    fn test_defaults_when_params_absent() {
+        let filter = TaskQuery::default().into_filter(7).unwrap();
+
+        assert_eq!(filter.user_id, 7);
+        assert_eq!(filter.status, None);
+        assert_eq!(filter.sort, TaskSort::CreatedDesc);
+        assert_eq!(filter.limit, Some(DEFAULT_PAGE_SIZE));
+        assert_eq!(filter.offset, Some(0));
+    }
+
+    #[test]
+    fn test_parses_every_param() {
+        let query: TaskQuery = serde_urlencoded::from_str(
+            "status=done&priority=high&limit=20&offset=40&sort=due_date_asc",
+        )
+        .unwrap();
+        let filter = query.into_filter(1).unwrap();
+
+        assert_eq!(filter.status, Some(TaskStatus::Done));
+        assert_eq!(filter.priorities, [TaskPriority::High]);
+        assert_eq!(filter.sort, TaskSort::DueDateAsc);
+        assert_eq!(filter.limit, Some(20));
+        assert_eq!(filter.offset, Some(40));
+    }
+
+    #[test]
+    fn test_parses_priority_list() {
+        let query: TaskQuery = serde_urlencoded::from_str("priority=high,%20urgent").unwrap();
+        let filter = query.into_filter(1).unwrap();
+        assert_eq!(
+            filter.priorities,
+            [TaskPriority::High, TaskPriority::Urgent]
+        );
+
+        let query: TaskQuery = serde_urlencoded::from_str("priority=").unwrap();
+        assert!(query.into_filter(1).unwrap().priorities.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_values_are_validation_errors() {
+        for query in [
+            "status=bogus",
+            "priority=critical",
+            "priority=high,critical",
+            "sort=sideways",
+        ] {
+            let query: TaskQuery = serde_urlencoded::from_str(query).unwrap();
+            assert!(query.into_filter(1).unwrap_err().is_validation());
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// web-service/src/rate_limit.rs
+// Per-client token-bucket rate limiting for the API, UI, and WebSocket
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use shared::{AppError, AppResult};
+use warp::{Filter, Rejection};
+
+use crate::error::reject;
+
+/// Once this many clients are tracked, full buckets are dropped so idle
+/// clients don't accumulate forever.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Who a request is counted against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    /// An authenticated user, from a valid token
+    User(i64),
+    /// Anyone else, by client IP
+    Ip(IpAddr),
+    /// No token and no known address (e.g. in-process tests)
+    Unknown,
+}
+
+/// A single client's bucket.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token-bucket rate limiter shared across all requests.
+///
+/// Each client may burst up to `per_minute` requests, and tokens refill
+/// continuously at `per_minute / 60` per second - so a client that waits
+/// one second gets back a second's worth of requests rather than
+/// everything resetting at the top of the minute.
+///
+/// Cloning is cheap; clones share the same buckets.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Arc<Mutex<HashMap<ClientKey, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// Allow each client `per_minute` requests per minute.
+    pub fn per_minute(per_minute: u32) -> Self {
+        let capacity = f64::from(per_minute.max(1));
+        Self {
+            capacity,
+            refill_per_sec: capacity / 60.0,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Take one token for `key` at time `now`.
+    ///
+    /// Returns how long to wait before retrying if the bucket is empty.
+    pub fn check(&self, key: ClientKey, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= PRUNE_THRESHOLD {
+            let (capacity, rate) = (self.capacity, self.refill_per_sec);
+            buckets.retain(|_, b| refilled(b, now, capacity, rate) < capacity);
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.capacity,
+            last_refill: now,
+        });
+        bucket.tokens = refilled(bucket, now, self.capacity, self.refill_per_sec);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_sec))
+        }
+    }
+
+    /// Take one token for `key` now.
+    ///
+    /// # Errors
+    /// * `AppError::RateLimited` - If the bucket is empty, with the whole
+    ///   seconds to wait
+    pub fn take(&self, key: ClientKey) -> AppResult<()> {
+        self.check(key, Instant::now())
+            .map_err(|wait| AppError::RateLimited(wait.as_secs_f64().ceil() as u64))
+    }
+}
+
+/// Token count after refilling from `bucket.last_refill` up to `now`.
+fn refilled(bucket: &Bucket, now: Instant, capacity: f64, refill_per_sec: f64) -> f64 {
+    let elapsed = now.saturating_duration_since(bucket.last_refill);
+    (bucket.tokens + elapsed.as_secs_f64() * refill_per_sec).min(capacity)
+}
+
+/// Reject clients that exceed their budget with 429 Too Many Requests,
+/// counting every request per client IP.
+///
+/// For routes that don't take a token, like login and register; routes
+/// behind an auth filter use [`rate_limited`] instead.
+pub fn rate_limit(limiter: RateLimiter) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and_then(move |addr: Option<SocketAddr>| {
+            let limiter = limiter.clone();
+            async move { limiter.take(ip_key(addr)).map_err(reject) }
+        })
+        .untuple_one()
+}
+
+/// Rate-limit the requests an auth filter lets through, per user.
+///
+/// Wraps one of the `auth` filters and counts the request against the
+/// user it authenticated, so the token is only verified once. Requests it
+/// rejects are counted per client IP, then rejected as before, so
+/// guessing tokens is limited too. Over the budget is 429 either way.
+pub fn rate_limited<A>(
+    auth: A,
+    limiter: RateLimiter,
+) -> impl Filter<Extract = (i64,), Error = Rejection> + Clone
+where
+    A: Filter<Extract = (i64,), Error = Rejection> + Clone + Send + Sync + 'static,
+{
+    auth.map(Ok)
+        .or_else(|rejection| async move { Ok::<_, Rejection>((Err(rejection),)) })
+        .and(warp::addr::remote())
+        .and_then(
+            move |user: Result<i64, Rejection>, addr: Option<SocketAddr>| {
+                let limiter = limiter.clone();
+                async move {
+                    let key = match user {
+                        Ok(user_id) => ClientKey::User(user_id),
+                        Err(_) => ip_key(addr),
+                    };
+                    limiter.take(key).map_err(reject)?;
+                    user
+                }
+            },
+        )
+}
+
+/// The bucket for a request nobody has authenticated.
+fn ip_key(addr: Option<SocketAddr>) -> ClientKey {
+    addr.map_or(ClientKey::Unknown, |addr| ClientKey::Ip(addr.ip()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::with_auth;
+    use crate::error::handle_rejection;
+    use chrono::Duration as ChronoDuration;
+    use shared::auth::issue_token;
+    use warp::http::StatusCode;
+
+    #[test]
+    fn test_bucket_rejects_after_limit_then_refills() {
+        let limiter = RateLimiter::per_minute(3);
+        let start = Instant::now();
+        let key = ClientKey::User(1);
+
+        for _ in 0..3 {
+            assert!(limiter.check(key.clone(), start).is_ok());
+        }
+        let wait = limiter.check(key.clone(), start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(20));
+
+        // Other clients have their own bucket
+        assert!(limiter.check(ClientKey::User(2), start).is_ok());
+
+        // One token refills every 20 seconds at 3/minute
+        let later = start + Duration::from_secs(20);
+        assert!(limiter.check(key.clone(), later).is_ok());
+        assert!(limiter.check(key, later).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filter_returns_429_when_exceeded() {
+        let secret: Arc<str> = Arc::from("test-secret");
+        let route = rate_limited(with_auth(secret.clone()), RateLimiter::per_minute(2))
+            .map(|user_id: i64| user_id.to_string())
+            .recover(handle_rejection);
+        let token = issue_token(7, &secret, ChronoDuration::hours(1)).unwrap();
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let res = warp::test::request()
+                .header("authorization", format!("Bearer {}", token))
+                .reply(&route)
+                .await;
+            statuses.push(res.status());
+        }
+
+        assert_eq!(
+            statuses,
+            vec![
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::TOO_MANY_REQUESTS
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bad_tokens_are_counted_per_ip() {
+        let secret: Arc<str> = Arc::from("test-secret");
+        let route = rate_limited(with_auth(secret.clone()), RateLimiter::per_minute(2))
+            .map(|user_id: i64| user_id.to_string())
+            .recover(handle_rejection);
+        let addr: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+        let request = |authorization: String| {
+            warp::test::request()
+                .remote_addr(addr)
+                .header("authorization", authorization)
+                .reply(&route)
+        };
+
+        assert_eq!(
+            request("Bearer junk".to_string()).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            request("Bearer junk".to_string()).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            request("Bearer junk".to_string()).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // A valid token from the same address has its own bucket
+        let token = issue_token(7, &secret, ChronoDuration::hours(1)).unwrap();
+        let res = request(format!("Bearer {}", token)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().as_ref(), b"7");
+    }
+}
@@ -0,0 +1,1176 @@
+// web-service/src/routes.rs
+// Route filters for the health check and JSON task API
+//
+// Filters only describe *what* a request looks like (method, path, body);
+// the actual work happens in handlers.rs.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use metrics_exporter_prometheus::PrometheusHandle;
+use serde::de::DeserializeOwned;
+use shared::config::LockoutConfig;
+use shared::db::ensure_writable;
+use shared::{DbPool, SqlxTaskRepository, TaskStore};
+use utoipa_swagger_ui::Config;
+use warp::{Filter, Rejection, Reply};
+
+use crate::auth::{require_ownership, with_auth, with_query_auth, with_session_auth};
+use crate::error::reject;
+use crate::events::TaskEvents;
+use crate::handlers;
+use crate::middleware::compressed;
+use crate::negotiate::accept;
+use crate::openapi;
+use crate::query::{SearchQuery, TaskQuery};
+use crate::rate_limit::{rate_limit, rate_limited, RateLimiter};
+use crate::ws;
+
+/// GET /health - service and database health as JSON
+pub fn health(pool: DbPool) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("health")
+        .and(warp::get())
+        .and(with_pool(pool))
+        .and_then(handlers::health)
+}
+
+/// GET /metrics - Prometheus scrape endpoint
+///
+/// `handle` comes from `telemetry::prometheus()`, installed at startup.
+pub fn metrics(
+    pool: DbPool,
+    handle: PrometheusHandle,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("metrics")
+        .and(warp::get())
+        .and(with_pool(pool))
+        .and(warp::any().map(move || handle.clone()))
+        .and_then(handlers::metrics)
+}
+
+/// GET /api/openapi.json (the OpenAPI spec) and GET /api/docs (Swagger UI)
+///
+/// Public, like the docs of any API: the spec holds no user data.
+pub fn api_docs() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let config = Arc::new(Config::from(openapi::SPEC_PATH));
+
+    let spec = warp::path!("api" / "openapi.json")
+        .and(warp::get())
+        .and_then(openapi::spec);
+
+    let ui = warp::path!("api" / "docs" / ..)
+        .and(warp::get())
+        .and(warp::path::full())
+        .and(warp::path::tail())
+        .and(warp::any().map(move || config.clone()))
+        .and_then(openapi::swagger_ui);
+
+    spec.or(ui)
+}
+
+/// Account routes: POST /api/register and POST /api/login.
+///
+/// These are the only API routes that don't require a token, so
+/// `limiter` counts them per client IP to slow down password guessing
+/// across accounts. Repeated wrong passwords lock an account according to
+/// `lockout`. Bodies over `body_limit` bytes are rejected with 413.
+pub fn auth_routes(
+    pool: DbPool,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+    lockout: LockoutConfig,
+    body_limit: u64,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let register = warp::path!("api" / "register")
+        .and(warp::post())
+        .and(rate_limit(limiter.clone()))
+        .and(writable())
+        .and(json_body(body_limit))
+        .and(with_pool(pool.clone()))
+        .and_then(handlers::register);
+
+    let login = warp::path!("api" / "login")
+        .and(warp::post())
+        .and(rate_limit(limiter))
+        .and(json_body(body_limit))
+        .and(with_pool(pool))
+        .and(warp::any().map(move || jwt_secret.clone()))
+        .and(warp::any().map(move || lockout))
+        .and_then(handlers::login);
+
+    register.or(login)
+}
+
+/// All task API routes under `/api/tasks`.
+///
+/// Every route requires a bearer token; handlers only ever see the
+/// authenticated user's own tasks. Requests are rate-limited per user by
+/// `limiter` once the token checks out. Changes are published to
+/// `events` for the SSE stream. Large responses are compressed when the
+/// client accepts it, and request bodies over `body_limit` bytes are
+/// rejected with 413.
+pub fn task_routes(
+    pool: DbPool,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+    events: TaskEvents,
+    body_limit: u64,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let store = SqlxTaskRepository::new(pool.clone());
+    let routes = list_tasks(pool.clone(), jwt_secret.clone(), limiter.clone())
+        .or(task_events(
+            events.clone(),
+            jwt_secret.clone(),
+            limiter.clone(),
+        ))
+        .or(search_tasks(pool, jwt_secret.clone(), limiter.clone()))
+        .or(get_task(store.clone(), jwt_secret.clone(), limiter.clone()))
+        .or(create_tasks_batch(
+            store.clone(),
+            jwt_secret.clone(),
+            limiter.clone(),
+            events.clone(),
+            body_limit,
+        ))
+        .or(create_task(
+            store.clone(),
+            jwt_secret.clone(),
+            limiter.clone(),
+            events.clone(),
+            body_limit,
+        ))
+        .or(update_task(
+            store.clone(),
+            jwt_secret.clone(),
+            limiter.clone(),
+            events.clone(),
+            body_limit,
+        ))
+        .or(delete_task(store, jwt_secret, limiter, events));
+
+    compressed(routes)
+}
+
+/// GET /ws?token=... - two-way task sync over a WebSocket.
+///
+/// The token goes in the query string because browsers can't set headers
+/// on a WebSocket handshake. The handshake and each command count against
+/// `limiter` like a REST request. See `ws::session` for the message protocol.
+pub fn websocket(
+    pool: DbPool,
+    jwt_secret: Arc<str>,
+    events: TaskEvents,
+    limiter: RateLimiter,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("ws")
+        .and(warp::ws())
+        .and(rate_limited(with_query_auth(jwt_secret), limiter.clone()))
+        .and(with_pool(pool))
+        .and(with_events(events))
+        .map(
+            move |upgrade: warp::ws::Ws, user_id: i64, pool: DbPool, events: TaskEvents| {
+                let limiter = limiter.clone();
+                upgrade
+                    .on_upgrade(move |socket| ws::session(socket, user_id, pool, events, limiter))
+            },
+        )
+}
+
+/// GET /api/tasks with optional `TaskQuery` params
+///
+/// Responds with JSON or HTML depending on the `Accept` header, and 406
+/// if it allows neither.
+fn list_tasks(
+    pool: DbPool,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("api" / "tasks")
+        .and(warp::get())
+        .and(rate_limited(with_auth(jwt_secret), limiter))
+        .and(accept())
+        .and(warp::query::<TaskQuery>())
+        .and(with_pool(pool))
+        .and_then(handlers::list_tasks)
+}
+
+/// GET /api/tasks/events - Server-Sent Events stream of task changes
+///
+/// Accepts the bearer header or the UI's `token` cookie, since browsers'
+/// `EventSource` can't set headers.
+fn task_events(
+    events: TaskEvents,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let auth = with_auth(jwt_secret.clone())
+        .or(with_session_auth(jwt_secret))
+        .unify();
+    warp::path!("api" / "tasks" / "events")
+        .and(warp::get())
+        .and(rate_limited(auth, limiter))
+        .and(with_events(events))
+        .and_then(handlers::task_events)
+}
+
+/// GET /api/tasks/search?q=... - tasks mentioning `q`, with snippets
+fn search_tasks(
+    pool: DbPool,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("api" / "tasks" / "search")
+        .and(warp::get())
+        .and(rate_limited(with_auth(jwt_secret), limiter))
+        .and(warp::query::<SearchQuery>())
+        .and(with_pool(pool))
+        .and_then(handlers::search_tasks)
+}
+
+/// GET /api/tasks/:id, honoring `If-None-Match`
+fn get_task<S: TaskStore + Clone + 'static>(
+    store: S,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let ids = warp::path!("api" / "tasks" / i64)
+        .and(warp::get())
+        .and(rate_limited(with_auth(jwt_secret), limiter));
+    require_ownership(ids, store.clone())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_store(store))
+        .and_then(handlers::get_task::<S>)
+}
+
+/// POST /api/tasks with a JSON `CreateTask` body
+fn create_task<S: TaskStore + Clone + 'static>(
+    store: S,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+    events: TaskEvents,
+    body_limit: u64,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("api" / "tasks")
+        .and(warp::post())
+        .and(rate_limited(with_auth(jwt_secret), limiter))
+        .and(writable())
+        .and(json_body(body_limit))
+        .and(with_store(store))
+        .and(with_events(events))
+        .and_then(handlers::create_task::<S>)
+}
+
+/// POST /api/tasks/batch with a JSON array of `CreateTask`
+fn create_tasks_batch<S: TaskStore + Clone + 'static>(
+    store: S,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+    events: TaskEvents,
+    body_limit: u64,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("api" / "tasks" / "batch")
+        .and(warp::post())
+        .and(rate_limited(with_auth(jwt_secret), limiter))
+        .and(writable())
+        .and(json_body(body_limit))
+        .and(with_store(store))
+        .and(with_events(events))
+        .and_then(handlers::create_tasks_batch::<S>)
+}
+
+/// PUT /api/tasks/:id with a JSON `UpdateTask` body
+fn update_task<S: TaskStore + Clone + 'static>(
+    store: S,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+    events: TaskEvents,
+    body_limit: u64,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let ids = warp::path!("api" / "tasks" / i64)
+        .and(warp::put())
+        .and(rate_limited(with_auth(jwt_secret), limiter));
+    require_ownership(ids, store.clone())
+        .and(writable())
+        .and(json_body(body_limit))
+        .and(with_store(store))
+        .and(with_events(events))
+        .and_then(handlers::update_task::<S>)
+}
+
+/// DELETE /api/tasks/:id
+fn delete_task<S: TaskStore + Clone + 'static>(
+    store: S,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+    events: TaskEvents,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let ids = warp::path!("api" / "tasks" / i64)
+        .and(warp::delete())
+        .and(rate_limited(with_auth(jwt_secret), limiter));
+    require_ownership(ids, store.clone())
+        .and(writable())
+        .and(with_store(store))
+        .and(with_events(events))
+        .and_then(handlers::delete_task::<S>)
+}
+
+/// Deserialize a JSON body of at most `limit` bytes.
+///
+/// Larger bodies are rejected with 413 before any of them is read, and
+/// bodies without a `Content-Length` with 411.
+pub(crate) fn json_body<T: DeserializeOwned + Send>(
+    limit: u64,
+) -> impl Filter<Extract = (T,), Error = Rejection> + Clone {
+    warp::body::content_length_limit(limit).and(warp::body::json())
+}
+
+/// Reject with 503 while the service is in read-only mode.
+///
+/// Lets write routes fail before reading the body; the repositories
+/// check again, so routes without this are still covered.
+fn writable() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::any()
+        .and_then(|| async { ensure_writable().map_err(reject) })
+        .untuple_one()
+}
+
+/// Inject a clone of the connection pool into a handler.
+///
+/// Cloning a pool is cheap - it's an `Arc` internally.
+fn with_pool(pool: DbPool) -> impl Filter<Extract = (DbPool,), Error = Infallible> + Clone {
+    warp::any().map(move || pool.clone())
+}
+
+/// Inject a clone of a task store into a handler.
+fn with_store<S: TaskStore + Clone + 'static>(
+    store: S,
+) -> impl Filter<Extract = (S,), Error = Infallible> + Clone {
+    warp::any().map(move || store.clone())
+}
+
+/// Inject a clone of the task event publisher into a handler.
+fn with_events(
+    events: TaskEvents,
+) -> impl Filter<Extract = (TaskEvents,), Error = Infallible> + Clone {
+    warp::any().map(move || events.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::handle_rejection;
+    use chrono::Duration;
+    use shared::auth::issue_token;
+    use shared::constants::MAX_BODY_BYTES;
+    use shared::db::{
+        create_test_pool, create_test_task, create_test_user, new_test_task, MockTaskStore,
+    };
+    use shared::{Task, TaskRepository};
+    use warp::http::StatusCode;
+
+    const SECRET: &str = "test-secret";
+
+    fn bearer(user_id: i64) -> String {
+        format!(
+            "Bearer {}",
+            issue_token(user_id, SECRET, Duration::hours(1)).unwrap()
+        )
+    }
+
+    #[tokio::test]
+    async fn test_users_only_see_their_own_tasks() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        create_test_task(&pool, alice, "Alice's task")
+            .await
+            .unwrap();
+        let bobs = create_test_task(&pool, bob, "Bob's task").await.unwrap();
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .path("/api/tasks")
+            .header("authorization", bearer(alice))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let tasks: Vec<Task> = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Alice's task");
+
+        let res = warp::test::request()
+            .path(&format!("/api/tasks/{}", bobs.id))
+            .header("authorization", bearer(alice))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "forbidden");
+
+        for method in ["PUT", "DELETE"] {
+            let res = warp::test::request()
+                .method(method)
+                .path(&format!("/api/tasks/{}", bobs.id))
+                .header("authorization", bearer(alice))
+                .json(&serde_json::json!({"title": "Mine now"}))
+                .reply(&api)
+                .await;
+            assert_eq!(res.status(), StatusCode::FORBIDDEN, "{}", method);
+        }
+
+        // A task nobody owns is still just missing
+        let res = warp::test::request()
+            .path(&format!("/api/tasks/{}", bobs.id + 100))
+            .header("authorization", bearer(alice))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_past_due_date_allowed_on_update_only() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let task = create_test_task(&pool, alice, "Forgotten").await.unwrap();
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+        let yesterday = chrono::Utc::now() - Duration::days(1);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/tasks")
+            .header("authorization", bearer(alice))
+            .json(&serde_json::json!({
+                "title": "Already late",
+                "description": "",
+                "status": "todo",
+                "priority": "low",
+                "due_date": yesterday
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = warp::test::request()
+            .method("PUT")
+            .path(&format!("/api/tasks/{}", task.id))
+            .header("authorization", bearer(alice))
+            .json(&serde_json::json!({"due_date": yesterday}))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let updated: Task = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(
+            updated.due_date.map(|due| due.timestamp()),
+            Some(yesterday.timestamp())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_tasks_query_params() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        for (title, status, priority) in [
+            ("Shipped", "done", "high"),
+            ("Also shipped", "done", "high"),
+            ("Low and done", "done", "low"),
+            ("Still open", "todo", "high"),
+        ] {
+            let task = create_test_task(&pool, alice, title).await.unwrap();
+            TaskRepository::update(
+                &pool,
+                task.id,
+                task.user_id,
+                shared::UpdateTask::builder()
+                    .status(status.parse().unwrap())
+                    .priority(priority.parse().unwrap())
+                    .build(),
+            )
+            .await
+            .unwrap();
+        }
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .path("/api/tasks?status=done&priority=high&limit=20&offset=0&sort=created_asc")
+            .header("authorization", bearer(alice))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let tasks: Vec<Task> = serde_json::from_slice(res.body()).unwrap();
+        let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["Shipped", "Also shipped"]);
+
+        for query in ["status=bogus", "sort=sideways", "limit=abc", "limit=0"] {
+            let res = warp::test::request()
+                .path(&format!("/api/tasks?{}", query))
+                .header("authorization", bearer(alice))
+                .reply(&api)
+                .await;
+            assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", query);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_returns_snippets() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let in_description = create_test_task(&pool, alice, "Plan the quarter")
+            .await
+            .unwrap();
+        TaskRepository::update(
+            &pool,
+            in_description.id,
+            in_description.user_id,
+            shared::UpdateTask::builder()
+                .description("Agree the budget with finance")
+                .build(),
+        )
+        .await
+        .unwrap();
+        create_test_task(&pool, alice, "Budget review")
+            .await
+            .unwrap();
+        create_test_task(&pool, alice, "Unrelated").await.unwrap();
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .path("/api/tasks/search?q=budget")
+            .header("authorization", bearer(alice))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let hits: Vec<shared::search::SearchHit> = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(hits.len(), 2);
+        let snippet_of = |title: &str| {
+            hits.iter()
+                .find(|hit| hit.task.title == title)
+                .map(|hit| hit.snippet.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            snippet_of("Plan the quarter"),
+            "Agree the <mark>budget</mark> with finance"
+        );
+        // Only the title matched
+        assert_eq!(snippet_of("Budget review"), "");
+
+        let res = warp::test::request()
+            .path("/api/tasks/search?q=%20")
+            .header("authorization", bearer(alice))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_list_tasks_negotiates_content_type() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        create_test_task(&pool, alice, "Negotiated").await.unwrap();
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+        let list = |accept: &'static str| {
+            warp::test::request()
+                .path("/api/tasks")
+                .header("authorization", bearer(alice))
+                .header("accept", accept)
+        };
+
+        let res = list("application/json").reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "application/json");
+        assert_eq!(res.headers()["vary"], "accept");
+        let tasks: Vec<Task> = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(tasks[0].title, "Negotiated");
+
+        let res = list("text/html").reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "text/html; charset=utf-8");
+        assert!(String::from_utf8_lossy(res.body()).contains("Negotiated"));
+
+        let res = list("image/png").reply(&api).await;
+        assert_eq!(res.status(), StatusCode::NOT_ACCEPTABLE);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "not_acceptable");
+    }
+
+    #[tokio::test]
+    async fn test_large_responses_are_gzipped() {
+        use std::io::Read;
+
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let mut small = None;
+        for i in 0..30 {
+            small = Some(
+                create_test_task(&pool, alice, &format!("Task number {}", i))
+                    .await
+                    .unwrap(),
+            );
+        }
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .path("/api/tasks")
+            .header("authorization", bearer(alice))
+            .header("accept-encoding", "gzip, deflate")
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-encoding"], "gzip");
+        let mut json = String::new();
+        flate2::read::GzDecoder::new(&res.body()[..])
+            .read_to_string(&mut json)
+            .unwrap();
+        let tasks: Vec<Task> = serde_json::from_str(&json).unwrap();
+        assert_eq!(tasks.len(), 30);
+
+        // A single task is under the threshold, and no header means no gzip
+        let res = warp::test::request()
+            .path(&format!("/api/tasks/{}", small.unwrap().id))
+            .header("authorization", bearer(alice))
+            .header("accept-encoding", "gzip")
+            .reply(&api)
+            .await;
+        assert!(res.headers().get("content-encoding").is_none());
+        let res = warp::test::request()
+            .path("/api/tasks")
+            .header("authorization", bearer(alice))
+            .reply(&api)
+            .await;
+        assert!(res.headers().get("content-encoding").is_none());
+        assert_eq!(
+            serde_json::from_slice::<Vec<Task>>(res.body())
+                .unwrap()
+                .len(),
+            30
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_routes_require_token() {
+        let pool = create_test_pool().await.unwrap();
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request().path("/api/tasks/1").reply(&api).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_create_publishes_event() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let events = TaskEvents::new();
+        let mut rx = events.subscribe();
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            events,
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/tasks")
+            .header("authorization", bearer(alice))
+            .json(&serde_json::json!({
+                "title": "Live",
+                "description": "",
+                "status": "todo",
+                "priority": "low",
+                "due_date": null
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.kind, crate::events::TaskEventKind::Created);
+        assert_eq!(event.user_id, alice);
+        assert_eq!(event.task.unwrap().title, "Live");
+    }
+
+    #[tokio::test]
+    async fn test_batch_create_reports_each_item() {
+        let store = MockTaskStore::new();
+        let api = create_tasks_batch(
+            store.clone(),
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+        let item = |title: &str| {
+            serde_json::json!({
+                "title": title,
+                "description": "",
+                "status": "todo",
+                "priority": "low",
+                "due_date": null
+            })
+        };
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/tasks/batch")
+            .header("authorization", bearer(1))
+            .json(&[item("First"), item(""), item("Third")])
+            .reply(&api)
+            .await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let results: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        let results = results.as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["index"], 0);
+        assert_eq!(results[0]["result"]["Ok"]["title"], "First");
+        assert_eq!(results[1]["index"], 1);
+        assert_eq!(results[1]["result"]["Err"]["code"], "validation");
+        assert!(results[1]["result"]["Err"]["fields"]["title"].is_string());
+        assert_eq!(results[2]["result"]["Ok"]["title"], "Third");
+        assert_eq!(store.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_body_over_limit_is_rejected() {
+        let store = MockTaskStore::new();
+        let api = create_task(
+            store.clone(),
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            1024,
+        )
+        .recover(handle_rejection);
+        let post = |description: String| {
+            warp::test::request()
+                .method("POST")
+                .path("/api/tasks")
+                .header("authorization", bearer(1))
+                .json(&serde_json::json!({
+                    "title": "Sized",
+                    "description": description,
+                    "status": "todo",
+                    "priority": "low",
+                    "due_date": null
+                }))
+                .reply(&api)
+        };
+
+        let res = post("x".repeat(2000)).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "payload_too_large");
+        assert!(store.is_empty());
+
+        let res = post("x".repeat(500)).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_task_crud_against_mock_store() {
+        // No pool anywhere: the handlers only ever see the in-memory store
+        let store = MockTaskStore::new();
+        let secret: Arc<str> = Arc::from(SECRET);
+        let events = TaskEvents::new();
+        let limiter = RateLimiter::per_minute(100);
+        let api = create_task(
+            store.clone(),
+            secret.clone(),
+            limiter.clone(),
+            events.clone(),
+            MAX_BODY_BYTES,
+        )
+        .or(get_task(store.clone(), secret.clone(), limiter.clone()))
+        .or(update_task(
+            store.clone(),
+            secret.clone(),
+            limiter.clone(),
+            events.clone(),
+            MAX_BODY_BYTES,
+        ))
+        .or(delete_task(store.clone(), secret, limiter, events))
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/tasks")
+            .header("authorization", bearer(1))
+            .json(&serde_json::json!({
+                "title": "Mocked",
+                "description": "",
+                "status": "todo",
+                "priority": "low",
+                "due_date": null
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let created: Task = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(created.user_id, 1);
+        let path = format!("/api/tasks/{}", created.id);
+
+        let res = warp::test::request()
+            .path(&path)
+            .header("authorization", bearer(2))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        for expected in [StatusCode::OK, StatusCode::CONFLICT] {
+            let res = warp::test::request()
+                .method("PUT")
+                .path(&path)
+                .header("authorization", bearer(1))
+                .json(&serde_json::json!({"status": "done", "version": 1}))
+                .reply(&api)
+                .await;
+            assert_eq!(res.status(), expected);
+        }
+
+        let res = warp::test::request()
+            .method("DELETE")
+            .path(&path)
+            .header("authorization", bearer(1))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_task_honors_if_none_match() {
+        let store = MockTaskStore::new();
+        let api = get_task(
+            store.clone(),
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+        )
+        .recover(handle_rejection);
+        let task = store.create(new_test_task(1, "Polled")).await.unwrap();
+        let path = format!("/api/tasks/{}", task.id);
+        let get = |if_none_match: Option<&str>| {
+            let mut req = warp::test::request()
+                .path(&path)
+                .header("authorization", bearer(1));
+            if let Some(etag) = if_none_match {
+                req = req.header("if-none-match", etag);
+            }
+            req.reply(&api)
+        };
+
+        let res = get(None).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let etag = res.headers()["etag"].to_str().unwrap().to_string();
+
+        let res = get(Some(&etag)).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert!(res.body().is_empty());
+        assert_eq!(res.headers()["etag"], etag.as_str());
+
+        store
+            .update(
+                task.id,
+                task.user_id,
+                shared::UpdateTask::builder().title("Edited").build(),
+            )
+            .await
+            .unwrap();
+        let res = get(Some(&etag)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_ne!(res.headers()["etag"], etag.as_str());
+        let fetched: Task = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(fetched.title, "Edited");
+    }
+
+    /// A span as seen by [`SpanCapture`]: name, parent's name, and fields.
+    #[derive(Debug)]
+    struct CapturedSpan {
+        name: &'static str,
+        parent: Option<&'static str>,
+        fields: String,
+    }
+
+    /// Tracing layer that records every span created while it's installed.
+    #[derive(Clone, Default)]
+    struct SpanCapture(std::sync::Arc<std::sync::Mutex<Vec<CapturedSpan>>>);
+
+    impl<S> tracing_subscriber::Layer<S> for SpanCapture
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            struct Fields(String);
+            impl tracing::field::Visit for Fields {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    self.0 += &format!("{}={:?} ", field.name(), value);
+                }
+            }
+
+            let span = ctx.span(id).unwrap();
+            let mut fields = Fields(String::new());
+            attrs.record(&mut fields);
+            self.0.lock().unwrap().push(CapturedSpan {
+                name: span.name(),
+                parent: span.parent().map(|parent| parent.name()),
+                fields: fields.0,
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_emits_nested_spans() {
+        use tracing_subscriber::prelude::*;
+
+        let capture = SpanCapture::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let api = task_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .with(crate::middleware::request_span());
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/tasks")
+            .header("authorization", bearer(alice))
+            .json(&serde_json::json!({
+                "title": "Traced",
+                "description": "",
+                "status": "todo",
+                "priority": "low",
+                "due_date": null
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        // request > create_task (handler) > create (TaskRepository)
+        let spans = capture.0.lock().unwrap();
+        let find = |name: &str, parent: Option<&str>| {
+            spans
+                .iter()
+                .find(|span| span.name == name && span.parent == parent)
+                .unwrap_or_else(|| panic!("no {} span under {:?} in {:?}", name, parent, spans))
+        };
+
+        let request = find("request", None);
+        assert!(request.fields.contains("request_id="), "{:?}", request);
+
+        let handler = find("create_task", Some("request"));
+        assert!(handler.fields.contains(&format!("user_id={}", alice)));
+
+        let repository = find("create", Some("create_task"));
+        assert!(repository.fields.contains(&format!("user_id={}", alice)));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_counts_requests() {
+        let pool = create_test_pool().await.unwrap();
+        let api = health(pool.clone())
+            .or(metrics(pool, crate::telemetry::prometheus()))
+            .with(crate::middleware::request_logging());
+
+        let res = warp::test::request().path("/health").reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request().path("/metrics").reply(&api).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+
+        let body = std::str::from_utf8(res.body()).unwrap();
+        assert!(body.contains("# HELP http_requests_total"), "{}", body);
+        assert!(
+            body.contains("# TYPE http_requests_total counter"),
+            "{}",
+            body
+        );
+        assert!(body.lines().any(
+            |line| line.starts_with("http_requests_total{") && line.contains("status=\"200\"")
+        ));
+        assert!(body.contains("db_pool_connections"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_register_then_login() {
+        let pool = create_test_pool().await.unwrap();
+        let api = auth_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            LockoutConfig::default(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/register")
+            .json(&serde_json::json!({
+                "username": "alice",
+                "password": "correct horse",
+                "email": null
+            }))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let user: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert!(user.get("password_hash").is_none());
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/api/login")
+            .json(&serde_json::json!({"username": "alice", "password": "correct horse"}))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        let token = body["token"].as_str().unwrap();
+        let claims = shared::auth::verify_token(token, SECRET).unwrap();
+        assert_eq!(claims.sub, user["id"].as_i64().unwrap());
+        assert_eq!(body["user"]["username"], "alice");
+        assert!(res.headers()["set-cookie"]
+            .to_str()
+            .unwrap()
+            .contains("HttpOnly"));
+    }
+
+    #[tokio::test]
+    async fn test_login_failures_are_indistinguishable() {
+        let pool = create_test_pool().await.unwrap();
+        shared::UserRepository::create(
+            &pool,
+            shared::CreateUser {
+                username: "alice".to_string(),
+                password: "correct horse".to_string(),
+                email: None,
+            },
+        )
+        .await
+        .unwrap();
+        let api = auth_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(100),
+            LockoutConfig::default(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let mut bodies = Vec::new();
+        for (username, password) in [("alice", "wrong password"), ("nobody", "correct horse")] {
+            let res = warp::test::request()
+                .method("POST")
+                .path("/api/login")
+                .json(&serde_json::json!({"username": username, "password": password}))
+                .reply(&api)
+                .await;
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+            bodies.push(res.body().clone());
+        }
+        assert_eq!(bodies[0], bodies[1]);
+    }
+
+    #[tokio::test]
+    async fn test_login_is_rate_limited() {
+        let pool = create_test_pool().await.unwrap();
+        let api = auth_routes(
+            pool,
+            Arc::from(SECRET),
+            RateLimiter::per_minute(2),
+            LockoutConfig::default(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let res = warp::test::request()
+                .method("POST")
+                .path("/api/login")
+                .json(&serde_json::json!({"username": "nobody", "password": "guess"}))
+                .reply(&api)
+                .await;
+            statuses.push(res.status());
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                StatusCode::UNAUTHORIZED,
+                StatusCode::UNAUTHORIZED,
+                StatusCode::TOO_MANY_REQUESTS
+            ]
+        );
+    }
+}
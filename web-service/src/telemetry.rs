@@ -0,0 +1,72 @@
+// web-service/src/telemetry.rs
+// Prometheus metrics for HTTP traffic and the database pool
+
+use std::sync::OnceLock;
+
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use shared::db::metrics::{CALLS_METRIC, DURATION_METRIC, POOL_SATURATION_METRIC};
+use shared::DbPool;
+use warp::log::Info;
+
+/// Counter of served requests, labelled by `method` and `status`.
+pub const HTTP_REQUESTS_METRIC: &str = "http_requests_total";
+/// Histogram of request latency in seconds, labelled by `method`.
+pub const HTTP_DURATION_METRIC: &str = "http_request_duration_seconds";
+/// Gauge of open database connections, idle or in use.
+pub const DB_POOL_SIZE_METRIC: &str = "db_pool_connections";
+/// Gauge of idle database connections.
+pub const DB_POOL_IDLE_METRIC: &str = "db_pool_idle_connections";
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Install the Prometheus recorder, or return the one already installed.
+///
+/// A process can only have one global recorder, so the first call
+/// installs it and every later call gets the same handle back.
+///
+/// # Panics
+/// If some other recorder was installed first.
+pub fn prometheus() -> PrometheusHandle {
+    HANDLE
+        .get_or_init(|| {
+            let handle = PrometheusBuilder::new()
+                .install_recorder()
+                .expect("failed to install Prometheus recorder");
+            describe_metrics();
+            handle
+        })
+        .clone()
+}
+
+/// Register `# HELP` text; the exporter only prints it for described metrics.
+fn describe_metrics() {
+    describe_counter!(HTTP_REQUESTS_METRIC, "HTTP requests served");
+    describe_histogram!(HTTP_DURATION_METRIC, "HTTP request latency in seconds");
+    describe_gauge!(DB_POOL_SIZE_METRIC, "Open database connections");
+    describe_gauge!(DB_POOL_IDLE_METRIC, "Idle database connections");
+    describe_gauge!(
+        POOL_SATURATION_METRIC,
+        "Fraction of the maximum database connections in use"
+    );
+    describe_counter!(CALLS_METRIC, "Task repository calls");
+    describe_histogram!(DURATION_METRIC, "Task repository call latency in seconds");
+}
+
+/// Count one served request. Called from `middleware::request_logging`.
+///
+/// The path is left out of the labels: with task IDs in it, every task
+/// would get its own time series.
+pub fn record_request(info: &Info<'_>) {
+    let method = info.method().to_string();
+    let status = info.status().as_u16().to_string();
+
+    counter!(HTTP_REQUESTS_METRIC, "method" => method.clone(), "status" => status).increment(1);
+    histogram!(HTTP_DURATION_METRIC, "method" => method).record(info.elapsed().as_secs_f64());
+}
+
+/// Refresh the pool gauges. Called on each scrape so they're never stale.
+pub fn record_pool(pool: &DbPool) {
+    gauge!(DB_POOL_SIZE_METRIC).set(pool.size() as f64);
+    gauge!(DB_POOL_IDLE_METRIC).set(pool.num_idle() as f64);
+}
@@ -0,0 +1,119 @@
+// web-service/src/tls.rs
+// Binding the server over HTTPS when a certificate is configured
+//
+// Bearer tokens and the session cookie travel with every request, so
+// anything beyond local development should set TLS_CERT and TLS_KEY.
+// Without them the server falls back to plain HTTP and says so in the log.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::Path;
+
+use futures::future::{BoxFuture, FutureExt};
+use shared::{AppError, AppResult};
+use tracing::{info, warn};
+use warp::{Filter, Rejection, Reply};
+
+/// Bind `routes` to `addr`, over HTTPS if `tls` gives a certificate and
+/// key path, and over plain HTTP otherwise.
+///
+/// Returns the address actually bound (useful with port 0) and the server
+/// future, which finishes once `shutdown` resolves and in-flight requests
+/// are done. `routes` may still reject: the CORS wrapper outside
+/// `.recover()` rejects disallowed origins, and warp answers those itself.
+///
+/// # Errors
+/// * `AppError::Internal` - If the certificate or key file doesn't exist;
+///   checked here because warp would otherwise panic while binding
+pub fn bind<F>(
+    routes: F,
+    addr: SocketAddr,
+    tls: Option<(&Path, &Path)>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> AppResult<(SocketAddr, BoxFuture<'static, ()>)>
+where
+    F: Filter<Error = Rejection> + Clone + Send + Sync + 'static,
+    F::Extract: Reply,
+{
+    let server = warp::serve(routes);
+
+    match tls {
+        Some((cert, key)) => {
+            for (var, path) in [("TLS_CERT", cert), ("TLS_KEY", key)] {
+                if !path.is_file() {
+                    return Err(AppError::Internal(format!(
+                        "{} points to {}, which is not a file",
+                        var,
+                        path.display()
+                    )));
+                }
+            }
+
+            let (addr, server) = server
+                .tls()
+                .cert_path(cert)
+                .key_path(key)
+                .bind_with_graceful_shutdown(addr, shutdown);
+            info!("🔒 Serving HTTPS with certificate {}", cert.display());
+            Ok((addr, server.boxed()))
+        }
+        None => {
+            let (addr, server) = server.bind_with_graceful_shutdown(addr, shutdown);
+            warn!("⚠️  TLS_CERT/TLS_KEY not set, serving plain HTTP; tokens are sent unencrypted");
+            Ok((addr, server.boxed()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tokio::sync::oneshot;
+
+    /// Self-signed certificate for `localhost`, only ever used by tests
+    fn test_cert() -> (PathBuf, PathBuf) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata");
+        (dir.join("localhost.crt"), dir.join("localhost.key"))
+    }
+
+    #[tokio::test]
+    async fn test_binds_with_self_signed_cert() {
+        let (cert, key) = test_cert();
+        let (stop, stopped) = oneshot::channel::<()>();
+
+        let (addr, server) = bind(
+            warp::path::end().map(|| "ok"),
+            ([127, 0, 0, 1], 0).into(),
+            Some((&cert, &key)),
+            async {
+                stopped.await.ok();
+            },
+        )
+        .unwrap();
+        let server = tokio::spawn(server);
+
+        assert_ne!(addr.port(), 0);
+        tokio::net::TcpStream::connect(addr).await.unwrap();
+
+        stop.send(()).unwrap();
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_missing_cert_file_is_an_error() {
+        let (_, key) = test_cert();
+        let missing = Path::new("does/not/exist.crt");
+
+        let err = bind(
+            warp::path::end().map(|| "ok"),
+            ([127, 0, 0, 1], 0).into(),
+            Some((missing, &key)),
+            async {},
+        )
+        .err()
+        .unwrap();
+
+        assert!(matches!(err, AppError::Internal(msg) if msg.contains("TLS_CERT")));
+    }
+}
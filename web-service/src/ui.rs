@@ -0,0 +1,333 @@
+// web-service/src/ui.rs
+// Server-rendered HTML task UI using Askama templates and HTMX
+//
+// The full page is rendered once; after that HTMX swaps in small HTML
+// fragments (a single table row) returned by the create/update/delete
+// routes, so there's no client-side JavaScript of our own.
+
+use std::sync::Arc;
+
+use askama::Template;
+use serde::Deserialize;
+use shared::{
+    AppError, CreateTask, DbPool, SqlxTaskRepository, Task, TaskPriority, TaskRepository,
+    TaskStatus, TaskSummary, UpdateTask,
+};
+use tracing::instrument;
+use warp::{Filter, Rejection, Reply};
+
+use crate::auth::{require_ownership, with_session_auth};
+use crate::error::reject;
+use crate::events::{TaskEvent, TaskEventKind, TaskEvents};
+use crate::rate_limit::{rate_limited, RateLimiter};
+
+/// Full page: the create form plus the user's task table.
+///
+/// The rows only need a few columns, so the page loads summaries rather
+/// than full tasks.
+#[derive(Template)]
+#[template(path = "tasks.html")]
+pub struct TasksTemplate {
+    pub tasks: Vec<TaskSummary>,
+}
+
+/// A single `<tr>` for one task, returned to HTMX after a change.
+#[derive(Template)]
+#[template(path = "task_row.html")]
+pub struct TaskRowTemplate {
+    pub task: Task,
+}
+
+/// Form body posted by the "Add" form.
+#[derive(Debug, Deserialize)]
+pub struct NewTaskForm {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub priority: TaskPriority,
+}
+
+/// Form body posted when the status dropdown changes.
+#[derive(Debug, Deserialize)]
+pub struct StatusForm {
+    pub status: TaskStatus,
+}
+
+/// All HTML UI routes under `/tasks`.
+///
+/// Authenticated with the `token` cookie rather than a header, since the
+/// browser sends it automatically with every HTMX request. Requests count
+/// against `limiter` per user, like the API. Form bodies over
+/// `body_limit` bytes are rejected with 413.
+pub fn ui_routes(
+    pool: DbPool,
+    jwt_secret: Arc<str>,
+    limiter: RateLimiter,
+    events: TaskEvents,
+    body_limit: u64,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let store = SqlxTaskRepository::new(pool.clone());
+    let with_pool = warp::any().map(move || pool.clone());
+    let with_events = warp::any().map(move || events.clone());
+
+    // GET /tasks - full page
+    let page = warp::path!("tasks")
+        .and(warp::get())
+        .and(rate_limited(
+            with_session_auth(jwt_secret.clone()),
+            limiter.clone(),
+        ))
+        .and(with_pool.clone())
+        .and_then(task_page);
+
+    // POST /tasks - create, respond with the new row
+    let create = warp::path!("tasks")
+        .and(warp::post())
+        .and(rate_limited(
+            with_session_auth(jwt_secret.clone()),
+            limiter.clone(),
+        ))
+        .and(warp::body::content_length_limit(body_limit))
+        .and(warp::body::form())
+        .and(with_pool.clone())
+        .and(with_events.clone())
+        .and_then(create_row);
+
+    // POST /tasks/:id/status - change status, respond with the updated row
+    let status = warp::path!("tasks" / i64 / "status")
+        .and(warp::post())
+        .and(rate_limited(
+            with_session_auth(jwt_secret.clone()),
+            limiter.clone(),
+        ));
+    let status = require_ownership(status, store.clone())
+        .and(warp::body::content_length_limit(body_limit))
+        .and(warp::body::form())
+        .and(with_pool.clone())
+        .and(with_events.clone())
+        .and_then(update_status);
+
+    // DELETE /tasks/:id - delete, respond with nothing so the row disappears
+    let delete = warp::path!("tasks" / i64)
+        .and(warp::delete())
+        .and(rate_limited(with_session_auth(jwt_secret), limiter));
+    let delete = require_ownership(delete, store)
+        .and(with_pool)
+        .and(with_events)
+        .and_then(delete_row);
+
+    page.or(create).or(status).or(delete)
+}
+
+#[instrument(skip(pool))]
+async fn task_page(user_id: i64, pool: DbPool) -> Result<impl Reply, Rejection> {
+    let tasks = TaskRepository::list_summaries(&pool, user_id)
+        .await
+        .map_err(reject)?;
+
+    render(&TasksTemplate { tasks })
+}
+
+#[instrument(skip(form, pool, events))]
+async fn create_row(
+    user_id: i64,
+    form: NewTaskForm,
+    pool: DbPool,
+    events: TaskEvents,
+) -> Result<impl Reply, Rejection> {
+    let task = CreateTask {
+        title: form.title,
+        description: form.description,
+        status: TaskStatus::Todo,
+        priority: form.priority,
+        due_date: None,
+        user_id,
+        recurrence: None,
+        estimated_minutes: None,
+        actual_minutes: None,
+        project_id: None,
+        parent_id: None,
+    };
+    task.validate_new()
+        .map_err(|errors| reject(errors.into()))?;
+    let task = TaskRepository::create(&pool, task).await.map_err(reject)?;
+    events.publish(TaskEvent::changed(TaskEventKind::Created, &task));
+
+    render(&TaskRowTemplate { task })
+}
+
+#[instrument(skip(form, pool, events))]
+async fn update_status(
+    id: i64,
+    user_id: i64,
+    form: StatusForm,
+    pool: DbPool,
+    events: TaskEvents,
+) -> Result<impl Reply, Rejection> {
+    let update = UpdateTask::builder().status(form.status).build();
+    let task = TaskRepository::update(&pool, id, user_id, update)
+        .await
+        .map_err(reject)?;
+    events.publish(TaskEvent::changed(TaskEventKind::Updated, &task));
+
+    render(&TaskRowTemplate { task })
+}
+
+#[instrument(skip(pool, events))]
+async fn delete_row(
+    id: i64,
+    user_id: i64,
+    pool: DbPool,
+    events: TaskEvents,
+) -> Result<impl Reply, Rejection> {
+    TaskRepository::delete(&pool, id, user_id)
+        .await
+        .map_err(reject)?;
+    events.publish(TaskEvent::deleted(user_id, id));
+
+    // HTMX replaces the row with this empty body
+    Ok(warp::reply::html(String::new()))
+}
+
+/// Render a template into an HTML reply.
+pub(crate) fn render<T: Template>(template: &T) -> Result<warp::reply::Html<String>, Rejection> {
+    template
+        .render()
+        .map(warp::reply::html)
+        .map_err(|e| reject(AppError::Internal(format!("Template error: {}", e))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::handle_rejection;
+    use chrono::Duration;
+    use shared::auth::issue_token;
+    use shared::constants::MAX_BODY_BYTES;
+    use shared::db::{create_test_pool, create_test_user};
+    use warp::http::StatusCode;
+
+    fn summary(id: i64, title: &str) -> TaskSummary {
+        TaskSummary {
+            id,
+            title: title.to_string(),
+            status: TaskStatus::Todo,
+            priority: TaskPriority::High,
+            due_date: None,
+        }
+    }
+
+    #[test]
+    fn test_list_template_renders_every_task() {
+        let html = TasksTemplate {
+            tasks: vec![summary(1, "Buy milk"), summary(2, "Walk <dog>")],
+        }
+        .render()
+        .unwrap();
+
+        assert!(html.contains("Buy milk"));
+        // Titles are HTML-escaped
+        assert!(html.contains("Walk &lt;dog&gt;"));
+        assert!(html.contains(r#"id="task-2""#));
+    }
+
+    #[tokio::test]
+    async fn test_create_returns_row_fragment() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let token = issue_token(user_id, "secret", Duration::hours(1)).unwrap();
+        let ui = ui_routes(
+            pool,
+            Arc::from("secret"),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/tasks")
+            .header("cookie", format!("token={}", token))
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("title=Water+plants&priority=urgent")
+            .reply(&ui)
+            .await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = std::str::from_utf8(res.body()).unwrap();
+        assert!(body.trim_start().starts_with("<tr"));
+        assert!(body.contains("Water plants"));
+        assert!(body.contains("urgent"));
+        assert!(!body.contains("<html"));
+    }
+
+    #[tokio::test]
+    async fn test_markup_is_stored_as_typed_and_escaped_on_render() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let token = issue_token(user_id, "secret", Duration::hours(1)).unwrap();
+        let ui = ui_routes(
+            pool.clone(),
+            Arc::from("secret"),
+            RateLimiter::per_minute(100),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/tasks")
+            .header("cookie", format!("token={}", token))
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("title=%3Cscript%3Ealert(1)%3C%2Fscript%3E+x+%3C+y")
+            .reply(&ui)
+            .await;
+
+        let body = std::str::from_utf8(res.body()).unwrap();
+        assert!(!body.contains("<script>"));
+        assert!(body.contains("&lt;script&gt;alert(1)&lt;/script&gt; x &lt; y"));
+        let stored = TaskRepository::find_by_user(&pool, user_id).await.unwrap();
+        assert_eq!(stored[0].title, "<script>alert(1)</script> x < y");
+    }
+
+    #[tokio::test]
+    async fn test_ui_and_api_share_a_users_rate_limit() {
+        let pool = create_test_pool().await.unwrap();
+        let user_id = create_test_user(&pool, "alice").await.unwrap();
+        let token = issue_token(user_id, "secret", Duration::hours(1)).unwrap();
+        let limiter = RateLimiter::per_minute(1);
+        let ui = ui_routes(
+            pool.clone(),
+            Arc::from("secret"),
+            limiter.clone(),
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+        let api = crate::routes::task_routes(
+            pool,
+            Arc::from("secret"),
+            limiter,
+            TaskEvents::new(),
+            MAX_BODY_BYTES,
+        )
+        .recover(handle_rejection);
+
+        let res = warp::test::request()
+            .path("/tasks")
+            .header("cookie", format!("token={}", token))
+            .reply(&ui)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .path("/api/tasks")
+            .header("authorization", format!("Bearer {}", token))
+            .reply(&api)
+            .await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+}
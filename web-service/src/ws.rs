@@ -0,0 +1,328 @@
+// web-service/src/ws.rs
+// Two-way task sync over a WebSocket: change events out, commands in
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use shared::{
+    AppError, AppResult, CreateTask, DbPool, ErrorResponse, SqlxTaskRepository, Task,
+    TaskRepository, UpdateTask,
+};
+use tokio_stream::wrappers::BroadcastStream;
+use tracing::{debug, error, instrument};
+use warp::ws::{Message, WebSocket};
+
+use crate::auth::load_owned_task;
+use crate::events::{TaskEvent, TaskEventKind, TaskEvents};
+use crate::rate_limit::{ClientKey, RateLimiter};
+
+/// A JSON text message from the client, tagged by `type`, e.g.
+/// `{"type": "create", "task": {...}}` or `{"type": "delete", "id": 3}`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Command {
+    Create { task: CreateTask },
+    Update { id: i64, changes: UpdateTask },
+    Delete { id: i64 },
+}
+
+/// A JSON text message to the client, tagged by `type`.
+///
+/// Commands are answered with `created`/`updated`/`deleted` or `error`;
+/// `event` messages carry changes made anywhere (this socket included).
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Created { task: Task },
+    Updated { task: Task },
+    Deleted { id: i64 },
+    Event(TaskEvent),
+    Error(ErrorResponse),
+}
+
+impl ServerMessage {
+    fn error(err: &AppError) -> Self {
+        let (status, body) = err.to_response();
+        if status.is_server_error() {
+            error!("WebSocket command failed: {}", err);
+        }
+        ServerMessage::Error(body)
+    }
+
+    fn to_message(&self) -> Message {
+        // Every variant is plain data, so serializing can't fail
+        Message::text(serde_json::to_string(self).unwrap_or_default())
+    }
+}
+
+/// Run one client's socket until either side closes it.
+///
+/// The user's task events are forwarded as they happen, while incoming
+/// commands are run against the repository one at a time. Each command
+/// takes a token from the user's `limiter` bucket, like a REST request. A
+/// malformed, failing, or over-limit command gets an `error` message back;
+/// the socket stays open.
+#[instrument(skip(socket, pool, events, limiter))]
+pub async fn session(
+    socket: WebSocket,
+    user_id: i64,
+    pool: DbPool,
+    events: TaskEvents,
+    limiter: RateLimiter,
+) {
+    let (mut outgoing, mut incoming) = socket.split();
+    let mut changes = BroadcastStream::new(events.subscribe());
+
+    loop {
+        let reply = tokio::select! {
+            message = incoming.next() => match message {
+                Some(Ok(message)) if message.is_text() => {
+                    let text = message.to_str().unwrap_or_default();
+                    reply_to(text, user_id, &pool, &events, &limiter).await
+                }
+                Some(Ok(message)) if message.is_binary() => ServerMessage::Error(
+                    ErrorResponse::new("bad_request", "Commands must be JSON text messages"),
+                ),
+                // Pings and pongs are answered by warp itself
+                Some(Ok(message)) if !message.is_close() => continue,
+                _ => break,
+            },
+            event = changes.next() => match event {
+                Some(Ok(event)) if event.user_id == user_id => ServerMessage::Event(event),
+                // Another user's change, or events this socket lagged behind on
+                Some(_) => continue,
+                None => break,
+            },
+        };
+
+        if outgoing.send(reply.to_message()).await.is_err() {
+            break;
+        }
+    }
+
+    debug!("WebSocket closed");
+}
+
+/// Answer one text message from `user_id`: take a token from their
+/// bucket, then parse and run the command.
+async fn reply_to(
+    text: &str,
+    user_id: i64,
+    pool: &DbPool,
+    events: &TaskEvents,
+    limiter: &RateLimiter,
+) -> ServerMessage {
+    if let Err(err) = limiter.take(ClientKey::User(user_id)) {
+        return ServerMessage::error(&err);
+    }
+
+    match serde_json::from_str::<Command>(text) {
+        Ok(command) => run_command(command, user_id, pool, events)
+            .await
+            .unwrap_or_else(|err| ServerMessage::error(&err)),
+        Err(err) => ServerMessage::Error(ErrorResponse::new(
+            "bad_request",
+            format!("Invalid command: {}", err),
+        )),
+    }
+}
+
+/// Apply one command as `user_id`, publishing the change like the REST API.
+async fn run_command(
+    command: Command,
+    user_id: i64,
+    pool: &DbPool,
+    events: &TaskEvents,
+) -> AppResult<ServerMessage> {
+    match command {
+        Command::Create { mut task } => {
+            // Never trust an owner supplied in the message
+            task.user_id = user_id;
+            task.validate_new()?;
+            let task = TaskRepository::create(pool, task).await?;
+            events.publish(TaskEvent::changed(TaskEventKind::Created, &task));
+            Ok(ServerMessage::Created { task })
+        }
+        Command::Update { id, changes } => {
+            load_owned_task(&SqlxTaskRepository::new(pool.clone()), id, user_id).await?;
+            let task = TaskRepository::update(pool, id, user_id, changes).await?;
+            events.publish(TaskEvent::changed(TaskEventKind::Updated, &task));
+            Ok(ServerMessage::Updated { task })
+        }
+        Command::Delete { id } => {
+            load_owned_task(&SqlxTaskRepository::new(pool.clone()), id, user_id).await?;
+            TaskRepository::delete(pool, id, user_id).await?;
+            events.publish(TaskEvent::deleted(user_id, id));
+            Ok(ServerMessage::Deleted { id })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limit::RateLimiter;
+    use crate::routes::websocket;
+    use chrono::Duration;
+    use serde_json::Value;
+    use shared::auth::issue_token;
+    use shared::db::{create_test_pool, create_test_task, create_test_user};
+    use std::sync::Arc;
+    use warp::test::WsClient;
+
+    const SECRET: &str = "test-secret";
+
+    /// Receive messages until one of the given `type` arrives.
+    async fn recv_type(client: &mut WsClient, kind: &str) -> Value {
+        loop {
+            let message = client.recv().await.unwrap();
+            let value: Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+            if value["type"] == kind {
+                return value;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_message_persists_and_echoes_task() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let token = issue_token(alice, SECRET, Duration::hours(1)).unwrap();
+        let route = websocket(
+            pool.clone(),
+            Arc::from(SECRET),
+            TaskEvents::new(),
+            RateLimiter::per_minute(100),
+        );
+
+        let mut client = warp::test::ws()
+            .path(&format!("/ws?token={}", token))
+            .handshake(route)
+            .await
+            .unwrap();
+
+        // Malformed input is answered with an error, and the socket survives
+        client.send_text("{not json").await;
+        let error = recv_type(&mut client, "error").await;
+        assert_eq!(error["code"], "bad_request");
+
+        client
+            .send_text(
+                serde_json::json!({
+                    "type": "create",
+                    "task": {
+                        "title": "Over the wire",
+                        "description": "",
+                        "status": "todo",
+                        "priority": "high",
+                        "due_date": null,
+                        "user_id": 9999
+                    }
+                })
+                .to_string(),
+            )
+            .await;
+        let created = recv_type(&mut client, "created").await;
+        let task: Task = serde_json::from_value(created["task"].clone()).unwrap();
+
+        assert_eq!(task.title, "Over the wire");
+        assert_eq!(task.user_id, alice);
+        assert_eq!(
+            TaskRepository::find_by_id(&pool, task.id).await.unwrap().id,
+            task.id
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handshake_requires_token() {
+        let pool = create_test_pool().await.unwrap();
+        let route = websocket(
+            pool,
+            Arc::from(SECRET),
+            TaskEvents::new(),
+            RateLimiter::per_minute(100),
+        );
+
+        for path in ["/ws", "/ws?token=garbage"] {
+            assert!(warp::test::ws()
+                .path(path)
+                .handshake(route.clone())
+                .await
+                .is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_message_is_validated_and_rate_limited() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let token = issue_token(alice, SECRET, Duration::hours(1)).unwrap();
+        let route = websocket(
+            pool.clone(),
+            Arc::from(SECRET),
+            TaskEvents::new(),
+            RateLimiter::per_minute(2),
+        );
+        let mut client = warp::test::ws()
+            .path(&format!("/ws?token={}", token))
+            .handshake(route)
+            .await
+            .unwrap();
+        let blank = serde_json::json!({
+            "type": "create",
+            "task": {"title": " ", "description": "", "status": "todo", "priority": "low", "due_date": null}
+        })
+        .to_string();
+
+        // Validated like POST /api/tasks, with every bad field listed
+        client.send_text(blank.clone()).await;
+        let error = recv_type(&mut client, "error").await;
+        assert_eq!(error["code"], "validation");
+        assert_eq!(error["fields"]["title"], "Title cannot be empty");
+
+        client.send_text(blank.clone()).await;
+        recv_type(&mut client, "error").await;
+        // The third command in a minute is over the limit of two
+        client.send_text(blank).await;
+        let error = recv_type(&mut client, "error").await;
+        assert_eq!(error["code"], "rate_limited");
+        assert_eq!(
+            TaskRepository::count_by_user(&pool, alice).await.unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commands_on_other_users_tasks_are_forbidden() {
+        let pool = create_test_pool().await.unwrap();
+        let alice = create_test_user(&pool, "alice").await.unwrap();
+        let bob = create_test_user(&pool, "bob").await.unwrap();
+        let bobs = create_test_task(&pool, bob, "Bob's").await.unwrap();
+        let token = issue_token(alice, SECRET, Duration::hours(1)).unwrap();
+        let route = websocket(
+            pool.clone(),
+            Arc::from(SECRET),
+            TaskEvents::new(),
+            RateLimiter::per_minute(100),
+        );
+        let mut client = warp::test::ws()
+            .path(&format!("/ws?token={}", token))
+            .handshake(route)
+            .await
+            .unwrap();
+
+        // Same answers as the REST routes: 403 for Bob's task, 404 for none
+        client
+            .send_text(serde_json::json!({"type": "delete", "id": bobs.id}).to_string())
+            .await;
+        let error = recv_type(&mut client, "error").await;
+        assert_eq!(error["code"], "forbidden");
+
+        client
+            .send_text(serde_json::json!({"type": "delete", "id": 9999}).to_string())
+            .await;
+        let error = recv_type(&mut client, "error").await;
+        assert_eq!(error["code"], "task_not_found");
+
+        assert!(TaskRepository::find_by_id(&pool, bobs.id).await.is_ok());
+    }
+}